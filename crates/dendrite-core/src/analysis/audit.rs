@@ -1,7 +1,21 @@
-use crate::mutation::model::{EditPlan, MutationKind};
+use crate::identity::IdentityRegistry;
+use crate::model::{Note, NoteId, NoteKey};
+use crate::mutation::model::{Diagnostic, DiagnosticSeverity, EditPlan, MutationKind};
 use crate::semantic::SemanticModel;
 use crate::slugify_heading;
 use crate::store::Store;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Audit result for a single note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteAudit {
+    pub path: PathBuf,
+    /// Changes whenever the note, one of its link targets or its alias and id warnings
+    /// change (see `audit_result_id`)
+    pub result_id: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
 
 /// Audit the entire workspace for reference graph health.
 ///
@@ -13,113 +27,369 @@ pub fn calculate_audit_diagnostics(
     store: &Store,
     model: &dyn SemanticModel,
 ) -> crate::mutation::model::EditPlan {
-    use crate::mutation::model::{Diagnostic, DiagnosticSeverity};
-    let mut diagnostics = Vec::new();
+    let diagnostics = store
+        .all_notes()
+        .flat_map(|note| audit_note(store, model, note))
+        .collect();
 
-    let audited_kinds = model.audited_link_kinds();
+    EditPlan {
+        mutation_kind: MutationKind::WorkspaceAudit,
+        edits: vec![],
+        preconditions: vec![],
+        diagnostics,
+        reversible: false,
+    }
+}
 
-    for note in store.all_notes() {
-        // ... (existing read content code)
-        let uri = note.path.as_ref().map(|p| p.to_string_lossy().to_string());
+/// Audit the outgoing links of a single note.
+fn audit_note(store: &Store, model: &dyn SemanticModel, note: &Note) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
 
-        for link in &note.links {
-            // Check if model wants to audit this link kind
-            if !audited_kinds.contains(&link.kind) {
-                continue;
-            }
-            let lower_target = link.raw_target.to_lowercase();
-            if lower_target.starts_with("http://")
-                || lower_target.starts_with("https://")
-                || lower_target.starts_with("mailto:")
-            {
-                continue;
-            }
+    let audited_kinds = model.audited_link_kinds();
+    let uri = note.path.as_ref().map(|p| p.to_string_lossy().to_string());
 
-            let mut is_broken = false;
+    for link in &note.links {
+        // Check if model wants to audit this link kind
+        if !audited_kinds.contains(&link.kind) {
+            continue;
+        }
+        let lower_target = link.raw_target.to_lowercase();
+        if lower_target.starts_with("http://")
+            || lower_target.starts_with("https://")
+            || lower_target.starts_with("mailto:")
+        {
+            continue;
+        }
 
-            let target_note = store.get_note(&link.target);
+        let mut is_broken = false;
 
-            if target_note.is_none() {
-                diagnostics.push(Diagnostic {
-                    severity: DiagnosticSeverity::Error,
-                    message: "Broken link: Target note not found.".to_string(),
-                    uri: uri.clone(),
-                    range: Some(link.range),
-                });
-                is_broken = true;
-            }
+        let target_note = store.get_note(&link.target);
 
-            // 2. Anchor Validity Check (only if link is not broken)
-            if !is_broken {
-                if let (Some(target), Some(anchor)) = (target_note, &link.anchor) {
-                    let mut found = false;
+        if target_note.is_none() {
+            diagnostics.push(Diagnostic {
+                severity: DiagnosticSeverity::Error,
+                message: "Broken link: Target note not found.".to_string(),
+                uri: uri.clone(),
+                range: Some(link.range),
+            });
+            is_broken = true;
+        }
 
-                    // Reserved anchors (always valid)
-                    if anchor == "^begin" || anchor == "^end" {
+        // 2. Anchor Validity Check (only if link is not broken)
+        if !is_broken {
+            if let (Some(target), Some(anchor)) = (target_note, &link.anchor) {
+                let mut found = false;
+
+                // Reserved anchors (always valid)
+                if anchor == "^begin" || anchor == "^end" {
+                    found = true;
+                } else if let Some(stripped) = anchor.strip_prefix('^') {
+                    // Block anchor - strip ^ prefix before comparing
+                    if target.blocks.iter().any(|b| b.id == stripped) {
                         found = true;
-                    } else if let Some(stripped) = anchor.strip_prefix('^') {
-                        // Block anchor - strip ^ prefix before comparing
-                        if target.blocks.iter().any(|b| b.id == stripped) {
-                            found = true;
-                        }
-                    } else {
-                        // Heading anchor - use slugified comparison
-                        if target
-                            .headings
-                            .iter()
-                            .any(|h| slugify_heading(&h.text) == *anchor)
-                        {
-                            found = true;
-                        }
                     }
-
-                    if !found {
-                        diagnostics.push(Diagnostic {
-                            severity: DiagnosticSeverity::Error,
-                            message: format!(
-                                "Invalid anchor: '{}' not found in target note.",
-                                anchor
-                            ),
-                            uri: uri.clone(),
-                            range: Some(link.range),
-                        });
+                } else {
+                    // Heading anchor - use slugified comparison
+                    if target
+                        .headings
+                        .iter()
+                        .any(|h| slugify_heading(&h.text) == *anchor)
+                    {
+                        found = true;
                     }
                 }
+
+                if !found {
+                    diagnostics.push(Diagnostic {
+                        severity: DiagnosticSeverity::Error,
+                        message: format!("Invalid anchor: '{}' not found in target note.", anchor),
+                        uri: uri.clone(),
+                        range: Some(link.range),
+                    });
+                }
             }
+        }
+
+        // 3. Model-strict syntax validation (e.g. Dendron bare anchors)
+        // Bare anchor: [[#foo]] is VALID as self-reference
+        // Invalid: bare anchor without target in non-self-reference context
+        if model.id().0 == "Dendron" && link.raw_target.starts_with('#') && link.target != note.id {
+            diagnostics.push(Diagnostic {
+                severity: DiagnosticSeverity::Error,
+                message: format!(
+                    "Dendron strictly forbids bare anchor links like '{}'. Use '[[note#anchor]]'.",
+                    link.raw_target
+                ),
+                uri: uri.clone(),
+                range: Some(link.range),
+            });
+        }
+    }
 
-            // 3. Model-strict syntax validation (e.g. Dendron bare anchors)
-            // Bare anchor: [[#foo]] is VALID as self-reference
-            // Invalid: bare anchor without target in non-self-reference context
-            if model.id().0 == "Dendron"
-                && link.raw_target.starts_with('#')
-                && link.target != note.id
-            {
-                diagnostics.push(Diagnostic {
-                    severity: DiagnosticSeverity::Error,
-                    message: format!(
-                        "Dendron strictly forbids bare anchor links like '{}'. Use '[[note#anchor]]'.",
-                        link.raw_target
-                    ),
-                    uri: uri.clone(),
-                    range: Some(link.range),
-                });
+    diagnostics
+}
+
+/// Stable identifier of a note's audit result.
+///
+/// Derived from the note's digest plus the digests of its link targets (or their absence),
+/// which is everything `audit_note` looks at, and from the alias and frontmatter id
+/// warnings, which depend on other notes' keys. Equal IDs mean an unchanged report.
+fn audit_result_id(store: &Store, note: &Note, shared_name_diagnostics: &[Diagnostic]) -> String {
+    let mut input = note.digest.clone().unwrap_or_default();
+    for link in &note.links {
+        input.push('\n');
+        match store.get_note(&link.target) {
+            Some(target) => input.push_str(target.digest.as_deref().unwrap_or("?")),
+            None => input.push('-'),
+        }
+    }
+    for diagnostic in shared_name_diagnostics {
+        input.push('\n');
+        input.push_str(&diagnostic.message);
+    }
+    crate::parser::compute_digest(&input)
+}
+
+/// Workspace-wide lookups behind the alias and frontmatter id checks of single notes.
+struct SharedNames<'a> {
+    identity: &'a IdentityRegistry,
+    /// Lowercased key -> key, for every note with a file
+    keys: HashMap<String, NoteKey>,
+    /// Note -> the frontmatter id it shares with other notes, and all of them
+    shared_ids: HashMap<NoteId, (String, Vec<NoteId>)>,
+}
+
+impl<'a> SharedNames<'a> {
+    fn new(store: &Store, identity: &'a IdentityRegistry) -> Self {
+        let keys = notes_by_key(store, identity)
+            .into_iter()
+            .map(|(key, _)| (key.to_lowercase(), key))
+            .collect();
+        let mut shared_ids = HashMap::new();
+        for (id, note_ids) in duplicate_frontmatter_ids(store, identity) {
+            for note_id in &note_ids {
+                shared_ids.insert(note_id.clone(), (id.clone(), note_ids.clone()));
             }
         }
+        Self {
+            identity,
+            keys,
+            shared_ids,
+        }
     }
 
-    EditPlan {
-        mutation_kind: MutationKind::WorkspaceAudit,
-        edits: vec![],
-        preconditions: vec![],
+    /// Alias collision and duplicate frontmatter id warnings of `note`
+    fn diagnostics(&self, store: &Store, note: &Note) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if let (Some(key), Some(_)) = (self.identity.key_of(&note.id), &note.path) {
+            diagnostics.extend(alias_diagnostics(self.identity, &self.keys, &key, note));
+        }
+        if let Some((id, note_ids)) = self.shared_ids.get(&note.id) {
+            diagnostics.push(shared_id_diagnostic(
+                store,
+                self.identity,
+                id,
+                note_ids,
+                &note.id,
+            ));
+        }
+        diagnostics
+    }
+}
+
+/// Audit a single note, returning its diagnostics together with its result ID.
+pub fn calculate_note_audit(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+    note: &Note,
+) -> Option<NoteAudit> {
+    note_audit(store, model, &SharedNames::new(store, identity), note)
+}
+
+/// Audit every note with a file, one report per note (including notes without issues).
+pub fn calculate_note_audits(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+) -> Vec<NoteAudit> {
+    let shared_names = SharedNames::new(store, identity);
+    store
+        .all_notes()
+        .filter_map(|note| note_audit(store, model, &shared_names, note))
+        .collect()
+}
+
+fn note_audit(
+    store: &Store,
+    model: &dyn SemanticModel,
+    shared_names: &SharedNames,
+    note: &Note,
+) -> Option<NoteAudit> {
+    let path = note.path.clone()?;
+    let shared_name_diagnostics = shared_names.diagnostics(store, note);
+    let mut diagnostics = audit_note(store, model, note);
+    diagnostics.extend(shared_name_diagnostics.iter().cloned());
+    Some(NoteAudit {
+        path,
+        result_id: audit_result_id(store, note, &shared_name_diagnostics),
         diagnostics,
-        reversible: false,
+    })
+}
+
+/// Notes with a file, by key.
+fn notes_by_key<'a>(store: &'a Store, identity: &IdentityRegistry) -> Vec<(NoteKey, &'a Note)> {
+    let mut notes: Vec<(NoteKey, &Note)> = store
+        .all_notes()
+        .filter(|note| note.path.is_some())
+        .filter_map(|note| Some((identity.key_of(&note.id)?, note)))
+        .collect();
+    notes.sort_by(|a, b| a.0.cmp(&b.0));
+    notes
+}
+
+/// Frontmatter aliases that do not resolve to the note declaring them.
+///
+/// An alias matching another note's key always resolves to that note; an alias
+/// declared by several notes resolves to the one with the smallest key.
+pub fn alias_collision_diagnostics(store: &Store, identity: &IdentityRegistry) -> Vec<Diagnostic> {
+    let notes = notes_by_key(store, identity);
+    let keys: HashMap<String, NoteKey> = notes
+        .iter()
+        .map(|(key, _)| (key.to_lowercase(), key.clone()))
+        .collect();
+    notes
+        .iter()
+        .flat_map(|(key, note)| alias_diagnostics(identity, &keys, key, note))
+        .collect()
+}
+
+/// The alias collisions of one note, given the lowercased keys of all notes.
+fn alias_diagnostics(
+    identity: &IdentityRegistry,
+    keys: &HashMap<String, NoteKey>,
+    key: &NoteKey,
+    note: &Note,
+) -> Vec<Diagnostic> {
+    let uri = note.path.as_ref().map(|p| p.to_string_lossy().to_string());
+    let mut seen = HashSet::new();
+    let mut diagnostics = Vec::new();
+    for alias in note.aliases() {
+        let alias = alias.trim();
+        if alias.is_empty() || !seen.insert(alias.to_lowercase()) {
+            continue;
+        }
+
+        let message = if let Some(other) = keys
+            .get(&alias.to_lowercase())
+            .filter(|other| *other != key)
+        {
+            format!(
+                "Alias '{}' matches the key of note '{}', which takes precedence.",
+                alias, other
+            )
+        } else {
+            let mut others: Vec<NoteKey> = identity
+                .alias_owners(alias)
+                .iter()
+                .filter(|id| **id != note.id)
+                .filter_map(|id| identity.key_of(id))
+                .collect();
+            if others.is_empty() {
+                continue;
+            }
+            others.sort();
+            let owner = identity
+                .alias_owner(alias)
+                .and_then(|id| identity.key_of(&id))
+                .unwrap_or_default();
+            format!(
+                "Alias '{}' is also declared by '{}'; links to it resolve to '{}'.",
+                alias,
+                others.join("', '"),
+                owner
+            )
+        };
+        diagnostics.push(Diagnostic {
+            severity: DiagnosticSeverity::Warning,
+            message,
+            uri: uri.clone(),
+            range: None,
+        });
+    }
+    diagnostics
+}
+
+/// Notes sharing a frontmatter `id`, e.g. after copying a file, grouped by id.
+///
+/// In each group the note holding the id's stable NoteId comes first (it keeps
+/// the id), the others follow by key.
+pub(crate) fn duplicate_frontmatter_ids(
+    store: &Store,
+    identity: &IdentityRegistry,
+) -> Vec<(String, Vec<NoteId>)> {
+    let mut by_id: BTreeMap<&str, Vec<&Note>> = BTreeMap::new();
+    for note in store.all_notes().filter(|note| note.path.is_some()) {
+        if let Some(id) = note.frontmatter_id() {
+            by_id.entry(id).or_default().push(note);
+        }
+    }
+
+    by_id
+        .into_iter()
+        .filter(|(_, notes)| notes.len() > 1)
+        .map(|(id, notes)| {
+            let stable_id = NoteId::from_frontmatter_id(id);
+            let mut ids: Vec<NoteId> = notes.into_iter().map(|note| note.id.clone()).collect();
+            ids.sort_by_key(|note_id| (*note_id != stable_id, identity.key_of(note_id)));
+            (id.to_string(), ids)
+        })
+        .collect()
+}
+
+/// One warning per note whose frontmatter `id` is shared with other notes.
+pub fn duplicate_id_diagnostics(store: &Store, identity: &IdentityRegistry) -> Vec<Diagnostic> {
+    duplicate_frontmatter_ids(store, identity)
+        .iter()
+        .flat_map(|(id, note_ids)| {
+            note_ids
+                .iter()
+                .map(|note_id| shared_id_diagnostic(store, identity, id, note_ids, note_id))
+        })
+        .collect()
+}
+
+/// The warning for `note_id`, one of the notes sharing frontmatter id `id`.
+fn shared_id_diagnostic(
+    store: &Store,
+    identity: &IdentityRegistry,
+    id: &str,
+    note_ids: &[NoteId],
+    note_id: &NoteId,
+) -> Diagnostic {
+    let others: Vec<NoteKey> = note_ids
+        .iter()
+        .filter(|other| *other != note_id)
+        .filter_map(|other| identity.key_of(other))
+        .collect();
+    Diagnostic {
+        severity: DiagnosticSeverity::Warning,
+        message: format!(
+            "Frontmatter id '{}' is also used by '{}'.",
+            id,
+            others.join("', '")
+        ),
+        uri: store
+            .get_note(note_id)
+            .and_then(|note| note.path.as_ref())
+            .map(|p| p.to_string_lossy().to_string()),
+        range: None,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::identity::IdentityRegistry;
     use crate::model::{Heading, Link, LinkKind, Note, TextRange};
     use crate::semantic::DendronModel;
     use std::path::PathBuf;
@@ -141,6 +411,8 @@ mod tests {
 
         note_a.links.push(Link {
             target: id_missing.clone(),
+            target_key: "Missing".to_string(),
+            target_vault: None,
             raw_target: "Missing".to_string(),
             alias: None,
             anchor: None,
@@ -184,6 +456,8 @@ mod tests {
 
         note_a.links.push(Link {
             target: id_target.clone(),
+            target_key: "Target".to_string(),
+            target_vault: None,
             raw_target: "Target".to_string(),
             alias: None,
             anchor: Some("NonExistent".to_string()),
@@ -218,6 +492,8 @@ mod tests {
         // This should NOT trigger the bare anchor error
         note_a.links.push(Link {
             target: id_a.clone(), // Self-reference
+            target_key: "A".to_string(),
+            target_vault: None,
             raw_target: "#forbidden".to_string(),
             alias: None,
             anchor: Some("forbidden".to_string()),
@@ -237,4 +513,64 @@ mod tests {
             .iter()
             .all(|d| !d.message.contains("strictly forbids bare anchor")));
     }
+
+    #[test]
+    fn test_note_audit_result_id_tracks_link_targets() {
+        let mut store = Store::new();
+        let mut identity = IdentityRegistry::new();
+
+        let id_a = identity.get_or_create(&"A".to_string());
+        let id_b = identity.get_or_create(&"B".to_string());
+        let id_c = identity.get_or_create(&"C".to_string());
+
+        let mut note_a = Note {
+            id: id_a.clone(),
+            path: Some(PathBuf::from("A.md")),
+            digest: Some("a1".to_string()),
+            ..Default::default()
+        };
+        note_a.links.push(Link {
+            target: id_b.clone(),
+            target_key: "B".to_string(),
+            target_vault: None,
+            raw_target: "B".to_string(),
+            alias: None,
+            anchor: None,
+            range: TextRange::default(),
+            kind: LinkKind::WikiLink(crate::model::WikiLinkFormat::AliasFirst),
+        });
+        let note_c = Note {
+            id: id_c.clone(),
+            path: Some(PathBuf::from("C.md")),
+            digest: Some("c1".to_string()),
+            ..Default::default()
+        };
+        store.upsert_note(note_a.clone());
+        store.upsert_note(note_c);
+
+        let model = DendronModel::new(PathBuf::from("/test"));
+        let broken = calculate_note_audit(&store, &identity, &model, &note_a).unwrap();
+        assert_eq!(broken.diagnostics.len(), 1);
+
+        // Unrelated notes don't affect the result ID
+        store.upsert_note(Note {
+            id: id_c,
+            path: Some(PathBuf::from("C.md")),
+            digest: Some("c2".to_string()),
+            ..Default::default()
+        });
+        let unrelated = calculate_note_audit(&store, &identity, &model, &note_a).unwrap();
+        assert_eq!(unrelated.result_id, broken.result_id);
+
+        // Creating the link target does
+        store.upsert_note(Note {
+            id: id_b,
+            path: Some(PathBuf::from("B.md")),
+            digest: Some("b1".to_string()),
+            ..Default::default()
+        });
+        let fixed = calculate_note_audit(&store, &identity, &model, &note_a).unwrap();
+        assert_ne!(fixed.result_id, broken.result_id);
+        assert!(fixed.diagnostics.is_empty());
+    }
 }
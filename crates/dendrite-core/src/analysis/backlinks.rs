@@ -0,0 +1,267 @@
+use crate::identity::IdentityRegistry;
+use crate::line_map::LineMap;
+use crate::model::{Heading, NoteId, NoteKey, TextRange};
+use crate::mutation::model::ContentProvider;
+use crate::parser::markdown_options;
+use crate::semantic::SemanticModel;
+use crate::store::Store;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A single link pointing at a note, with where and how it is used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklinkContext {
+    pub source_key: NoteKey,
+    pub source_path: PathBuf,
+    pub source_title: Option<String>,
+    /// Range of the link in the source note
+    pub range: TextRange,
+    /// Heading or block anchor of the target the link points to
+    pub anchor: Option<String>,
+    /// The paragraph or list item containing the link (its line if unavailable)
+    pub context: String,
+    /// Headings the link sits under, outermost first
+    pub heading_path: Vec<String>,
+}
+
+/// Backlinks pointing at the same anchor of the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnchorGroup {
+    /// None for links to the note as a whole
+    pub anchor: Option<String>,
+    pub backlinks: Vec<BacklinkContext>,
+}
+
+/// Every link pointing at `target`, ordered by source key and position.
+///
+/// Context is taken from the current content of each source note.
+pub(crate) fn calculate_backlinks(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+    content_provider: &dyn ContentProvider,
+    target: &NoteId,
+) -> Vec<BacklinkContext> {
+    let mut backlinks: Vec<BacklinkContext> = Vec::new();
+    let mut current_source: Option<(&NoteId, Option<String>)> = None;
+
+    let mut refs: Vec<_> = store
+        .backlink_refs(target)
+        .filter_map(|(note, link)| Some((identity.key_of(&note.id)?, note, link)))
+        .collect();
+    refs.sort_by(|a, b| {
+        a.0.cmp(&b.0)
+            .then_with(|| a.2.range.start.line.cmp(&b.2.range.start.line))
+            .then_with(|| a.2.range.start.col.cmp(&b.2.range.start.col))
+    });
+
+    for (source_key, note, link) in refs {
+        let Some(path) = &note.path else {
+            continue;
+        };
+        // Read each source once
+        if current_source.as_ref().map(|(id, _)| *id) != Some(&note.id) {
+            current_source = Some((
+                &note.id,
+                content_provider.get_content(&path.to_string_lossy()),
+            ));
+        }
+        let content = current_source
+            .as_ref()
+            .and_then(|(_, content)| content.as_deref())
+            .unwrap_or_default();
+
+        backlinks.push(BacklinkContext {
+            source_key,
+            source_path: path.clone(),
+            source_title: note.title.clone(),
+            range: link.range,
+            anchor: link.anchor.clone(),
+            context: context_of(model, content, link.range),
+            heading_path: heading_path(&note.headings, link.range),
+        });
+    }
+
+    backlinks
+}
+
+/// Group backlinks by target anchor: whole-note links first, then anchors A-Z.
+pub fn group_by_anchor(backlinks: Vec<BacklinkContext>) -> Vec<AnchorGroup> {
+    let mut groups: Vec<AnchorGroup> = Vec::new();
+    for backlink in backlinks {
+        match groups.iter_mut().find(|g| g.anchor == backlink.anchor) {
+            Some(group) => group.backlinks.push(backlink),
+            None => groups.push(AnchorGroup {
+                anchor: backlink.anchor.clone(),
+                backlinks: vec![backlink],
+            }),
+        }
+    }
+    groups.sort_by(|a, b| a.anchor.cmp(&b.anchor));
+    groups
+}
+
+/// Text of the innermost paragraph, heading or list item containing `range`.
+fn context_of(model: &dyn SemanticModel, content: &str, range: TextRange) -> String {
+    let line_map = LineMap::new(content);
+    let Some(offset) = line_map.point_to_offset(content, range.start) else {
+        return String::new();
+    };
+
+    let (options, _) = markdown_options(&model.supported_link_kinds());
+    // (start, end) of candidate blocks, and the items still open
+    let mut blocks: Vec<(usize, usize)> = Vec::new();
+    let mut open_items: Vec<usize> = Vec::new();
+    let mut item_awaiting_text: Option<usize> = None;
+
+    for (event, event_range) in Parser::new_ext(content, options).into_offset_iter() {
+        // An item's text starts after its list marker
+        if let Some(index) = item_awaiting_text.take() {
+            blocks[index].0 = event_range.start;
+        }
+        match event {
+            Event::Start(Tag::Paragraph | Tag::Heading { .. }) => {
+                blocks.push((event_range.start, event_range.end));
+            }
+            Event::Start(Tag::Item) => {
+                open_items.push(blocks.len());
+                item_awaiting_text = Some(blocks.len());
+                blocks.push((event_range.start, event_range.end));
+            }
+            Event::End(TagEnd::Item) => {
+                open_items.pop();
+            }
+            // Nested lists are not part of their parent item's own text
+            Event::Start(Tag::List(_)) => {
+                if let Some(&index) = open_items.last() {
+                    blocks[index].1 = blocks[index].1.min(event_range.start);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let innermost = blocks
+        .into_iter()
+        .filter(|(start, end)| *start <= offset && offset < *end)
+        .min_by_key(|(start, end)| end - start);
+
+    let text = match innermost {
+        Some((start, end)) => &content[start..end],
+        // Links outside any block (e.g. in frontmatter): fall back to the line
+        None => {
+            let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = content[offset..]
+                .find('\n')
+                .map_or(content.len(), |i| offset + i);
+            &content[line_start..line_end]
+        }
+    };
+    text.trim().to_string()
+}
+
+/// Headings enclosing `range`, outermost first.
+fn heading_path(headings: &[Heading], range: TextRange) -> Vec<String> {
+    let mut path: Vec<&Heading> = Vec::new();
+    for heading in headings {
+        if heading.range.start.line > range.start.line {
+            break;
+        }
+        while path.last().is_some_and(|last| last.level >= heading.level) {
+            path.pop();
+        }
+        // A link inside the heading itself sits under its parents only
+        if heading.range.start.line == range.start.line {
+            break;
+        }
+        path.push(heading);
+    }
+    path.into_iter()
+        .map(|heading| heading.text.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::setup;
+    use crate::vfs::PhysicalFileSystem;
+    use crate::workspace::Indexer;
+
+    #[test]
+    fn test_backlinks_with_context() {
+        let (ws, provider) = setup(&[
+            ("target", "# Target\n## Setup\n## Usage"),
+            (
+                "a",
+                "# Guide\n## Install\nFirst read [[target#setup]]\nfor details.\n\n\
+                 - item with [[target]]\n  - nested child\n\n## Other\nAgain [[target#setup]].",
+            ),
+            ("b", "See [[target]] here."),
+        ]);
+
+        let backlinks = ws.backlinks_with_context(&provider, "target");
+        let summary: Vec<_> = backlinks
+            .iter()
+            .map(|b| {
+                (
+                    b.source_key.as_str(),
+                    b.anchor.as_deref(),
+                    b.context.as_str(),
+                    b.heading_path.join(" > "),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "a",
+                    Some("setup"),
+                    "First read [[target#setup]]\nfor details.",
+                    "Guide > Install".to_string()
+                ),
+                (
+                    "a",
+                    None,
+                    "item with [[target]]",
+                    "Guide > Install".to_string()
+                ),
+                (
+                    "a",
+                    Some("setup"),
+                    "Again [[target#setup]].",
+                    "Guide > Other".to_string()
+                ),
+                ("b", None, "See [[target]] here.", String::new()),
+            ]
+        );
+        assert_eq!(backlinks[0].range.start.line, 2);
+
+        let groups = group_by_anchor(backlinks);
+        let grouped: Vec<_> = groups
+            .iter()
+            .map(|g| (g.anchor.as_deref(), g.backlinks.len()))
+            .collect();
+        assert_eq!(grouped, vec![(None, 2), (Some("setup"), 2)]);
+    }
+
+    #[test]
+    fn test_backlinks_follow_reindexing() {
+        let (mut ws, mut provider) =
+            setup(&[("target", "# T"), ("a", "[[target]] and [[target]]")]);
+        assert_eq!(ws.backlinks_with_context(&provider, "target").len(), 2);
+
+        let content = "no more links";
+        provider
+            .files
+            .insert("/test/a.md".to_string(), content.to_string());
+        Indexer::new(&mut ws, &PhysicalFileSystem).update_content(
+            PathBuf::from("/test/a.md"),
+            content,
+            "main".to_string(),
+        );
+        assert!(ws.backlinks_with_context(&provider, "target").is_empty());
+    }
+}
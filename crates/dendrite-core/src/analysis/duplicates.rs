@@ -0,0 +1,278 @@
+use crate::identity::IdentityRegistry;
+use crate::model::NoteKey;
+use crate::mutation::model::{Diagnostic, DiagnosticSeverity};
+use crate::search::SearchIndex;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+/// Shared-content ratio above which two notes count as near-duplicates
+pub const DEFAULT_DUPLICATE_THRESHOLD: f64 = 0.8;
+
+/// Consecutive tokens per shingle
+const SHINGLE_SIZE: usize = 3;
+/// Notes with fewer tokens are too short to compare meaningfully
+const MIN_TOKENS: usize = 8;
+/// The MinHash signature is split into bands for locality-sensitive hashing.
+/// Notes agreeing on a whole band become candidates; with 16 bands of 4 rows,
+/// pairs sharing 50% of their shingles are found about two times in three,
+/// pairs sharing 80% almost always.
+const BANDS: usize = 16;
+const ROWS_PER_BAND: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateNote {
+    pub key: NoteKey,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicatePair {
+    pub a: NoteKey,
+    pub b: NoteKey,
+    /// Jaccard similarity of the two notes' shingle sets, in [0, 1]
+    pub shared_ratio: f64,
+}
+
+/// Notes connected by near-duplicate pairs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateCluster {
+    /// Sorted by key
+    pub notes: Vec<DuplicateNote>,
+    /// Lowest shared-content ratio among the cluster's pairs
+    pub shared_ratio: f64,
+    /// Pairs above the threshold, most similar first
+    pub pairs: Vec<DuplicatePair>,
+}
+
+/// Group notes whose bodies share at least `threshold` of their word shingles.
+///
+/// Candidates come from MinHash signatures over the search index's token
+/// streams; each candidate pair is then confirmed with its exact ratio.
+/// Frontmatter is not part of the comparison.
+pub(crate) fn calculate_duplicate_clusters(
+    store: &Store,
+    identity: &IdentityRegistry,
+    index: &SearchIndex,
+    threshold: f64,
+) -> Vec<DuplicateCluster> {
+    let mut notes: Vec<(DuplicateNote, HashSet<u64>)> = store
+        .all_notes()
+        .filter_map(|note| {
+            let tokens = index.doc_tokens(&note.id);
+            if tokens.len() < MIN_TOKENS {
+                return None;
+            }
+            let note = DuplicateNote {
+                key: identity.key_of(&note.id)?,
+                path: note.path.clone()?,
+            };
+            Some((note, tokens.windows(SHINGLE_SIZE).map(hash_of).collect()))
+        })
+        .collect();
+    notes.sort_by(|a, b| a.0.key.cmp(&b.0.key));
+
+    // Notes agreeing on a whole band of their signature
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for (i, (_, shingles)) in notes.iter().enumerate() {
+        for (band, rows) in signature(shingles).chunks(ROWS_PER_BAND).enumerate() {
+            buckets.entry((band, hash_of(rows))).or_default().push(i);
+        }
+    }
+    let mut candidates: BTreeSet<(usize, usize)> = BTreeSet::new();
+    for members in buckets.values() {
+        for (n, &i) in members.iter().enumerate() {
+            candidates.extend(members[n + 1..].iter().map(|&j| (i, j)));
+        }
+    }
+
+    let pairs: Vec<(usize, usize, f64)> = candidates
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let ratio = jaccard(&notes[i].1, &notes[j].1);
+            (ratio >= threshold).then_some((i, j, ratio))
+        })
+        .collect();
+
+    // Union-find over the confirmed pairs
+    let mut parent: Vec<usize> = (0..notes.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for &(i, j, _) in &pairs {
+        let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+        parent[root_i.max(root_j)] = root_i.min(root_j);
+    }
+
+    let mut groups: BTreeMap<usize, Vec<(usize, usize, f64)>> = BTreeMap::new();
+    for pair in pairs {
+        groups
+            .entry(find(&mut parent, pair.0))
+            .or_default()
+            .push(pair);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = groups
+        .into_values()
+        .map(|group| {
+            let members: BTreeSet<usize> = group.iter().flat_map(|&(i, j, _)| [i, j]).collect();
+            let mut pairs: Vec<DuplicatePair> = group
+                .iter()
+                .map(|&(i, j, ratio)| DuplicatePair {
+                    a: notes[i].0.key.clone(),
+                    b: notes[j].0.key.clone(),
+                    shared_ratio: ratio,
+                })
+                .collect();
+            // Stable sort keeps equally similar pairs in key order
+            pairs.sort_by(|x, y| y.shared_ratio.total_cmp(&x.shared_ratio));
+            DuplicateCluster {
+                notes: members.into_iter().map(|i| notes[i].0.clone()).collect(),
+                shared_ratio: pairs.last().map_or(0.0, |pair| pair.shared_ratio),
+                pairs,
+            }
+        })
+        .collect();
+    clusters.sort_by(|x, y| y.shared_ratio.total_cmp(&x.shared_ratio));
+    clusters
+}
+
+/// One audit warning per note and near-duplicate partner.
+pub fn duplicate_diagnostics(clusters: &[DuplicateCluster]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for cluster in clusters {
+        let paths: HashMap<&NoteKey, &PathBuf> = cluster
+            .notes
+            .iter()
+            .map(|note| (&note.key, &note.path))
+            .collect();
+        for pair in &cluster.pairs {
+            for (key, other) in [(&pair.a, &pair.b), (&pair.b, &pair.a)] {
+                diagnostics.push(Diagnostic {
+                    severity: DiagnosticSeverity::Warning,
+                    message: format!(
+                        "Near-duplicate of '{}' ({:.0}% shared content).",
+                        other,
+                        pair.shared_ratio * 100.0
+                    ),
+                    uri: paths
+                        .get(key)
+                        .map(|path| path.to_string_lossy().to_string()),
+                    range: None,
+                });
+            }
+        }
+    }
+    diagnostics
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// MinHash signature: the minimum of each of `BANDS * ROWS_PER_BAND` hash functions.
+fn signature(shingles: &HashSet<u64>) -> Vec<u64> {
+    (1..=(BANDS * ROWS_PER_BAND) as u64)
+        .map(|seed| {
+            let salt = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            shingles
+                .iter()
+                .map(|&shingle| mix(shingle ^ salt))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// SplitMix64 finalizer, turning a salted shingle into an independent hash
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+    use crate::workspace::Workspace;
+
+    fn setup(notes: &[(&str, &str)]) -> Workspace {
+        test_fixtures::setup(notes).0
+    }
+
+    const MEETING: &str = "Attendees were Alice Bob and Carol. We agreed to ship the \
+        release on Friday after the final review of the migration plan and the \
+        rollback procedure for the database cluster.";
+
+    #[test]
+    fn test_duplicate_clusters() {
+        let copy = format!("---\ntitle: Copy\n---\n{} Carol owns it.", MEETING);
+        let ws = setup(&[
+            ("meeting.2023.x", MEETING),
+            ("meeting.2023.x-copy", &copy),
+            ("meeting.2023.x-old", MEETING),
+            (
+                "other",
+                "Completely different notes about gardening tomatoes and peppers in the spring",
+            ),
+            ("short", "Attendees were Alice"),
+        ]);
+
+        let clusters = ws.duplicate_clusters(DEFAULT_DUPLICATE_THRESHOLD);
+        assert_eq!(clusters.len(), 1);
+        let keys: Vec<_> = clusters[0].notes.iter().map(|n| n.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "meeting.2023.x",
+                "meeting.2023.x-copy",
+                "meeting.2023.x-old"
+            ]
+        );
+        assert_eq!(clusters[0].pairs.len(), 3);
+        assert_eq!(clusters[0].pairs[0].shared_ratio, 1.0);
+        assert!(clusters[0].shared_ratio >= DEFAULT_DUPLICATE_THRESHOLD);
+        assert!(clusters[0].shared_ratio < 1.0);
+
+        assert!(ws.duplicate_clusters(0.99)[0].pairs.len() == 1);
+    }
+
+    #[test]
+    fn test_duplicates_in_workspace_audit() {
+        let ws = setup(&[("a", MEETING), ("b", MEETING)]);
+        let messages: Vec<_> = ws
+            .audit()
+            .diagnostics
+            .into_iter()
+            .map(|d| (d.uri.unwrap(), d.message))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "/test/a.md".to_string(),
+                    "Near-duplicate of 'b' (100% shared content).".to_string()
+                ),
+                (
+                    "/test/b.md".to_string(),
+                    "Near-duplicate of 'a' (100% shared content).".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use crate::identity::IdentityRegistry;
+use crate::model::{LinkKind, NoteKey};
+use crate::semantic::SemanticModel;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Graphviz
+    Dot,
+    GraphMl,
+    /// Node-link JSON (`{ nodes, links }`, as read by networkx, d3, ...)
+    #[default]
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Only export this note and its descendants
+    pub subtree: Option<NoteKey>,
+    /// Only export notes of this vault (ghost nodes are kept while they have exported descendants)
+    pub vault: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportEdgeKind {
+    Link,
+    Embed,
+    /// From a note to its hierarchy parent
+    HierarchyParent,
+}
+
+impl ExportEdgeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Link => "link",
+            Self::Embed => "embed",
+            Self::HierarchyParent => "hierarchy-parent",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct ExportNode {
+    id: NoteKey,
+    title: Option<String>,
+    vault: Option<String>,
+    tags: Vec<String>,
+    /// Hierarchy level without a file
+    ghost: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+struct ExportEdge {
+    source: NoteKey,
+    target: NoteKey,
+    kind: ExportEdgeKind,
+}
+
+#[derive(Serialize)]
+struct NodeLinkGraph {
+    directed: bool,
+    multigraph: bool,
+    nodes: Vec<ExportNode>,
+    links: Vec<ExportEdge>,
+}
+
+/// Serialize the note graph (notes, ghost nodes, links, embeds and hierarchy edges).
+pub fn export_graph(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+    options: &ExportOptions,
+) -> String {
+    let (nodes, edges) = collect_graph(store, identity, model, options);
+    match options.format {
+        ExportFormat::Dot => to_dot(&nodes, &edges),
+        ExportFormat::GraphMl => to_graphml(&nodes, &edges),
+        ExportFormat::Json => serde_json::to_string_pretty(&NodeLinkGraph {
+            directed: true,
+            multigraph: true,
+            nodes,
+            links: edges,
+        })
+        .unwrap_or_default(),
+    }
+}
+
+fn collect_graph(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+    options: &ExportOptions,
+) -> (Vec<ExportNode>, Vec<ExportEdge>) {
+    let in_subtree = |key: &NoteKey| match &options.subtree {
+        Some(root) => key == root || model.is_descendant(key, root),
+        None => true,
+    };
+
+    let mut notes = BTreeMap::new();
+    for note in store.all_notes() {
+        let Some(key) = identity.key_of(&note.id) else {
+            continue;
+        };
+        let in_vault = options
+            .vault
+            .as_ref()
+            .is_none_or(|vault| note.path.is_none() || note.vault_name == *vault);
+        if in_subtree(&key) && in_vault {
+            notes.insert(key, note);
+        }
+    }
+
+    // Ghost nodes only stay when something real hangs below them
+    let real_keys: Vec<&NoteKey> = notes
+        .iter()
+        .filter(|(_, note)| note.path.is_some())
+        .map(|(key, _)| key)
+        .collect();
+    let kept: BTreeSet<NoteKey> = notes
+        .iter()
+        .filter(|(key, note)| {
+            note.path.is_some() || real_keys.iter().any(|real| model.is_descendant(real, key))
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    notes.retain(|key, _| kept.contains(key));
+
+    let mut edges = BTreeSet::new();
+    for (key, note) in &notes {
+        for link in &note.links {
+            let Some(target) = identity.key_of(&link.target) else {
+                continue;
+            };
+            if !notes.contains_key(&target) {
+                continue;
+            }
+            let kind = match link.kind {
+                LinkKind::EmbeddedWikiLink(_) => ExportEdgeKind::Embed,
+                _ => ExportEdgeKind::Link,
+            };
+            edges.insert(ExportEdge {
+                source: key.clone(),
+                target,
+                kind,
+            });
+        }
+        if let Some(parent) = model.resolve_parent(key) {
+            if notes.contains_key(&parent) {
+                edges.insert(ExportEdge {
+                    source: key.clone(),
+                    target: parent,
+                    kind: ExportEdgeKind::HierarchyParent,
+                });
+            }
+        }
+    }
+
+    let nodes = notes
+        .into_iter()
+        .map(|(key, note)| ExportNode {
+            id: key,
+            title: note.title.clone(),
+            vault: note.path.as_ref().map(|_| note.vault_name.clone()),
+            tags: note.tags(),
+            ghost: note.path.is_none(),
+        })
+        .collect();
+
+    (nodes, edges.into_iter().collect())
+}
+
+fn to_dot(nodes: &[ExportNode], edges: &[ExportEdge]) -> String {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+
+    let mut out = String::from("digraph notes {\n");
+    for node in nodes {
+        let mut attrs = vec![format!(
+            "label={}",
+            quote(node.title.as_deref().unwrap_or(&node.id))
+        )];
+        if let Some(vault) = &node.vault {
+            attrs.push(format!("vault={}", quote(vault)));
+        }
+        if !node.tags.is_empty() {
+            attrs.push(format!("tags={}", quote(&node.tags.join(","))));
+        }
+        if node.ghost {
+            attrs.push("style=dashed".to_string());
+        }
+        let _ = writeln!(out, "  {} [{}];", quote(&node.id), attrs.join(", "));
+    }
+    for edge in edges {
+        let style = match edge.kind {
+            ExportEdgeKind::Link => "",
+            ExportEdgeKind::Embed => ", style=bold",
+            ExportEdgeKind::HierarchyParent => ", style=dotted",
+        };
+        let _ = writeln!(
+            out,
+            "  {} -> {} [kind={}{}];",
+            quote(&edge.source),
+            quote(&edge.target),
+            quote(edge.kind.as_str()),
+            style
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn to_graphml(nodes: &[ExportNode], edges: &[ExportEdge]) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+
+    let mut out = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+        "  <key id=\"vault\" for=\"node\" attr.name=\"vault\" attr.type=\"string\"/>\n",
+        "  <key id=\"tags\" for=\"node\" attr.name=\"tags\" attr.type=\"string\"/>\n",
+        "  <key id=\"ghost\" for=\"node\" attr.name=\"ghost\" attr.type=\"boolean\"/>\n",
+        "  <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+        "  <graph id=\"notes\" edgedefault=\"directed\">\n",
+    ));
+    for node in nodes {
+        let _ = writeln!(out, "    <node id=\"{}\">", escape(&node.id));
+        if let Some(title) = &node.title {
+            let _ = writeln!(out, "      <data key=\"title\">{}</data>", escape(title));
+        }
+        if let Some(vault) = &node.vault {
+            let _ = writeln!(out, "      <data key=\"vault\">{}</data>", escape(vault));
+        }
+        if !node.tags.is_empty() {
+            let _ = writeln!(
+                out,
+                "      <data key=\"tags\">{}</data>",
+                escape(&node.tags.join(","))
+            );
+        }
+        let _ = writeln!(out, "      <data key=\"ghost\">{}</data>", node.ghost);
+        out.push_str("    </node>\n");
+    }
+    for edge in edges {
+        let _ = writeln!(
+            out,
+            "    <edge source=\"{}\" target=\"{}\">\n      <data key=\"kind\">{}</data>\n    </edge>",
+            escape(&edge.source),
+            escape(&edge.target),
+            edge.kind.as_str()
+        );
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DendriteConfig;
+    use crate::test_fixtures::{dendron_workspace, test_path, MockProvider};
+    use crate::workspace::Workspace;
+
+    fn setup() -> Workspace {
+        let mut ws = dendron_workspace(DendriteConfig::default());
+        let mut provider = MockProvider::default();
+        for (key, content, vault) in [
+            (
+                "proj.a",
+                "---\ntitle: \"A \\\"quoted\\\"\"\ntags: [x, y]\n---\n[[proj.b]] ![[other]]",
+                "main",
+            ),
+            ("proj.b", "# B\n[[proj.a]]", "main"),
+            ("other", "# Other", "side"),
+        ] {
+            provider.index(&mut ws, test_path(&format!("{}.md", key)), content, vault);
+        }
+        ws.fill_missing_hierarchy_levels();
+        ws
+    }
+
+    #[test]
+    fn test_export_json() {
+        let ws = setup();
+        let json: serde_json::Value =
+            serde_json::from_str(&ws.export_graph(&ExportOptions::default())).unwrap();
+
+        let ids: Vec<_> = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["other", "proj", "proj.a", "proj.b", "root"]);
+        assert_eq!(json["nodes"][1]["ghost"], true);
+        assert_eq!(json["nodes"][2]["tags"], serde_json::json!(["x", "y"]));
+        assert!(json["links"].as_array().unwrap().contains(
+            &serde_json::json!({ "source": "proj.a", "target": "other", "kind": "embed" })
+        ));
+        assert!(json["links"].as_array().unwrap().contains(
+            &serde_json::json!({ "source": "proj.a", "target": "proj", "kind": "hierarchy-parent" })
+        ));
+    }
+
+    #[test]
+    fn test_export_filters() {
+        let ws = setup();
+
+        let subtree = ws.export_graph(&ExportOptions {
+            subtree: Some("proj".to_string()),
+            ..Default::default()
+        });
+        let json: serde_json::Value = serde_json::from_str(&subtree).unwrap();
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 3);
+        // The embed of "other" points outside the subtree
+        assert_eq!(json["links"].as_array().unwrap().len(), 4);
+
+        let side = ws.export_graph(&ExportOptions {
+            vault: Some("side".to_string()),
+            ..Default::default()
+        });
+        let json: serde_json::Value = serde_json::from_str(&side).unwrap();
+        let ids: Vec<_> = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["other", "root"]);
+    }
+
+    #[test]
+    fn test_export_dot_and_graphml() {
+        let ws = setup();
+
+        let dot = ws.export_graph(&ExportOptions {
+            format: ExportFormat::Dot,
+            ..Default::default()
+        });
+        assert!(dot.starts_with("digraph notes {\n"));
+        assert!(dot.contains(r#""proj.a" [label="A \"quoted\"", vault="main", tags="x,y"];"#));
+        assert!(dot.contains(r#""proj" [label="proj", style=dashed];"#));
+        assert!(dot.contains(r#""proj.a" -> "other" [kind="embed", style=bold];"#));
+
+        let graphml = ws.export_graph(&ExportOptions {
+            format: ExportFormat::GraphMl,
+            ..Default::default()
+        });
+        assert!(graphml.contains("<data key=\"title\">A &quot;quoted&quot;</data>"));
+        assert!(graphml.contains(
+            "<edge source=\"proj.b\" target=\"proj\">\n      <data key=\"kind\">hierarchy-parent</data>"
+        ));
+    }
+}
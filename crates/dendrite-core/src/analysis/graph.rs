@@ -0,0 +1,284 @@
+use crate::identity::IdentityRegistry;
+use crate::model::{NoteId, NoteKey};
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// Link counts of a single note.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteDegree {
+    pub key: NoteKey,
+    pub in_degree: usize,
+    pub out_degree: usize,
+}
+
+/// Structural health of the note link graph.
+///
+/// Only notes backed by a file take part; links to missing notes, external
+/// links and self-links are ignored, and parallel links count once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphReport {
+    pub note_count: usize,
+    pub link_count: usize,
+    /// Notes with neither incoming nor outgoing links
+    pub orphans: Vec<NoteKey>,
+    /// Notes that are linked to but link nowhere
+    pub dead_ends: Vec<NoteKey>,
+    /// Most linked-to notes, highest in-degree first
+    pub hubs_by_in_degree: Vec<NoteDegree>,
+    /// Notes with the most outgoing links, highest out-degree first
+    pub hubs_by_out_degree: Vec<NoteDegree>,
+    /// Weakly connected components, largest first
+    pub components: Vec<Vec<NoteKey>>,
+    /// Notes not reachable from the root note by following links.
+    /// None if the model has no root note or it doesn't exist.
+    pub unreachable_from_root: Option<Vec<NoteKey>>,
+}
+
+/// Deduplicated link graph between existing notes, keyed by NoteId.
+pub(crate) struct LinkGraph {
+    /// All nodes, sorted by key
+    pub nodes: Vec<(NoteId, NoteKey)>,
+    pub outgoing: HashMap<NoteId, BTreeSet<NoteKey>>,
+    pub incoming: HashMap<NoteId, BTreeSet<NoteKey>>,
+    ids: HashMap<NoteKey, NoteId>,
+}
+
+impl LinkGraph {
+    pub(crate) fn build(store: &Store, identity: &IdentityRegistry) -> Self {
+        let mut nodes: Vec<(NoteId, NoteKey)> = store
+            .all_notes()
+            .filter(|note| note.path.is_some())
+            .filter_map(|note| Some((note.id.clone(), identity.key_of(&note.id)?)))
+            .collect();
+        nodes.sort_by(|a, b| a.1.cmp(&b.1));
+
+        let keys: HashMap<&NoteId, &NoteKey> = nodes.iter().map(|(id, key)| (id, key)).collect();
+        let mut outgoing: HashMap<NoteId, BTreeSet<NoteKey>> = HashMap::new();
+        let mut incoming: HashMap<NoteId, BTreeSet<NoteKey>> = HashMap::new();
+
+        for (id, key) in &nodes {
+            let Some(note) = store.get_note(id) else {
+                continue;
+            };
+            for link in &note.links {
+                if link.target == *id {
+                    continue;
+                }
+                let Some(target_key) = keys.get(&link.target) else {
+                    continue;
+                };
+                outgoing
+                    .entry(id.clone())
+                    .or_default()
+                    .insert((*target_key).clone());
+                incoming
+                    .entry(link.target.clone())
+                    .or_default()
+                    .insert(key.clone());
+            }
+        }
+
+        let ids = nodes
+            .iter()
+            .map(|(id, key)| (key.clone(), id.clone()))
+            .collect();
+
+        Self {
+            nodes,
+            outgoing,
+            incoming,
+            ids,
+        }
+    }
+
+    pub(crate) fn id_of(&self, key: &str) -> Option<&NoteId> {
+        self.ids.get(key)
+    }
+
+    pub(crate) fn out_degree(&self, id: &NoteId) -> usize {
+        self.outgoing.get(id).map_or(0, BTreeSet::len)
+    }
+
+    pub(crate) fn in_degree(&self, id: &NoteId) -> usize {
+        self.incoming.get(id).map_or(0, BTreeSet::len)
+    }
+
+    /// Keys linked from `id`
+    pub(crate) fn links_of(&self, id: &NoteId) -> impl Iterator<Item = &NoteKey> {
+        self.outgoing.get(id).into_iter().flatten()
+    }
+
+    /// Keys linking to `id`
+    pub(crate) fn backlinks_of(&self, id: &NoteId) -> impl Iterator<Item = &NoteKey> {
+        self.incoming.get(id).into_iter().flatten()
+    }
+}
+
+/// Compute orphans, dead ends, hubs, components and root reachability.
+pub fn calculate_graph_report(
+    store: &Store,
+    identity: &IdentityRegistry,
+    root_key: Option<&str>,
+    hub_limit: usize,
+) -> GraphReport {
+    let graph = LinkGraph::build(store, identity);
+
+    let mut orphans = Vec::new();
+    let mut dead_ends = Vec::new();
+    let mut degrees = Vec::new();
+    for (id, key) in &graph.nodes {
+        let (in_degree, out_degree) = (graph.in_degree(id), graph.out_degree(id));
+        match (in_degree, out_degree) {
+            (0, 0) => orphans.push(key.clone()),
+            (_, 0) => dead_ends.push(key.clone()),
+            _ => {}
+        }
+        degrees.push(NoteDegree {
+            key: key.clone(),
+            in_degree,
+            out_degree,
+        });
+    }
+
+    let hubs = |degree: fn(&NoteDegree) -> usize| {
+        let mut hubs: Vec<NoteDegree> = degrees.iter().filter(|d| degree(d) > 0).cloned().collect();
+        // Stable sort keeps ties in key order
+        hubs.sort_by_key(|d| std::cmp::Reverse(degree(d)));
+        hubs.truncate(hub_limit);
+        hubs
+    };
+
+    GraphReport {
+        note_count: graph.nodes.len(),
+        link_count: graph.outgoing.values().map(BTreeSet::len).sum(),
+        orphans,
+        dead_ends,
+        hubs_by_in_degree: hubs(|d| d.in_degree),
+        hubs_by_out_degree: hubs(|d| d.out_degree),
+        components: weakly_connected_components(&graph),
+        unreachable_from_root: root_key
+            .and_then(|root| graph.id_of(root))
+            .map(|root| unreachable_from(&graph, root)),
+    }
+}
+
+fn weakly_connected_components(graph: &LinkGraph) -> Vec<Vec<NoteKey>> {
+    let mut seen: HashSet<&NoteKey> = HashSet::new();
+    let mut components = Vec::new();
+
+    for (_, start) in &graph.nodes {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut component = vec![start.clone()];
+        let mut queue = VecDeque::from([start]);
+        while let Some(key) = queue.pop_front() {
+            let Some(id) = graph.id_of(key) else {
+                continue;
+            };
+            for neighbor in graph.links_of(id).chain(graph.backlinks_of(id)) {
+                if seen.insert(neighbor) {
+                    component.push(neighbor.clone());
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        component.sort();
+        components.push(component);
+    }
+
+    // Stable sort keeps equally sized components in key order
+    components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+    components
+}
+
+fn unreachable_from(graph: &LinkGraph, root: &NoteId) -> Vec<NoteKey> {
+    let mut reached: HashSet<&NoteKey> = HashSet::new();
+    let mut queue: VecDeque<&NoteId> = VecDeque::from([root]);
+    while let Some(id) = queue.pop_front() {
+        for key in graph.links_of(id) {
+            if reached.insert(key) {
+                if let Some(next) = graph.id_of(key) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    graph
+        .nodes
+        .iter()
+        .filter(|(id, key)| id != root && !reached.contains(key))
+        .map(|(_, key)| key.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+    use crate::workspace::Workspace;
+
+    fn setup(notes: &[(&str, &str)]) -> Workspace {
+        let (mut ws, _) = test_fixtures::setup(notes);
+        ws.fill_missing_hierarchy_levels();
+        ws
+    }
+
+    #[test]
+    fn test_graph_report() {
+        let ws = setup(&[
+            ("root", "[[a]]"),
+            ("a", "[[b]] [[b]] [[a]] [[missing]]"),
+            ("b", "# B"),
+            ("c", "[[d]]"),
+            ("d", "[[b]]"),
+            ("lonely", "# Lonely"),
+            ("x.y", "# Only a ghost parent"),
+        ]);
+
+        let report = calculate_graph_report(&ws.store, &ws.identity, Some("root"), 2);
+
+        // Ghost note "x" is not part of the graph
+        assert_eq!(report.note_count, 7);
+        assert_eq!(report.link_count, 4);
+        assert_eq!(report.orphans, vec!["lonely", "x.y"]);
+        assert_eq!(report.dead_ends, vec!["b"]);
+        assert_eq!(
+            report.hubs_by_in_degree[0],
+            NoteDegree {
+                key: "b".to_string(),
+                in_degree: 2,
+                out_degree: 0
+            }
+        );
+        assert_eq!(report.hubs_by_in_degree.len(), 2);
+        assert_eq!(report.hubs_by_out_degree[0].key, "a");
+        assert_eq!(
+            report.components,
+            vec![
+                vec!["a", "b", "c", "d", "root"],
+                vec!["lonely"],
+                vec!["x.y"]
+            ]
+        );
+        assert_eq!(
+            report.unreachable_from_root,
+            Some(vec![
+                "c".to_string(),
+                "d".to_string(),
+                "lonely".to_string(),
+                "x.y".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_graph_report_without_root_note() {
+        let ws = setup(&[("a", "[[b]]"), ("b", "# B")]);
+        let report = calculate_graph_report(&ws.store, &ws.identity, Some("root"), 10);
+        assert_eq!(report.unreachable_from_root, None);
+        assert_eq!(report.components.len(), 1);
+    }
+}
@@ -0,0 +1,335 @@
+use crate::identity::IdentityRegistry;
+use crate::line_map::LineMap;
+use crate::model::{NoteKey, TextRange};
+use crate::mutation::model::ContentProvider;
+use crate::parser::markdown_options;
+use crate::semantic::SemanticModel;
+use crate::store::Store;
+use aho_corasick::{AhoCorasick, MatchKind};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Titles and aliases shorter than this are too noisy to report
+const MIN_MENTION_LENGTH: usize = 3;
+
+/// Plain-text occurrence of another note's title or alias.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlinkedMention {
+    /// Note containing the mention
+    pub path: PathBuf,
+    pub range: TextRange,
+    /// The mention as written
+    pub text: String,
+    /// Key of the mentioned note
+    pub target: NoteKey,
+}
+
+/// Matches every note title and alias at once.
+///
+/// Names shared by several notes are ambiguous and left out.
+pub(crate) struct MentionMatcher {
+    automaton: Option<AhoCorasick>,
+    targets: Vec<NoteKey>,
+}
+
+impl MentionMatcher {
+    pub(crate) fn build(store: &Store, identity: &IdentityRegistry) -> Self {
+        // lowercased name -> target key, None once ambiguous
+        let mut names: HashMap<String, Option<NoteKey>> = HashMap::new();
+        for note in store.all_notes().filter(|note| note.path.is_some()) {
+            let Some(key) = identity.key_of(&note.id) else {
+                continue;
+            };
+            let mut note_names: Vec<String> = note.title.iter().cloned().collect();
+            note_names.extend(note.aliases());
+            for name in note_names {
+                let name = name.trim().to_lowercase();
+                if name.chars().count() < MIN_MENTION_LENGTH {
+                    continue;
+                }
+                names
+                    .entry(name)
+                    .and_modify(|target| {
+                        if target.as_ref() != Some(&key) {
+                            *target = None;
+                        }
+                    })
+                    .or_insert_with(|| Some(key.clone()));
+            }
+        }
+
+        let mut entries: Vec<(String, NoteKey)> = names
+            .into_iter()
+            .filter_map(|(name, target)| Some((name, target?)))
+            .collect();
+        entries.sort();
+
+        // Both names and prose are lowercased, so matching is case-insensitive
+        // beyond ASCII too
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(entries.iter().map(|(name, _)| name))
+            .ok();
+
+        Self {
+            automaton,
+            targets: entries.into_iter().map(|(_, key)| key).collect(),
+        }
+    }
+
+    /// Find mentions in a note's content, skipping frontmatter, code, links and
+    /// mentions of the note itself.
+    pub(crate) fn find(
+        &self,
+        model: &dyn SemanticModel,
+        path: &Path,
+        content: &str,
+    ) -> Vec<UnlinkedMention> {
+        let Some(automaton) = &self.automaton else {
+            return Vec::new();
+        };
+        let source_key = model.note_key_from_path(path, content);
+        let line_map = LineMap::new(content);
+        let mut mentions = Vec::new();
+
+        for (start, end) in plain_text_spans(model, content) {
+            let text = Lowercased::new(&content[start..end]);
+            for found in automaton.find_iter(&text.text) {
+                let (Some(found_start), Some(found_end)) =
+                    (text.original(found.start()), text.original(found.end()))
+                else {
+                    continue;
+                };
+                let (match_start, match_end) = (start + found_start, start + found_end);
+                let target = &self.targets[found.pattern().as_usize()];
+                if *target == source_key || !is_word_boundary(content, match_start, match_end) {
+                    continue;
+                }
+                mentions.push(UnlinkedMention {
+                    path: path.to_path_buf(),
+                    range: TextRange {
+                        start: line_map.offset_to_point(content, match_start),
+                        end: line_map.offset_to_point(content, match_end),
+                    },
+                    text: content[match_start..match_end].to_string(),
+                    target: target.clone(),
+                });
+            }
+        }
+
+        mentions
+    }
+}
+
+/// Lowercased copy of a text that maps offsets back to the original.
+///
+/// Lowercasing may change the byte length of a character (or expand it to
+/// several), so offsets are mapped per character.
+struct Lowercased {
+    text: String,
+    /// Original offset for each byte of `text` that starts a lowercased
+    /// character; None inside one
+    offsets: Vec<Option<usize>>,
+}
+
+impl Lowercased {
+    fn new(original: &str) -> Self {
+        let mut text = String::with_capacity(original.len());
+        let mut offsets = Vec::with_capacity(original.len() + 1);
+        for (offset, c) in original.char_indices() {
+            let start = text.len();
+            text.extend(c.to_lowercase());
+            offsets.push(Some(offset));
+            offsets.resize(offsets.len() + text.len() - start - 1, None);
+        }
+        offsets.push(Some(original.len()));
+        Self { text, offsets }
+    }
+
+    /// Offset in the original text, if `offset` is a character boundary there
+    fn original(&self, offset: usize) -> Option<usize> {
+        self.offsets[offset]
+    }
+}
+
+/// Byte spans of prose: text outside frontmatter, code, links, images and HTML.
+fn plain_text_spans(model: &dyn SemanticModel, content: &str) -> Vec<(usize, usize)> {
+    let (options, _) = markdown_options(&model.supported_link_kinds());
+    let mut spans = Vec::new();
+    let mut skip_depth = 0usize;
+
+    for (event, range) in Parser::new_ext(content, options).into_offset_iter() {
+        match event {
+            Event::Start(
+                Tag::CodeBlock(_)
+                | Tag::Link { .. }
+                | Tag::Image { .. }
+                | Tag::HtmlBlock
+                | Tag::MetadataBlock(_),
+            ) => skip_depth += 1,
+            Event::End(
+                TagEnd::CodeBlock
+                | TagEnd::Link
+                | TagEnd::Image
+                | TagEnd::HtmlBlock
+                | TagEnd::MetadataBlock(_),
+            ) => skip_depth = skip_depth.saturating_sub(1),
+            Event::Text(_) if skip_depth == 0 => {
+                // Merge adjacent text events (pulldown-cmark splits on some punctuation)
+                match spans.last_mut() {
+                    Some((_, end)) if *end == range.start => *end = range.end,
+                    _ => spans.push((range.start, range.end)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    spans
+}
+
+fn is_word_boundary(content: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    !content[..start].chars().next_back().is_some_and(is_word)
+        && !content[end..].chars().next().is_some_and(is_word)
+}
+
+/// Find unlinked mentions in the given notes, or in every note when `paths` is None.
+pub(crate) fn calculate_unlinked_mentions(
+    store: &Store,
+    matcher: &MentionMatcher,
+    model: &dyn SemanticModel,
+    content_provider: &dyn ContentProvider,
+    paths: Option<&[PathBuf]>,
+) -> Vec<UnlinkedMention> {
+    let mut sources: Vec<&PathBuf> = match paths {
+        Some(paths) => paths.iter().collect(),
+        None => store
+            .all_notes()
+            .filter_map(|note| note.path.as_ref())
+            .collect(),
+    };
+    sources.sort();
+
+    sources
+        .into_iter()
+        .filter_map(|path| {
+            let content = content_provider.get_content(&path.to_string_lossy())?;
+            Some(matcher.find(model, path, &content))
+        })
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::test_fixtures::setup;
+    use crate::vfs::PhysicalFileSystem;
+    use crate::workspace::{Indexer, Workspace};
+
+    #[test]
+    fn test_unlinked_mentions() {
+        let (ws, provider) = setup(&[
+            (
+                "rust",
+                "---\ntitle: Rust Language\naliases: [rustlang]\n---\nAbout Rust Language",
+            ),
+            ("tokio", "# Tokio"),
+            (
+                "source",
+                "---\ntitle: rust language notes\n---\nI like rust language and RUSTLANG.\n\
+                 Already linked: [[rust]], [Rust Language](rust.md), `tokio`.\n\
+                 ```\ntokio\n```\nNot tokios, but tokio!",
+            ),
+        ]);
+
+        let mentions = ws.unlinked_mentions(&provider, Some(&[PathBuf::from("/test/source.md")]));
+        let found: Vec<_> = mentions
+            .iter()
+            .map(|m| (m.text.as_str(), m.target.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("rust language", "rust"),
+                ("RUSTLANG", "rust"),
+                ("tokio", "tokio")
+            ]
+        );
+        assert_eq!(
+            mentions[0].range,
+            TextRange {
+                start: Point { line: 3, col: 7 },
+                end: Point { line: 3, col: 20 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_skips_self_and_ambiguous_mentions() {
+        let (ws, provider) = setup(&[
+            ("a", "# Shared Name\nshared name here"),
+            ("b", "# Shared Name"),
+            ("c", "# Unique\nMentions unique"),
+            ("d", "shared name and unique"),
+        ]);
+
+        let mentions = ws.unlinked_mentions(&provider, None);
+        let found: Vec<_> = mentions
+            .iter()
+            .map(|m| (m.path.to_string_lossy().to_string(), m.target.clone()))
+            .collect();
+        assert_eq!(found, vec![("/test/d.md".to_string(), "c".to_string())]);
+    }
+
+    #[test]
+    fn test_mentions_ignore_non_ascii_case() {
+        let (ws, provider) = setup(&[
+            ("emile", "# Émile Zola"),
+            ("strasse", "# Straße"),
+            // "ẞ" is one byte longer than its lowercase "ß"
+            (
+                "source",
+                "An ÉMILE ZOLA novel, read on the STRAẞE by émile zola",
+            ),
+        ]);
+
+        let mentions = ws.unlinked_mentions(&provider, Some(&[PathBuf::from("/test/source.md")]));
+        let found: Vec<_> = mentions
+            .iter()
+            .map(|m| (m.text.as_str(), m.target.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("ÉMILE ZOLA", "emile"),
+                ("STRAẞE", "strasse"),
+                ("émile zola", "emile")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mentions_follow_title_changes() {
+        let (mut ws, provider) = setup(&[("tokio", "# Tokio"), ("source", "tokio and axum")]);
+        let source = [PathBuf::from("/test/source.md")];
+        let texts = |ws: &Workspace| -> Vec<String> {
+            ws.unlinked_mentions(&provider, Some(&source))
+                .into_iter()
+                .map(|m| m.text)
+                .collect()
+        };
+        assert_eq!(texts(&ws), vec!["tokio"]);
+
+        Indexer::new(&mut ws, &PhysicalFileSystem).update_content(
+            PathBuf::from("/test/tokio.md"),
+            "# Axum",
+            "main".to_string(),
+        );
+        assert_eq!(texts(&ws), vec!["axum"]);
+    }
+}
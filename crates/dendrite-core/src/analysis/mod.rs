@@ -1 +1,8 @@
 pub mod audit;
+pub mod backlinks;
+pub mod duplicates;
+pub mod export;
+pub mod graph;
+pub mod mentions;
+pub mod related;
+pub mod traversal;
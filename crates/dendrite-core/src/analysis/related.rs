@@ -0,0 +1,135 @@
+use crate::identity::IdentityRegistry;
+use crate::model::{NoteId, NoteKey};
+use crate::search::SearchIndex;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedNote {
+    pub key: NoteKey,
+    pub path: Option<PathBuf>,
+    pub title: Option<String>,
+    /// Cosine similarity of the TF-IDF vectors, in (0, 1]
+    pub score: f64,
+}
+
+/// Notes whose TF-IDF vectors are most similar to `id`'s, excluding notes it
+/// already links to or is linked from.
+///
+/// Vectors are derived from the full-text index, so they follow every
+/// incremental update of it. Terms found in every note weigh nothing.
+pub(crate) fn calculate_related_notes(
+    store: &Store,
+    identity: &IdentityRegistry,
+    index: &SearchIndex,
+    id: &NoteId,
+    limit: usize,
+) -> Vec<RelatedNote> {
+    let doc_count = index.doc_count() as f64;
+    let idf = |term: &str| {
+        let document_frequency = index.postings(term).map_or(0, HashMap::len) as f64;
+        if document_frequency == 0.0 {
+            0.0
+        } else {
+            (doc_count / document_frequency).ln()
+        }
+    };
+    let norm = |doc: &NoteId| {
+        index
+            .doc_terms(doc)
+            .map(|(term, tf)| (tf as f64 * idf(term)).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    };
+
+    let source_norm = norm(id);
+    if source_norm == 0.0 {
+        return Vec::new();
+    }
+
+    let mut excluded: HashSet<NoteId> = store.backlinks_of(id).into_iter().collect();
+    if let Some(note) = store.get_note(id) {
+        excluded.extend(note.links.iter().map(|link| link.target.clone()));
+    }
+    excluded.insert(id.clone());
+
+    // Dot products with every note sharing a weighted term
+    let mut dots: HashMap<&NoteId, f64> = HashMap::new();
+    for (term, tf) in index.doc_terms(id) {
+        let weight = idf(term);
+        if weight == 0.0 {
+            continue;
+        }
+        for (other, positions) in index.postings(term).into_iter().flatten() {
+            if !excluded.contains(other) {
+                *dots.entry(other).or_default() +=
+                    tf as f64 * weight * positions.len() as f64 * weight;
+            }
+        }
+    }
+
+    let mut related: Vec<RelatedNote> = dots
+        .into_iter()
+        .filter_map(|(other, dot)| {
+            let note = store.get_note(other)?;
+            let score = dot / (source_norm * norm(other));
+            Some(RelatedNote {
+                key: identity.key_of(other)?,
+                path: note.path.clone(),
+                title: note.title.clone(),
+                score,
+            })
+        })
+        .collect();
+
+    related.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.key.cmp(&b.key)));
+    related.truncate(limit);
+    related
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::{self, test_path};
+    use crate::workspace::Workspace;
+
+    fn setup(notes: &[(&str, &str)]) -> Workspace {
+        test_fixtures::setup(notes).0
+    }
+
+    fn related(ws: &Workspace, key: &str) -> Vec<String> {
+        ws.related_notes(&test_path(&format!("{}.md", key)), 10)
+            .into_iter()
+            .map(|note| note.key)
+            .collect()
+    }
+
+    #[test]
+    fn test_related_notes_ranking_and_exclusions() {
+        let ws = setup(&[
+            ("source", "rust borrow checker lifetimes the notes"),
+            ("close", "borrow checker and lifetimes in rust the"),
+            ("partial", "rust macros the"),
+            ("linked", "rust borrow checker lifetimes [[source]] the"),
+            ("unrelated", "gardening tomatoes the"),
+        ]);
+
+        // "the" appears everywhere and carries no weight
+        assert_eq!(related(&ws, "source"), vec!["close", "partial"]);
+        assert!(related(&ws, "unrelated").is_empty());
+    }
+
+    #[test]
+    fn test_related_notes_follow_updates() {
+        let (mut ws, mut provider) = test_fixtures::setup(&[
+            ("a", "kubernetes cluster"),
+            ("b", "kubernetes cluster"),
+            ("c", "sourdough bread"),
+        ]);
+        assert_eq!(related(&ws, "a"), vec!["b"]);
+
+        provider.index(&mut ws, test_path("a.md"), "sourdough bread", "main");
+        assert_eq!(related(&ws, "a"), vec!["c"]);
+    }
+}
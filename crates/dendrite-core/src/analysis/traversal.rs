@@ -0,0 +1,379 @@
+use super::graph::LinkGraph;
+use crate::identity::IdentityRegistry;
+use crate::model::NoteKey;
+use crate::semantic::SemanticModel;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::PathBuf;
+
+/// Which link edges a traversal may follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkDirection {
+    /// Links from a note to its targets
+    Outgoing,
+    /// Backlinks, from a note to the notes linking to it
+    Incoming,
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct TraversalOptions {
+    pub direction: LinkDirection,
+    /// Also step between a note and its nearest existing hierarchy parent/children
+    pub include_hierarchy: bool,
+}
+
+/// How a path step was reached from the previous note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepKind {
+    Link,
+    Backlink,
+    Parent,
+    Child,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathStep {
+    pub key: NoteKey,
+    /// None for the starting note
+    pub via: Option<StepKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeKind {
+    Link,
+    /// From parent to child
+    Hierarchy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub key: NoteKey,
+    pub path: Option<PathBuf>,
+    pub title: Option<String>,
+    /// Hops from the center note
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: NoteKey,
+    pub target: NoteKey,
+    pub kind: EdgeKind,
+}
+
+/// A subgraph around a note, for local graph views.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighborhood {
+    /// Sorted by depth, then key
+    pub nodes: Vec<GraphNode>,
+    /// Every edge between two included nodes
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Link graph plus the hierarchy edges between existing notes.
+struct Traversal {
+    graph: LinkGraph,
+    parents: HashMap<NoteKey, NoteKey>,
+    children: HashMap<NoteKey, BTreeSet<NoteKey>>,
+    options: TraversalOptions,
+}
+
+impl Traversal {
+    fn new(
+        store: &Store,
+        identity: &IdentityRegistry,
+        model: &dyn SemanticModel,
+        options: TraversalOptions,
+    ) -> Self {
+        let graph = LinkGraph::build(store, identity);
+        let mut parents = HashMap::new();
+        let mut children: HashMap<NoteKey, BTreeSet<NoteKey>> = HashMap::new();
+
+        if options.include_hierarchy {
+            for (_, key) in &graph.nodes {
+                // Skip ghost levels up to the nearest note that exists
+                let mut ancestor = model.resolve_parent(key);
+                while let Some(candidate) = ancestor {
+                    if graph.id_of(&candidate).is_some() {
+                        children
+                            .entry(candidate.clone())
+                            .or_default()
+                            .insert(key.clone());
+                        parents.insert(key.clone(), candidate);
+                        break;
+                    }
+                    ancestor = model.resolve_parent(&candidate);
+                }
+            }
+        }
+
+        Self {
+            graph,
+            parents,
+            children,
+            options,
+        }
+    }
+
+    /// Neighbors in a deterministic order
+    fn neighbors(&self, key: &NoteKey) -> Vec<(&NoteKey, StepKind)> {
+        let mut neighbors = Vec::new();
+        let Some(id) = self.graph.id_of(key) else {
+            return neighbors;
+        };
+
+        if self.options.direction != LinkDirection::Incoming {
+            neighbors.extend(self.graph.links_of(id).map(|k| (k, StepKind::Link)));
+        }
+        if self.options.direction != LinkDirection::Outgoing {
+            neighbors.extend(self.graph.backlinks_of(id).map(|k| (k, StepKind::Backlink)));
+        }
+        if let Some(parent) = self.parents.get(key) {
+            neighbors.push((parent, StepKind::Parent));
+        }
+        neighbors.extend(
+            self.children
+                .get(key)
+                .into_iter()
+                .flatten()
+                .map(|k| (k, StepKind::Child)),
+        );
+        neighbors
+    }
+}
+
+/// Shortest path (fewest hops) from `from` to `to`, both included.
+/// None if either note doesn't exist or they are not connected.
+pub fn calculate_shortest_path(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+    from: &str,
+    to: &str,
+    options: TraversalOptions,
+) -> Option<Vec<PathStep>> {
+    let traversal = Traversal::new(store, identity, model, options);
+    traversal.graph.id_of(from)?;
+    traversal.graph.id_of(to)?;
+
+    // Breadth-first search, remembering how each note was reached
+    let mut previous: HashMap<NoteKey, (NoteKey, StepKind)> = HashMap::new();
+    let mut queue = VecDeque::from([from.to_string()]);
+    while let Some(key) = queue.pop_front() {
+        if key == to {
+            break;
+        }
+        for (neighbor, kind) in traversal.neighbors(&key) {
+            if neighbor != from && !previous.contains_key(neighbor) {
+                previous.insert(neighbor.clone(), (key.clone(), kind));
+                queue.push_back(neighbor.clone());
+            }
+        }
+    }
+
+    let mut steps = Vec::new();
+    let mut current = to.to_string();
+    while current != from {
+        let (prev, kind) = previous.get(&current)?;
+        steps.push(PathStep {
+            key: current,
+            via: Some(*kind),
+        });
+        current = prev.clone();
+    }
+    steps.push(PathStep {
+        key: from.to_string(),
+        via: None,
+    });
+    steps.reverse();
+    Some(steps)
+}
+
+/// Every note within `depth` hops of `center`, with the edges between them.
+pub fn calculate_neighborhood(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+    center: &str,
+    depth: usize,
+    options: TraversalOptions,
+) -> Option<Neighborhood> {
+    let traversal = Traversal::new(store, identity, model, options);
+    traversal.graph.id_of(center)?;
+
+    let mut depths: BTreeMap<NoteKey, usize> = BTreeMap::from([(center.to_string(), 0)]);
+    let mut queue = VecDeque::from([(center.to_string(), 0)]);
+    while let Some((key, distance)) = queue.pop_front() {
+        if distance == depth {
+            continue;
+        }
+        for (neighbor, _) in traversal.neighbors(&key) {
+            if !depths.contains_key(neighbor) {
+                depths.insert(neighbor.clone(), distance + 1);
+                queue.push_back((neighbor.clone(), distance + 1));
+            }
+        }
+    }
+
+    let mut nodes: Vec<GraphNode> = depths
+        .iter()
+        .filter_map(|(key, depth)| {
+            let note = store.get_note(traversal.graph.id_of(key)?)?;
+            Some(GraphNode {
+                key: key.clone(),
+                path: note.path.clone(),
+                title: note.title.clone(),
+                depth: *depth,
+            })
+        })
+        .collect();
+    nodes.sort_by_key(|node| node.depth);
+
+    let mut edges = Vec::new();
+    for key in depths.keys() {
+        let Some(id) = traversal.graph.id_of(key) else {
+            continue;
+        };
+        for target in traversal.graph.links_of(id) {
+            if depths.contains_key(target) {
+                edges.push(GraphEdge {
+                    source: key.clone(),
+                    target: target.clone(),
+                    kind: EdgeKind::Link,
+                });
+            }
+        }
+        for child in traversal.children.get(key).into_iter().flatten() {
+            if depths.contains_key(child) {
+                edges.push(GraphEdge {
+                    source: key.clone(),
+                    target: child.clone(),
+                    kind: EdgeKind::Hierarchy,
+                });
+            }
+        }
+    }
+
+    Some(Neighborhood { nodes, edges })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+    use crate::workspace::Workspace;
+
+    fn setup(notes: &[(&str, &str)]) -> Workspace {
+        let (mut ws, _) = test_fixtures::setup(notes);
+        ws.fill_missing_hierarchy_levels();
+        ws
+    }
+
+    fn path_keys(steps: Option<Vec<PathStep>>) -> Option<Vec<(String, Option<StepKind>)>> {
+        steps.map(|steps| steps.into_iter().map(|s| (s.key, s.via)).collect())
+    }
+
+    #[test]
+    fn test_shortest_path_directions() {
+        let ws = setup(&[("a", "[[b]]"), ("b", "[[c]]"), ("c", "# C"), ("d", "[[c]]")]);
+        let path = |from, to, direction| {
+            path_keys(ws.shortest_path(
+                from,
+                to,
+                TraversalOptions {
+                    direction,
+                    include_hierarchy: false,
+                },
+            ))
+        };
+
+        assert_eq!(
+            path("a", "c", LinkDirection::Outgoing),
+            Some(vec![
+                ("a".to_string(), None),
+                ("b".to_string(), Some(StepKind::Link)),
+                ("c".to_string(), Some(StepKind::Link)),
+            ])
+        );
+        assert_eq!(path("c", "a", LinkDirection::Outgoing), None);
+        assert_eq!(
+            path("a", "d", LinkDirection::Both)
+                .unwrap()
+                .last()
+                .unwrap()
+                .1,
+            Some(StepKind::Backlink)
+        );
+        assert_eq!(path("a", "missing", LinkDirection::Both), None);
+        assert_eq!(path("a", "a", LinkDirection::Both).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_shortest_path_through_hierarchy() {
+        // "proj" is a ghost level, so "proj.a" and "proj.a.b" are connected directly
+        let ws = setup(&[
+            ("proj.a", "# A"),
+            ("proj.a.b", "[[other]]"),
+            ("other", "# O"),
+        ]);
+
+        let without = ws.shortest_path("proj.a", "other", TraversalOptions::default());
+        assert_eq!(without, None);
+
+        let with = path_keys(ws.shortest_path(
+            "proj.a",
+            "other",
+            TraversalOptions {
+                include_hierarchy: true,
+                ..Default::default()
+            },
+        ))
+        .unwrap();
+        assert_eq!(with[1], ("proj.a.b".to_string(), Some(StepKind::Child)));
+        assert_eq!(with.len(), 3);
+    }
+
+    #[test]
+    fn test_neighborhood() {
+        let ws = setup(&[
+            ("a", "[[b]]"),
+            ("b", "[[c]]"),
+            ("c", "[[d]]"),
+            ("d", "# D"),
+            ("x", "[[a]]"),
+        ]);
+
+        let hood = ws
+            .neighborhood("b", 1, TraversalOptions::default())
+            .unwrap();
+        let keys: Vec<_> = hood
+            .nodes
+            .iter()
+            .map(|n| (n.key.as_str(), n.depth))
+            .collect();
+        assert_eq!(keys, vec![("b", 0), ("a", 1), ("c", 1)]);
+        assert_eq!(hood.edges.len(), 2);
+
+        let outgoing = ws
+            .neighborhood(
+                "a",
+                2,
+                TraversalOptions {
+                    direction: LinkDirection::Outgoing,
+                    include_hierarchy: false,
+                },
+            )
+            .unwrap();
+        let keys: Vec<_> = outgoing.nodes.iter().map(|n| n.key.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b", "c"]);
+        assert!(ws
+            .neighborhood("nope", 1, TraversalOptions::default())
+            .is_none());
+    }
+}
@@ -5,6 +5,7 @@ use std::time::SystemTime;
 use serde::{Deserialize, Serialize};
 
 use crate::identity::IdentityRegistry;
+use crate::search::SearchIndex;
 use crate::store::Store;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -21,11 +22,39 @@ pub struct PersistentState {
     pub store: Store,
     pub identity: IdentityRegistry,
     pub metadata: HashMap<PathBuf, FileMetadata>,
+    pub search: PersistedSearchIndex,
 }
 
-impl PersistentState {
+/// The full-text index, stored as an opaque blob with its own version so that a
+/// change to its format only discards the index, not the whole cache.
+#[derive(Serialize, Deserialize, Default)]
+pub struct PersistedSearchIndex {
+    pub version: u32,
+    pub data: Vec<u8>,
+}
+
+impl PersistedSearchIndex {
     pub const CURRENT_VERSION: u32 = 1;
 
+    pub fn new(index: &SearchIndex) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            version: Self::CURRENT_VERSION,
+            data: bincode::serialize(index)?,
+        })
+    }
+
+    /// The stored index, or None if it is missing or was written by another version.
+    pub fn restore(&self) -> Option<SearchIndex> {
+        if self.version != Self::CURRENT_VERSION {
+            return None;
+        }
+        bincode::deserialize(&self.data).ok()
+    }
+}
+
+impl PersistentState {
+    pub const CURRENT_VERSION: u32 = 6;
+
     pub fn new(model_id: String, store: Store, identity: IdentityRegistry) -> Self {
         Self {
             version: Self::CURRENT_VERSION,
@@ -33,6 +62,7 @@ impl PersistentState {
             store,
             identity,
             metadata: HashMap::new(),
+            search: PersistedSearchIndex::default(),
         }
     }
 
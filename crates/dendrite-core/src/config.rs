@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Top-level configuration for Dendrite
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +30,17 @@ pub struct VaultConfig {
     pub path: PathBuf,
 }
 
+impl VaultConfig {
+    /// The vault directory, with a relative `path` taken from the workspace `root`
+    pub fn resolve(&self, root: &Path) -> PathBuf {
+        if self.path.is_absolute() {
+            self.path.clone()
+        } else {
+            root.join(&self.path)
+        }
+    }
+}
+
 /// Semantic model configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SemanticConfig {
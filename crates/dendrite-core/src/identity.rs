@@ -1,6 +1,6 @@
 use crate::model::{NoteId, NoteKey};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// NoteKey <=> NoteId
 /// Concrete registry for managing unique identifiers for notes.
@@ -8,6 +8,12 @@ use std::collections::HashMap;
 pub struct IdentityRegistry {
     key_to_id: HashMap<NoteKey, NoteId>,
     id_to_key: HashMap<NoteId, NoteKey>,
+    /// Lowercased frontmatter alias => notes declaring it
+    #[serde(default)]
+    aliases: HashMap<String, Vec<NoteId>>,
+    /// Further notes sharing a key with the one in `key_to_id` (same key, other vaults)
+    #[serde(default)]
+    duplicates: HashMap<NoteKey, Vec<NoteId>>,
 }
 
 impl IdentityRegistry {
@@ -15,6 +21,8 @@ impl IdentityRegistry {
         Self {
             key_to_id: HashMap::new(),
             id_to_key: HashMap::new(),
+            aliases: HashMap::new(),
+            duplicates: HashMap::new(),
         }
     }
 
@@ -30,6 +38,24 @@ impl IdentityRegistry {
         id
     }
 
+    /// Bind `key` to a given `id`, e.g. one derived from frontmatter.
+    ///
+    /// Returns what had to give way: the id `key` was bound to, and the key
+    /// `id` was bound to.
+    pub(crate) fn bind(&mut self, key: &NoteKey, id: &NoteId) -> (Option<NoteId>, Option<NoteKey>) {
+        let displaced_id = self.key_to_id.get(key).filter(|old| *old != id).cloned();
+        let displaced_key = self.id_to_key.get(id).filter(|old| *old != key).cloned();
+        if let Some(old) = &displaced_id {
+            self.id_to_key.remove(old);
+        }
+        if let Some(old) = &displaced_key {
+            self.key_to_id.remove(old);
+        }
+        self.key_to_id.insert(key.clone(), id.clone());
+        self.id_to_key.insert(id.clone(), key.clone());
+        (displaced_id, displaced_key)
+    }
+
     #[allow(private_interfaces)]
     pub fn rebind(&mut self, old: &NoteKey, new: &NoteKey) -> Option<NoteId> {
         let id = self.key_to_id.remove(old)?;
@@ -38,6 +64,70 @@ impl IdentityRegistry {
         Some(id)
     }
 
+    /// Register another note under `key`, which already names a note.
+    /// Takes `id` unless the registry knows it already, otherwise a new id.
+    pub(crate) fn add_duplicate(&mut self, key: &NoteKey, id: Option<NoteId>) -> NoteId {
+        let id = match id {
+            Some(id) if !self.id_to_key.contains_key(&id) => id,
+            _ => NoteId::new(),
+        };
+        self.id_to_key.insert(id.clone(), key.clone());
+        self.duplicates
+            .entry(key.clone())
+            .or_default()
+            .push(id.clone());
+        id
+    }
+
+    /// Forget a note registered with `add_duplicate`, e.g. once its file is deleted
+    pub(crate) fn remove_duplicate(&mut self, id: &NoteId) {
+        let Some(key) = self.id_to_key.get(id) else {
+            return;
+        };
+        let Some(ids) = self.duplicates.get_mut(key) else {
+            return;
+        };
+        if let Some(index) = ids.iter().position(|dup| dup == id) {
+            ids.remove(index);
+            if ids.is_empty() {
+                self.duplicates.remove(key);
+            }
+            self.id_to_key.remove(id);
+        }
+    }
+
+    /// Move the note `id` from key `old` to `new`. When the note `old` names
+    /// moves away, a note sharing the key takes its place.
+    pub(crate) fn rekey(&mut self, id: &NoteId, old: &NoteKey, new: &NoteKey) {
+        if self.key_to_id.get(old) == Some(id) {
+            self.rebind(old, new);
+            if let Some(ids) = self.duplicates.get_mut(old) {
+                let promoted = ids.remove(0);
+                if ids.is_empty() {
+                    self.duplicates.remove(old);
+                }
+                self.key_to_id.insert(old.clone(), promoted);
+            }
+            return;
+        }
+
+        if let Some(ids) = self.duplicates.get_mut(old) {
+            ids.retain(|dup| dup != id);
+            if ids.is_empty() {
+                self.duplicates.remove(old);
+            }
+        }
+        if self.key_to_id.contains_key(new) {
+            self.duplicates
+                .entry(new.clone())
+                .or_default()
+                .push(id.clone());
+        } else {
+            self.key_to_id.insert(new.clone(), id.clone());
+        }
+        self.id_to_key.insert(id.clone(), new.clone());
+    }
+
     #[allow(private_interfaces)]
     pub fn lookup(&self, key: &NoteKey) -> Option<NoteId> {
         self.key_to_id.get(key).cloned()
@@ -47,6 +137,83 @@ impl IdentityRegistry {
     pub fn key_of(&self, id: &NoteId) -> Option<NoteKey> {
         self.id_to_key.get(id).cloned()
     }
+
+    /// Every note registered under `key`: the one `lookup` returns first
+    pub(crate) fn notes_named(&self, key: &NoteKey) -> Vec<NoteId> {
+        self.lookup(key)
+            .into_iter()
+            .chain(self.duplicates.get(key).into_iter().flatten().cloned())
+            .collect()
+    }
+
+    /// Whether more than one note is registered under `key`
+    pub(crate) fn is_shared(&self, key: &NoteKey) -> bool {
+        self.duplicates.contains_key(key)
+    }
+
+    /// Replace the aliases declared by `id`.
+    /// Returns the (lowercased) aliases that were added or removed.
+    pub(crate) fn set_aliases(&mut self, id: &NoteId, aliases: &[String]) -> Vec<String> {
+        let new: HashSet<String> = aliases
+            .iter()
+            .map(|alias| alias.trim().to_lowercase())
+            .filter(|alias| !alias.is_empty())
+            .collect();
+
+        let mut changed = Vec::new();
+        self.aliases.retain(|alias, owners| {
+            if !new.contains(alias) && owners.contains(id) {
+                owners.retain(|owner| owner != id);
+                changed.push(alias.clone());
+            }
+            !owners.is_empty()
+        });
+        for alias in new {
+            let owners = self.aliases.entry(alias.clone()).or_default();
+            if !owners.contains(id) {
+                owners.push(id.clone());
+                changed.push(alias);
+            }
+        }
+        changed
+    }
+
+    /// Notes declaring `alias` (case-insensitive)
+    pub(crate) fn alias_owners(&self, alias: &str) -> &[NoteId] {
+        self.aliases
+            .get(&alias.to_lowercase())
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The note `alias` resolves to: of several declaring it, the one with the smallest key
+    pub(crate) fn alias_owner(&self, alias: &str) -> Option<NoteId> {
+        self.alias_owners(alias)
+            .iter()
+            .min_by_key(|id| self.id_to_key.get(*id))
+            .cloned()
+    }
+
+    /// Resolve a link key: the note registered under it if `exists`, otherwise
+    /// another note sharing the key, otherwise the note declaring it as an
+    /// alias, otherwise whatever is registered.
+    pub(crate) fn resolve(
+        &self,
+        key: &NoteKey,
+        exists: impl Fn(&NoteId) -> bool,
+    ) -> Option<NoteId> {
+        match self.lookup(key) {
+            Some(id) if exists(&id) => Some(id),
+            literal => self
+                .duplicates
+                .get(key)
+                .into_iter()
+                .flatten()
+                .find(|id| exists(id))
+                .cloned()
+                .or_else(|| self.alias_owner(key))
+                .or(literal),
+        }
+    }
 }
 
 impl Default for IdentityRegistry {
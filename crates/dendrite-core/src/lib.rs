@@ -12,12 +12,17 @@ pub mod line_map;
 pub mod model;
 pub mod mutation;
 pub mod parser;
+pub mod render;
+pub mod search;
 pub mod semantic;
 pub mod store;
 pub mod utils;
 pub mod vfs;
 pub mod workspace;
 
+#[cfg(test)]
+mod test_fixtures;
+
 pub use config::DendriteConfig;
 pub use identity::IdentityRegistry;
 pub use semantic::{DendronModel, SemanticModel};
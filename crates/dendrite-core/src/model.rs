@@ -13,6 +13,16 @@ impl NoteId {
     pub fn new() -> Self {
         Self(Uuid::new_v4())
     }
+
+    /// The id of a note declaring `id` in its frontmatter.
+    /// The same frontmatter id always yields the same NoteId.
+    pub fn from_frontmatter_id(id: &str) -> Self {
+        use sha2::{Digest, Sha256};
+        let digest = Sha256::digest(format!("dendrite:note:{}", id).as_bytes());
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&digest[..16]);
+        Self(uuid::Builder::from_custom_bytes(bytes).into_uuid())
+    }
 }
 
 impl Default for NoteId {
@@ -69,11 +79,55 @@ pub struct Note {
     pub blocks: Vec<Block>,
     pub digest: Option<String>,
 }
+
+impl Note {
+    /// Tags from the `tags` frontmatter field (a string or a list of strings).
+    pub fn tags(&self) -> Vec<String> {
+        self.frontmatter_strings("tags")
+    }
+
+    /// The `id` frontmatter field, if it is a non-empty string.
+    pub fn frontmatter_id(&self) -> Option<&str> {
+        frontmatter_id(self.frontmatter.as_ref())
+    }
+
+    /// Alternative names from the `aliases` frontmatter field (a string or a list of strings).
+    pub fn aliases(&self) -> Vec<String> {
+        self.frontmatter_strings("aliases")
+    }
+
+    fn frontmatter_strings(&self, field: &str) -> Vec<String> {
+        match self.frontmatter.as_ref().and_then(|fm| fm.get(field)) {
+            Some(serde_json::Value::String(value)) => vec![value.clone()],
+            Some(serde_json::Value::Array(values)) => values
+                .iter()
+                .filter_map(|value| value.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The `id` field of parsed frontmatter, if it is a non-empty string.
+pub(crate) fn frontmatter_id(frontmatter: Option<&serde_json::Value>) -> Option<&str> {
+    frontmatter?
+        .get("id")?
+        .as_str()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+}
+
 /// Link entity
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Link {
     #[allow(private_interfaces)]
     pub target: NoteId,
+    /// Key the link was written against, before alias resolution (empty if unknown)
+    #[serde(default)]
+    pub target_key: NoteKey,
+    /// Vault named by a vault-qualified link (`[[dendron://vault/key]]`)
+    #[serde(default)]
+    pub target_vault: Option<String>,
     pub raw_target: String,
     pub alias: Option<String>,
     pub anchor: Option<String>,
@@ -134,10 +188,7 @@ mod frontmatter_serde {
     where
         S: Serializer,
     {
-        let s = match value {
-            Some(v) => Some(v.to_string()),
-            None => None,
-        };
+        let s = value.as_ref().map(|v| v.to_string());
         s.serialize(serializer)
     }
 
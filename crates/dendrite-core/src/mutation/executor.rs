@@ -0,0 +1,503 @@
+use crate::line_map::LineMap;
+use crate::mutation::model::{
+    Change, ContentProvider, Diagnostic, DiagnosticSeverity, EditPlan, ResourceOperation, TextEdit,
+};
+use crate::vfs::FileSystem;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Files an executed plan touched, with chained operations collapsed
+/// (a note renamed twice is reported as one rename, a created then renamed
+/// note as written at its final path).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionReport {
+    /// Files created or edited, at their final path
+    pub written: Vec<PathBuf>,
+    pub renamed: Vec<(PathBuf, PathBuf)>,
+    pub deleted: Vec<PathBuf>,
+}
+
+/// One file system operation of a staged plan
+enum Step {
+    Write(PathBuf, String),
+    Rename(PathBuf, PathBuf),
+    Delete(PathBuf),
+}
+
+/// Applies `EditPlan`s to a `FileSystem` without an editor.
+///
+/// A plan is first checked against its preconditions and staged in memory,
+/// so invalid edits are rejected before anything is written. The staged
+/// operations are then performed one by one, journaling the original state
+/// of every file and the directories created for them; if one fails, the
+/// journal is replayed backwards.
+pub struct Executor<'a> {
+    fs: &'a dyn FileSystem,
+}
+
+impl<'a> Executor<'a> {
+    pub fn new(fs: &'a dyn FileSystem) -> Self {
+        Self { fs }
+    }
+
+    pub fn execute(&self, plan: &EditPlan) -> Result<ExecutionReport, Vec<Diagnostic>> {
+        plan.validate(self.fs, self)?;
+        let staged = Staging::run(plan, self).map_err(|error| vec![error])?;
+        self.commit(staged.steps)?;
+        Ok(staged.report)
+    }
+
+    /// Perform the staged steps, restoring every touched file if one fails.
+    fn commit(&self, steps: Vec<Step>) -> Result<(), Vec<Diagnostic>> {
+        // Original bytes of each touched file, None if it did not exist
+        let mut journal: Vec<(PathBuf, Option<Vec<u8>>)> = Vec::new();
+        // Directories the steps create, outermost first
+        let mut created_dirs: Vec<PathBuf> = Vec::new();
+
+        for step in steps {
+            let touched: Vec<&Path> = match &step {
+                Step::Write(path, _) | Step::Delete(path) => vec![path],
+                Step::Rename(from, to) => vec![from, to],
+            };
+            for path in touched {
+                if !journal.iter().any(|(journaled, _)| journaled == path) {
+                    journal.push((path.to_path_buf(), self.fs.read_all(path).ok()));
+                }
+            }
+            if let Step::Write(target, _) | Step::Rename(_, target) = &step {
+                let missing: Vec<&Path> = target
+                    .ancestors()
+                    .skip(1)
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .take_while(|dir| self.fs.metadata(dir).is_err())
+                    .collect();
+                created_dirs.extend(missing.into_iter().rev().map(Path::to_path_buf));
+            }
+
+            let (path, result) = match &step {
+                Step::Write(path, content) => (path, self.fs.write_all(path, content.as_bytes())),
+                Step::Rename(from, to) => (from, self.fs.rename(from, to)),
+                Step::Delete(path) => (path, self.fs.remove_file(path)),
+            };
+            if let Err(err) = result {
+                let mut failures = vec![error(path, &format!("could not be updated: {}", err))];
+                failures.extend(self.rollback(journal, created_dirs));
+                return Err(failures);
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore journaled files, newest first, then remove the directories
+    /// created for them. Returns the paths that could not be restored.
+    fn rollback(
+        &self,
+        journal: Vec<(PathBuf, Option<Vec<u8>>)>,
+        created_dirs: Vec<PathBuf>,
+    ) -> Vec<Diagnostic> {
+        let mut failures = Vec::new();
+        for (path, original) in journal.into_iter().rev() {
+            let result = match original {
+                Some(bytes) => self.fs.write_all(&path, &bytes),
+                None if self.fs.metadata(&path).is_ok() => self.fs.remove_file(&path),
+                None => Ok(()),
+            };
+            if let Err(err) = result {
+                failures.push(error(&path, &format!("could not be restored: {}", err)));
+            }
+        }
+        for dir in created_dirs.into_iter().rev() {
+            if self.fs.metadata(&dir).is_err() {
+                continue;
+            }
+            if let Err(err) = self.fs.remove_dir(&dir) {
+                failures.push(error(&dir, &format!("could not be removed: {}", err)));
+            }
+        }
+        failures
+    }
+}
+
+/// What applying `plan` on top of `content_provider` would do, without
+/// writing anything: the files it touches, and the content of each once
+/// applied (None for files it removes).
+pub(crate) fn dry_run(
+    plan: &EditPlan,
+    content_provider: &dyn ContentProvider,
+) -> Result<(ExecutionReport, HashMap<PathBuf, Option<String>>), Diagnostic> {
+    Staging::run(plan, content_provider).map(|staged| (staged.report, staged.files))
+}
+
+/// A plan resolved change by change against the content it applies to.
+struct Staging<'a> {
+    source: &'a dyn ContentProvider,
+    /// Content of the files as the plan leaves them; None once removed
+    files: HashMap<PathBuf, Option<String>>,
+    steps: Vec<Step>,
+    report: ExecutionReport,
+}
+
+impl<'a> Staging<'a> {
+    fn run(plan: &EditPlan, source: &'a dyn ContentProvider) -> Result<Self, Diagnostic> {
+        let mut staging = Self {
+            source,
+            files: HashMap::new(),
+            steps: Vec::new(),
+            report: ExecutionReport::default(),
+        };
+
+        for group in &plan.edits {
+            let mut path = PathBuf::from(&group.uri);
+            let mut pending: Vec<&TextEdit> = Vec::new();
+
+            for change in &group.changes {
+                let op = match change {
+                    Change::TextEdit(edit) => {
+                        pending.push(edit);
+                        continue;
+                    }
+                    Change::ResourceOp(op) => op,
+                };
+                staging.text_edits(&path, &mut pending)?;
+
+                match op {
+                    ResourceOperation::CreateFile { content } => {
+                        if staging.content(&path).is_some() {
+                            return Err(error(&path, "already exists"));
+                        }
+                        let content = content.clone().unwrap_or_default();
+                        staging.files.insert(path.clone(), Some(content.clone()));
+                        staging.steps.push(Step::Write(path.clone(), content));
+                        mark_written(&mut staging.report, &path);
+                    }
+                    ResourceOperation::DeleteFile {
+                        ignore_if_not_exists,
+                    } => {
+                        if staging.content(&path).is_none() {
+                            if *ignore_if_not_exists {
+                                continue;
+                            }
+                            return Err(error(&path, "does not exist"));
+                        }
+                        staging.files.insert(path.clone(), None);
+                        staging.steps.push(Step::Delete(path.clone()));
+                        mark_deleted(&mut staging.report, &path);
+                    }
+                    ResourceOperation::RenameFile { new_uri, overwrite } => {
+                        let new_path = PathBuf::from(new_uri);
+                        let Some(content) = staging.content(&path) else {
+                            return Err(error(&path, "does not exist"));
+                        };
+                        if !overwrite && staging.content(&new_path).is_some() {
+                            return Err(error(&new_path, "already exists"));
+                        }
+                        staging.files.insert(path.clone(), None);
+                        staging.files.insert(new_path.clone(), Some(content));
+                        staging
+                            .steps
+                            .push(Step::Rename(path.clone(), new_path.clone()));
+                        mark_renamed(&mut staging.report, &path, &new_path);
+                        // Later changes of the group follow the file
+                        path = new_path;
+                    }
+                }
+            }
+            staging.text_edits(&path, &mut pending)?;
+        }
+
+        Ok(staging)
+    }
+
+    /// Apply a run of text edits at once; their ranges refer to the content
+    /// before any of them, like the edits of an LSP `TextDocumentEdit`.
+    fn text_edits(&mut self, path: &Path, pending: &mut Vec<&TextEdit>) -> Result<(), Diagnostic> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let Some(mut content) = self.content(path) else {
+            return Err(error(path, "does not exist"));
+        };
+
+        let line_map = LineMap::new(&content);
+        let mut spans = Vec::new();
+        for edit in pending.drain(..) {
+            let start = line_map.point_to_offset(&content, edit.range.start);
+            let end = line_map.point_to_offset(&content, edit.range.end);
+            match (start, end) {
+                (Some(start), Some(end)) if start <= end => {
+                    spans.push((start, end, edit.new_text.as_str()))
+                }
+                _ => return Err(error(path, "has no text at the edited range")),
+            }
+        }
+        // Back to front, so earlier offsets stay valid
+        spans.sort_by_key(|&(start, end, _)| std::cmp::Reverse((start, end)));
+        if spans.windows(2).any(|pair| pair[1].1 > pair[0].0) {
+            return Err(error(path, "has overlapping edits"));
+        }
+        for (start, end, new_text) in spans {
+            content.replace_range(start..end, new_text);
+        }
+
+        self.files.insert(path.to_path_buf(), Some(content.clone()));
+        self.steps.push(Step::Write(path.to_path_buf(), content));
+        mark_written(&mut self.report, path);
+        Ok(())
+    }
+
+    fn content(&self, path: &Path) -> Option<String> {
+        match self.files.get(path) {
+            Some(content) => content.clone(),
+            None => self.source.get_content(&path.to_string_lossy()),
+        }
+    }
+}
+
+/// Preconditions are checked against the file system itself.
+impl ContentProvider for Executor<'_> {
+    fn get_content(&self, uri: &str) -> Option<String> {
+        self.fs.read_to_string(Path::new(uri)).ok()
+    }
+}
+
+fn error(path: &Path, problem: &str) -> Diagnostic {
+    let uri = path.to_string_lossy().to_string();
+    Diagnostic {
+        severity: DiagnosticSeverity::Error,
+        message: format!("'{}' {}.", uri, problem),
+        uri: Some(uri),
+        range: None,
+    }
+}
+
+fn mark_written(report: &mut ExecutionReport, path: &Path) {
+    if !report.written.iter().any(|written| written == path) {
+        report.written.push(path.to_path_buf());
+    }
+}
+
+fn mark_deleted(report: &mut ExecutionReport, path: &Path) {
+    report.written.retain(|written| written != path);
+    // Deleting a renamed file deletes the original
+    let path = match report.renamed.iter().position(|(_, to)| to == path) {
+        Some(index) => report.renamed.remove(index).0,
+        None => path.to_path_buf(),
+    };
+    report.deleted.push(path);
+}
+
+fn mark_renamed(report: &mut ExecutionReport, from: &Path, to: &Path) {
+    for written in report.written.iter_mut().filter(|written| *written == from) {
+        *written = to.to_path_buf();
+    }
+    match report
+        .renamed
+        .iter_mut()
+        .find(|(_, renamed)| renamed == from)
+    {
+        Some((_, renamed)) => *renamed = to.to_path_buf(),
+        None => report.renamed.push((from.to_path_buf(), to.to_path_buf())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DendriteConfig, VaultConfig, WorkspaceConfig};
+    use crate::model::{Point, TextRange};
+    use crate::mutation::model::{EditGroup, MutationKind};
+    use crate::semantic::DendronModel;
+    use crate::vfs::PhysicalFileSystem;
+    use crate::workspace::{DendriteEngine, Workspace};
+    use std::fs;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn setup(notes: &[(&str, &str)]) -> (DendriteEngine, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        for (key, content) in notes {
+            fs::write(root.join(format!("{}.md", key)), content).unwrap();
+        }
+        let config = DendriteConfig {
+            workspace: WorkspaceConfig {
+                vaults: vec![VaultConfig {
+                    name: "main".to_string(),
+                    path: root.clone(),
+                }],
+                ..DendriteConfig::default().workspace
+            },
+            ..DendriteConfig::default()
+        };
+        let model = Box::new(DendronModel::new(root.clone()));
+        let mut engine =
+            DendriteEngine::new(Workspace::new(config, model), Arc::new(PhysicalFileSystem));
+        engine.initialize(root);
+        (engine, temp_dir)
+    }
+
+    fn insert_at(line: u32, col: u32, text: &str) -> Change {
+        let point = Point { line, col };
+        Change::TextEdit(TextEdit {
+            range: TextRange {
+                start: point,
+                end: point,
+            },
+            new_text: text.to_string(),
+            undo_text: Some(String::new()),
+        })
+    }
+
+    fn group(path: &Path, changes: Vec<Change>) -> EditGroup {
+        EditGroup {
+            uri: path.to_string_lossy().to_string(),
+            changes,
+        }
+    }
+
+    fn plan(edits: Vec<EditGroup>) -> EditPlan {
+        EditPlan {
+            mutation_kind: MutationKind::SplitNote,
+            edits,
+            preconditions: vec![],
+            diagnostics: vec![],
+            reversible: true,
+        }
+    }
+
+    #[test]
+    fn test_execute_rename_and_reindex() {
+        let (mut engine, temp_dir) = setup(&[("a", "# A"), ("ref", "See [[a]].")]);
+        let root = temp_dir.path();
+
+        let plan = engine.rename_note("a", "b").unwrap();
+        let report = engine.apply_edit_plan(&plan).unwrap();
+        assert_eq!(report.renamed, vec![(root.join("a.md"), root.join("b.md"))]);
+        assert_eq!(report.written, vec![root.join("ref.md")]);
+
+        assert!(!root.join("a.md").exists());
+        assert_eq!(fs::read_to_string(root.join("b.md")).unwrap(), "# A");
+        assert_eq!(
+            fs::read_to_string(root.join("ref.md")).unwrap(),
+            "See [[b]]."
+        );
+        let backlinks: Vec<_> = engine
+            .workspace
+            .backlinks_by_key("b")
+            .iter()
+            .map(|note| note.path.clone().unwrap())
+            .collect();
+        assert_eq!(backlinks, vec![root.join("ref.md")]);
+
+        // Applying the same plan again fails its preconditions
+        let errors = engine.apply_edit_plan(&plan).unwrap_err();
+        let messages: Vec<_> = errors.into_iter().map(|d| d.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                format!("'{}' already exists.", root.join("b.md").display()),
+                format!("'{}' no longer exists.", root.join("a.md").display()),
+                format!(
+                    "'{}' changed since the edit was planned.",
+                    root.join("ref.md").display()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_chained_operations() {
+        let (engine, temp_dir) = setup(&[("a", "one\ntwo\n")]);
+        let root = temp_dir.path();
+        let (a, b, c) = (root.join("a.md"), root.join("b.md"), root.join("c.md"));
+
+        let plan = plan(vec![
+            group(
+                &a,
+                vec![
+                    insert_at(1, 0, "1.5\n"),
+                    insert_at(0, 0, "zero\n"),
+                    Change::ResourceOp(ResourceOperation::RenameFile {
+                        new_uri: b.to_string_lossy().to_string(),
+                        overwrite: false,
+                    }),
+                    insert_at(0, 0, "# B\n"),
+                ],
+            ),
+            group(
+                &b,
+                vec![Change::ResourceOp(ResourceOperation::RenameFile {
+                    new_uri: c.to_string_lossy().to_string(),
+                    overwrite: false,
+                })],
+            ),
+        ]);
+        let report = Executor::new(&*engine.fs).execute(&plan).unwrap();
+        assert_eq!(report.renamed, vec![(a.clone(), c.clone())]);
+        assert_eq!(report.written, vec![c.clone()]);
+        assert_eq!(
+            fs::read_to_string(&c).unwrap(),
+            "# B\nzero\none\n1.5\ntwo\n"
+        );
+        assert!(!a.exists() && !b.exists());
+    }
+
+    #[test]
+    fn test_invalid_or_failing_plans_change_nothing() {
+        let (engine, temp_dir) = setup(&[("a", "# A"), ("b", "# B")]);
+        let root = temp_dir.path();
+        let executor = Executor::new(&*engine.fs);
+
+        // Rejected while staging
+        let errors = executor
+            .execute(&plan(vec![
+                group(&root.join("a.md"), vec![insert_at(0, 0, "edited ")]),
+                group(&root.join("b.md"), vec![insert_at(5, 0, "x")]),
+            ]))
+            .unwrap_err();
+        assert_eq!(
+            errors[0].message,
+            format!(
+                "'{}' has no text at the edited range.",
+                root.join("b.md").display()
+            )
+        );
+        assert_eq!(fs::read_to_string(root.join("a.md")).unwrap(), "# A");
+
+        // Fails on disk: the rename target's parent is a file
+        let blocked = root.join("a.md").join("b.md");
+        let errors = executor
+            .execute(&plan(vec![
+                group(&root.join("a.md"), vec![insert_at(0, 0, "edited ")]),
+                group(
+                    &root.join("b.md"),
+                    vec![Change::ResourceOp(ResourceOperation::RenameFile {
+                        new_uri: blocked.to_string_lossy().to_string(),
+                        overwrite: false,
+                    })],
+                ),
+            ]))
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("could not be updated"));
+        assert_eq!(fs::read_to_string(root.join("a.md")).unwrap(), "# A");
+        assert_eq!(fs::read_to_string(root.join("b.md")).unwrap(), "# B");
+
+        // Directories created by earlier steps are removed again
+        let rename_to = |path: &Path| {
+            vec![Change::ResourceOp(ResourceOperation::RenameFile {
+                new_uri: path.to_string_lossy().to_string(),
+                overwrite: false,
+            })]
+        };
+        let errors = executor
+            .execute(&plan(vec![
+                group(&root.join("a.md"), rename_to(&root.join("new/deep/a.md"))),
+                group(&root.join("b.md"), rename_to(&root.join("b.md/b.md"))),
+            ]))
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(fs::read_to_string(root.join("a.md")).unwrap(), "# A");
+        assert!(!root.join("new").exists());
+    }
+}
@@ -0,0 +1,508 @@
+use crate::identity::IdentityRegistry;
+use crate::line_map::LineMap;
+use crate::model::{LinkKind, NoteId, NoteKey, TextRange};
+use crate::mutation::model::{
+    Change, ContentProvider, EditGroup, EditPlan, MutationKind, TextEdit,
+};
+use crate::mutation::structural::{calculate_structural_edits, markdown_link_text, qualified_key};
+use crate::semantic::SemanticModel;
+use crate::store::Store;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Calculate the link updates needed before files are renamed by someone else
+/// (e.g. the editor's file explorer).
+///
+/// Each entry in `renames` may be a single note or a folder; every note at or below
+/// an old path is moved to the matching new path. Links written in moved notes
+/// are rewritten from their new location. The plan only contains TextEdits,
+/// since the file operations themselves are performed by the caller.
+pub(crate) fn calculate_file_rename_edits(
+    store: &Store,
+    identity: &IdentityRegistry,
+    content_provider: &dyn ContentProvider,
+    model: &dyn SemanticModel,
+    renames: &[(PathBuf, PathBuf)],
+) -> Option<EditPlan> {
+    // (note, old path, new path, new key) of every relocated note
+    let mut moves: Vec<(NoteId, &PathBuf, PathBuf, NoteKey)> = store
+        .all_notes()
+        .filter_map(|note| {
+            let path = note.path.as_ref()?;
+            let new_path = renames
+                .iter()
+                .find_map(|(old, new)| relocate(path, old, new))?;
+            let new_key = model.note_key_from_path(&new_path, "");
+            Some((note.id.clone(), path, new_path, new_key))
+        })
+        .collect();
+    moves.sort_by(|a, b| a.1.cmp(b.1));
+    let new_locations: HashMap<&NoteId, (&PathBuf, &NoteKey)> = moves
+        .iter()
+        .map(|(id, _, new_path, new_key)| (id, (new_path, new_key)))
+        .collect();
+    let moved_uris: HashSet<String> = moves
+        .iter()
+        .map(|(_, path, _, _)| path.to_string_lossy().to_string())
+        .collect();
+
+    let mut groups: BTreeMap<String, Vec<Change>> = BTreeMap::new();
+    let mut preconditions = Vec::new();
+
+    for (note_id, _, new_path, new_key) in &moves {
+        if let Some(plan) = calculate_structural_edits(
+            store,
+            identity,
+            content_provider,
+            model,
+            note_id,
+            new_path.clone(),
+            new_key,
+        ) {
+            preconditions.extend(plan.preconditions);
+            for group in plan.edits {
+                // Links in moved notes are rewritten below, from their new place
+                if moved_uris.contains(&group.uri) {
+                    continue;
+                }
+                let text_edits: Vec<_> = group
+                    .changes
+                    .into_iter()
+                    .filter(|change| matches!(change, Change::TextEdit(_)))
+                    .collect();
+                if !text_edits.is_empty() {
+                    groups.entry(group.uri).or_default().extend(text_edits);
+                }
+            }
+        }
+    }
+
+    for (note_id, path, new_path, _) in &moves {
+        let Some(note) = store.get_note(note_id) else {
+            continue;
+        };
+        let uri = path.to_string_lossy().to_string();
+
+        for link in &note.links {
+            let target_move = new_locations.get(&link.target);
+            let new_text = match link.kind {
+                LinkKind::WikiLink(_) | LinkKind::EmbeddedWikiLink(_) => {
+                    // Links written against one of the target's aliases keep working
+                    let Some((_, target_key)) = target_move else {
+                        continue;
+                    };
+                    let via_alias = !link.target_key.is_empty()
+                        && identity.key_of(&link.target).as_ref() != Some(&link.target_key);
+                    if via_alias || identity.key_of(&link.target).as_ref() == Some(*target_key) {
+                        continue;
+                    }
+                    model.format_wikilink(
+                        &qualified_key(link, target_key),
+                        link.alias.as_deref(),
+                        link.anchor.as_deref(),
+                        matches!(link.kind, LinkKind::EmbeddedWikiLink(_)),
+                    )
+                }
+                LinkKind::MarkdownLink => {
+                    let target = match target_move {
+                        Some((path, key)) => Some(((*path).clone(), (*key).clone())),
+                        None => store.get_note(&link.target).and_then(|target| {
+                            Some((target.path.clone()?, identity.key_of(&link.target)?))
+                        }),
+                    };
+                    let Some((target_path, target_key)) = target else {
+                        continue;
+                    };
+                    markdown_link_text(model, link, Some(new_path), &target_path, &target_key)
+                }
+                _ => continue,
+            };
+
+            let undo_text = original_text(content_provider, &uri, link.range);
+            if undo_text.as_deref() != Some(new_text.as_str()) {
+                groups
+                    .entry(uri.clone())
+                    .or_default()
+                    .push(Change::TextEdit(TextEdit {
+                        range: link.range,
+                        new_text,
+                        undo_text,
+                    }));
+            }
+        }
+    }
+
+    build_plan(
+        MutationKind::MoveNote,
+        groups,
+        preconditions,
+        content_provider,
+    )
+}
+
+/// Calculate the link updates needed before files are deleted by someone else.
+///
+/// References to deleted notes are replaced by their label (alias, or the raw target),
+/// so the surrounding text still reads naturally. Links inside notes that are
+/// deleted as well are left alone.
+pub(crate) fn calculate_file_delete_edits(
+    store: &Store,
+    content_provider: &dyn ContentProvider,
+    paths: &[PathBuf],
+) -> Option<EditPlan> {
+    let deleted: HashSet<NoteId> = store
+        .all_notes()
+        .filter(|note| {
+            note.path
+                .as_ref()
+                .is_some_and(|path| paths.iter().any(|p| path.starts_with(p)))
+        })
+        .map(|note| note.id.clone())
+        .collect();
+
+    let mut groups: BTreeMap<String, Vec<Change>> = BTreeMap::new();
+
+    for note in store.all_notes() {
+        if deleted.contains(&note.id) {
+            continue;
+        }
+        let Some(path) = &note.path else {
+            continue;
+        };
+        let uri = path.to_string_lossy().to_string();
+
+        let changes: Vec<Change> = note
+            .links
+            .iter()
+            .filter(|link| deleted.contains(&link.target))
+            .map(|link| {
+                Change::TextEdit(TextEdit {
+                    range: link.range,
+                    new_text: link
+                        .alias
+                        .clone()
+                        .unwrap_or_else(|| link.raw_target.clone()),
+                    undo_text: original_text(content_provider, &uri, link.range),
+                })
+            })
+            .collect();
+
+        if !changes.is_empty() {
+            groups.entry(uri).or_default().extend(changes);
+        }
+    }
+
+    build_plan(MutationKind::DeleteNote, groups, vec![], content_provider)
+}
+
+/// Calculate link updates for notes renamed by someone else, once the index
+/// already reflects the renames (e.g. files moved by `git checkout`).
+///
+/// Every link still written against an old key is pointed at the new note.
+pub(crate) fn calculate_external_rename_edits(
+    store: &Store,
+    identity: &IdentityRegistry,
+    content_provider: &dyn ContentProvider,
+    model: &dyn SemanticModel,
+    renames: &[(PathBuf, PathBuf)],
+) -> Option<EditPlan> {
+    let new_keys: HashMap<NoteKey, (&PathBuf, NoteKey)> = renames
+        .iter()
+        .filter_map(|(old_path, new_path)| {
+            let new_id = store.note_id_by_path(new_path)?;
+            Some((
+                model.note_key_from_path(old_path, ""),
+                (new_path, identity.key_of(new_id)?),
+            ))
+        })
+        .filter(|(old_key, (_, new_key))| old_key != new_key)
+        .collect();
+
+    let mut groups: BTreeMap<String, Vec<Change>> = BTreeMap::new();
+    for note in store.all_notes() {
+        let Some(path) = &note.path else {
+            continue;
+        };
+        let uri = path.to_string_lossy().to_string();
+
+        for link in &note.links {
+            let Some((new_path, new_key)) = new_keys.get(&link.target_key) else {
+                continue;
+            };
+            let new_text = match link.kind {
+                LinkKind::WikiLink(_) | LinkKind::EmbeddedWikiLink(_) => model.format_wikilink(
+                    new_key,
+                    link.alias.as_deref(),
+                    link.anchor.as_deref(),
+                    matches!(link.kind, LinkKind::EmbeddedWikiLink(_)),
+                ),
+                LinkKind::MarkdownLink => {
+                    markdown_link_text(model, link, Some(path), new_path, new_key)
+                }
+                _ => continue,
+            };
+            groups
+                .entry(uri.clone())
+                .or_default()
+                .push(Change::TextEdit(TextEdit {
+                    range: link.range,
+                    new_text,
+                    undo_text: original_text(content_provider, &uri, link.range),
+                }));
+        }
+    }
+
+    build_plan(MutationKind::MoveNote, groups, vec![], content_provider)
+}
+
+/// Map `path` through a file or folder rename, if it is affected by it.
+fn relocate(path: &Path, old: &Path, new: &Path) -> Option<PathBuf> {
+    if path == old {
+        return Some(new.to_path_buf());
+    }
+    path.strip_prefix(old).ok().map(|rest| new.join(rest))
+}
+
+fn original_text(
+    content_provider: &dyn ContentProvider,
+    uri: &str,
+    range: TextRange,
+) -> Option<String> {
+    let content = content_provider.get_content(uri)?;
+    let line_map = LineMap::new(&content);
+    let start = line_map.point_to_offset(&content, range.start)?;
+    let end = line_map.point_to_offset(&content, range.end)?;
+    content.get(start..end).map(str::to_string)
+}
+
+fn build_plan(
+    mutation_kind: MutationKind,
+    groups: BTreeMap<String, Vec<Change>>,
+    preconditions: Vec<crate::mutation::model::Precondition>,
+    content_provider: &dyn ContentProvider,
+) -> Option<EditPlan> {
+    if groups.is_empty() {
+        return None;
+    }
+
+    Some(
+        EditPlan {
+            mutation_kind,
+            edits: groups
+                .into_iter()
+                .map(|(uri, changes)| EditGroup { uri, changes })
+                .collect(),
+            preconditions,
+            diagnostics: vec![],
+            reversible: true,
+        }
+        .with_file_preconditions(content_provider),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::setup_files;
+    use crate::vfs::PhysicalFileSystem;
+
+    fn text_edits(plan: &EditPlan, uri: &str) -> Vec<(String, Option<String>)> {
+        plan.edits
+            .iter()
+            .filter(|group| group.uri == uri)
+            .flat_map(|group| &group.changes)
+            .map(|change| match change {
+                Change::TextEdit(edit) => (edit.new_text.clone(), edit.undo_text.clone()),
+                other => panic!("Unexpected change: {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_file_rename_updates_links_without_resource_ops() {
+        let (ws, provider) = setup_files(&[
+            ("a.md", "# A"),
+            ("b.md", "# B"),
+            ("c.md", "See [[a]] and [[Bee|b#intro]]"),
+        ]);
+
+        let renames = vec![
+            (PathBuf::from("/test/a.md"), PathBuf::from("/test/x.md")),
+            (PathBuf::from("/test/b.md"), PathBuf::from("/test/y.md")),
+        ];
+        let plan = calculate_file_rename_edits(
+            &ws.store,
+            &ws.identity,
+            &provider,
+            ws.model.as_ref(),
+            &renames,
+        )
+        .unwrap();
+
+        // Both notes' backlinks are merged into a single group for c.md
+        assert_eq!(plan.edits.len(), 1);
+        let mut edits = text_edits(&plan, "/test/c.md");
+        edits.sort();
+        assert_eq!(
+            edits,
+            vec![
+                (
+                    "[[Bee|y#intro]]".to_string(),
+                    Some("[[Bee|b#intro]]".to_string())
+                ),
+                ("[[x]]".to_string(), Some("[[a]]".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_folder_rename_moves_contained_notes() {
+        let (ws, provider) = setup_files(&[
+            ("old/a.md", "# A"),
+            ("old/deep/b.md", "# B"),
+            ("c.md", "[A](a.md) [B](b.md)"),
+        ]);
+
+        let renames = vec![(PathBuf::from("/test/old"), PathBuf::from("/test/new"))];
+        let plan = calculate_file_rename_edits(
+            &ws.store,
+            &ws.identity,
+            &provider,
+            ws.model.as_ref(),
+            &renames,
+        )
+        .unwrap();
+
+        let mut edits: Vec<_> = text_edits(&plan, "/test/c.md")
+            .into_iter()
+            .map(|(new_text, _)| new_text)
+            .collect();
+        edits.sort();
+        assert_eq!(edits, vec!["[A](new/a.md)", "[B](new/deep/b.md)"]);
+    }
+
+    #[test]
+    fn test_folder_rename_to_other_depth_rewrites_links_of_moved_notes() {
+        let (ws, provider) = setup_files(&[
+            ("old/a.md", "[B](deep/b.md) [C](../c.md)"),
+            ("old/deep/b.md", "[A](../a.md)"),
+            ("c.md", "[A](old/a.md)"),
+        ]);
+
+        let renames = vec![(
+            PathBuf::from("/test/old"),
+            PathBuf::from("/test/archive/new"),
+        )];
+        let plan = calculate_file_rename_edits(
+            &ws.store,
+            &ws.identity,
+            &provider,
+            ws.model.as_ref(),
+            &renames,
+        )
+        .unwrap();
+
+        // Links between notes moving together keep their relative paths
+        assert!(text_edits(&plan, "/test/old/deep/b.md").is_empty());
+        assert_eq!(
+            text_edits(&plan, "/test/old/a.md"),
+            vec![(
+                "[C](../../c.md)".to_string(),
+                Some("[C](../c.md)".to_string())
+            )]
+        );
+        assert_eq!(
+            text_edits(&plan, "/test/c.md"),
+            vec![(
+                "[A](archive/new/a.md)".to_string(),
+                Some("[A](old/a.md)".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_external_renames_keep_identity_and_fix_links() {
+        let moved = "---\nid: n1\n---\n# Moved";
+        let (mut ws, provider) = setup_files(&[
+            ("old.md", moved),
+            ("plain.md", "# Same content"),
+            ("other.md", "# Same content"),
+            ("ref.md", "[[old]] [[plain#same-content]] [Old](old.md)"),
+        ]);
+        let old_id = ws
+            .store
+            .note_id_by_path(&PathBuf::from("/test/old.md"))
+            .cloned();
+
+        // One batch of watcher events: renamed files show up as delete + create
+        let deleted = vec![
+            PathBuf::from("/test/old.md"),
+            PathBuf::from("/test/plain.md"),
+            PathBuf::from("/test/other.md"),
+        ];
+        let upserted = vec![
+            (
+                PathBuf::from("/test/new.md"),
+                moved.replace("Moved", "Edited"),
+            ),
+            (PathBuf::from("/test/a.md"), "# Same content".to_string()),
+            (PathBuf::from("/test/b.md"), "# Same content".to_string()),
+        ];
+        let renames = ws.sync_file_changes(&deleted, &upserted, &PhysicalFileSystem);
+
+        // Matched by frontmatter id despite the edit; identical bodies are ambiguous
+        assert_eq!(
+            renames,
+            vec![(PathBuf::from("/test/old.md"), PathBuf::from("/test/new.md"))]
+        );
+        assert_eq!(
+            ws.store
+                .note_id_by_path(&PathBuf::from("/test/new.md"))
+                .cloned(),
+            old_id
+        );
+        assert!(ws
+            .store
+            .note_id_by_path(&PathBuf::from("/test/plain.md"))
+            .is_none());
+        assert!(ws
+            .store
+            .note_id_by_path(&PathBuf::from("/test/a.md"))
+            .is_some());
+
+        let plan = ws.external_rename_edits(&provider, &renames).unwrap();
+        assert_eq!(
+            text_edits(&plan, "/test/ref.md"),
+            vec![
+                ("[[new]]".to_string(), Some("[[old]]".to_string())),
+                (
+                    "[Old](new.md)".to_string(),
+                    Some("[Old](old.md)".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_delete_unlinks_references() {
+        let (ws, provider) = setup_files(&[
+            ("gone.md", "Self link [[c]]"),
+            ("c.md", "See [[gone]] and [[Label|gone#h]]"),
+        ]);
+
+        let plan =
+            calculate_file_delete_edits(&ws.store, &provider, &[PathBuf::from("/test/gone.md")])
+                .unwrap();
+
+        assert_eq!(plan.mutation_kind, MutationKind::DeleteNote);
+        assert_eq!(plan.edits.len(), 1);
+        let edits = text_edits(&plan, "/test/c.md");
+        assert_eq!(
+            edits,
+            vec![
+                ("gone".to_string(), Some("[[gone]]".to_string())),
+                ("Label".to_string(), Some("[[Label|gone#h]]".to_string())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use crate::identity::IdentityRegistry;
+use crate::line_map::LineMap;
+use crate::model::{LinkKind, NoteKey, TextRange};
+use crate::mutation::model::{Change, EditGroup, EditPlan, MutationKind, TextEdit};
+use crate::parser::{parse_markdown, DocLink};
+use crate::semantic::SemanticModel;
+use crate::slugify_heading;
+use crate::store::Store;
+use std::path::Path;
+
+/// Calculate edits that normalize a note's link syntax and frontmatter.
+///
+/// - Markdown links to notes in the workspace become wikilinks
+/// - Wikilinks are re-emitted through `SemanticModel::format_wikilink` (alias order, spacing)
+/// - Heading anchors are slugified (`[[note#My Heading]]` -> `[[note#my-heading]]`)
+/// - Frontmatter fields follow `SemanticModel::frontmatter_field_order`
+///
+/// `content` is the current text of the note at `path`. When `range` is given, only
+/// edits fully inside it are returned.
+pub(crate) fn calculate_format_edits(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+    path: &Path,
+    content: &str,
+    range: Option<TextRange>,
+) -> Option<EditPlan> {
+    let source_key = model.note_key_from_path(path, content);
+    let line_map = LineMap::new(content);
+    let parsed = parse_markdown(content, &model.supported_link_kinds());
+
+    let mut changes = Vec::new();
+
+    if let Some(edit) = format_frontmatter(content, &line_map, model) {
+        changes.push(edit);
+    }
+
+    for link in &parsed.links {
+        let Some(new_text) = format_link(store, identity, model, &source_key, link) else {
+            continue;
+        };
+        let start = line_map.point_to_offset(content, link.range.start)?;
+        let end = line_map.point_to_offset(content, link.range.end)?;
+        let old_text = content.get(start..end)?;
+        if old_text != new_text {
+            changes.push(TextEdit {
+                range: link.range,
+                new_text,
+                undo_text: Some(old_text.to_string()),
+            });
+        }
+    }
+
+    if let Some(range) = range {
+        changes.retain(|edit| contains(&range, &edit.range));
+    }
+
+    if changes.is_empty() {
+        return None;
+    }
+
+    Some(EditPlan {
+        mutation_kind: MutationKind::FormatNote,
+        edits: vec![EditGroup {
+            uri: path.to_string_lossy().to_string(),
+            changes: changes.into_iter().map(Change::TextEdit).collect(),
+        }],
+        preconditions: vec![],
+        diagnostics: vec![],
+        reversible: true,
+    })
+}
+
+/// Canonical text for a link, or None if it should be left as written.
+fn format_link(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+    source_key: &NoteKey,
+    link: &DocLink,
+) -> Option<String> {
+    let anchor = link.anchor.as_deref().map(normalize_anchor);
+
+    match link.kind {
+        LinkKind::WikiLink(_) | LinkKind::EmbeddedWikiLink(_) => Some(model.format_wikilink(
+            &link.target,
+            link.alias.as_deref().filter(|alias| *alias != link.target),
+            anchor.as_deref(),
+            matches!(link.kind, LinkKind::EmbeddedWikiLink(_)),
+        )),
+        LinkKind::MarkdownLink => {
+            let lower_target = link.target.to_lowercase();
+            if link.target.is_empty()
+                || lower_target.contains("://")
+                || lower_target.starts_with("mailto:")
+            {
+                return None;
+            }
+
+            // Only convert links that point at an existing note
+            let key = model.note_key_from_link(source_key, &link.target);
+            let target_id = identity.lookup(&key)?;
+            store.get_note(&target_id)?;
+
+            Some(model.format_wikilink(
+                &key,
+                link.alias.as_deref().filter(|alias| *alias != key),
+                anchor.as_deref(),
+                false,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Slugify heading anchors; block anchors and `start:#end` ranges keep their shape.
+fn normalize_anchor(anchor: &str) -> String {
+    anchor
+        .split(":#")
+        .map(|part| {
+            if part.starts_with('^') {
+                part.to_string()
+            } else {
+                slugify_heading(part)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(":#")
+}
+
+/// Reorder top-level frontmatter fields. Unknown fields keep their relative order
+/// after the known ones; comments and nested lines travel with the field below/above them.
+fn format_frontmatter(
+    content: &str,
+    line_map: &LineMap,
+    model: &dyn SemanticModel,
+) -> Option<TextEdit> {
+    let order = model.frontmatter_field_order();
+    if order.is_empty() {
+        return None;
+    }
+
+    // Locate the lines between the opening and closing `---`
+    let mut lines = content.split_inclusive('\n');
+    let first = lines.next()?;
+    if first.trim_end() != "---" {
+        return None;
+    }
+    let body_start = first.len();
+    let mut body_end = body_start;
+    let mut closed = false;
+    for line in lines {
+        if line.trim_end() == "---" || line.trim_end() == "..." {
+            closed = true;
+            break;
+        }
+        body_end += line.len();
+    }
+    if !closed || body_end == body_start {
+        return None;
+    }
+    let body = &content[body_start..body_end];
+
+    // Split into entries: a top-level `key:` line plus its continuation lines
+    struct Entry<'a> {
+        key: Option<&'a str>,
+        text: String,
+    }
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut pending_comments = String::new();
+    for line in body.split_inclusive('\n') {
+        let is_top_level = !line.starts_with([' ', '\t', '-']) && !line.trim().is_empty();
+        if is_top_level && line.starts_with('#') {
+            pending_comments.push_str(line);
+        } else if is_top_level {
+            let key = line.split_once(':').map(|(key, _)| key.trim());
+            entries.push(Entry {
+                key,
+                text: std::mem::take(&mut pending_comments) + line,
+            });
+        } else if let Some(entry) = entries.last_mut() {
+            entry.text.push_str(line);
+        } else {
+            pending_comments.push_str(line);
+        }
+    }
+
+    let rank = |entry: &Entry| {
+        entry
+            .key
+            .and_then(|key| order.iter().position(|known| *known == key))
+            .unwrap_or(order.len())
+    };
+    let mut sorted: Vec<&Entry> = entries.iter().collect();
+    sorted.sort_by_key(|entry| rank(entry));
+
+    // Every entry must end with a newline once it may be moved
+    let mut new_body = String::with_capacity(body.len());
+    for entry in sorted {
+        new_body.push_str(&entry.text);
+        if !new_body.ends_with('\n') {
+            new_body.push('\n');
+        }
+    }
+    new_body.push_str(&pending_comments);
+
+    if new_body == body {
+        return None;
+    }
+
+    Some(TextEdit {
+        range: TextRange {
+            start: line_map.offset_to_point(content, body_start),
+            end: line_map.offset_to_point(content, body_end),
+        },
+        new_text: new_body,
+        undo_text: Some(body.to_string()),
+    })
+}
+
+fn contains(outer: &TextRange, inner: &TextRange) -> bool {
+    let key = |p: &crate::model::Point| (p.line, p.col);
+    key(&outer.start) <= key(&inner.start) && key(&inner.end) <= key(&outer.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::test_fixtures;
+    use crate::workspace::Workspace;
+
+    fn setup(notes: &[(&str, &str)]) -> Workspace {
+        test_fixtures::setup(notes).0
+    }
+
+    fn format(ws: &Workspace, content: &str, range: Option<TextRange>) -> Vec<(TextRange, String)> {
+        calculate_format_edits(
+            &ws.store,
+            &ws.identity,
+            ws.model.as_ref(),
+            Path::new("/test/source.md"),
+            content,
+            range,
+        )
+        .map(|plan| {
+            plan.edits
+                .into_iter()
+                .flat_map(|group| group.changes)
+                .map(|change| match change {
+                    Change::TextEdit(edit) => (edit.range, edit.new_text),
+                    other => panic!("Unexpected change: {:?}", other),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_format_links() {
+        let ws = setup(&[("target", "# Target\n\n## My Heading")]);
+        let content = "[Label](target.md) [[ target ]] ![[target#My Heading]] [[target|target]] [ext](https://example.com) [gone](missing.md)";
+
+        let new_texts: Vec<_> = format(&ws, content, None)
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect();
+        assert_eq!(
+            new_texts,
+            vec![
+                "[[Label|target]]",
+                "[[target]]",
+                "![[target#my-heading]]",
+                "[[target]]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_block_anchors_and_canonical_links() {
+        let ws = setup(&[("target", "Para ^blk")]);
+        assert!(format(
+            &ws,
+            "[[Alias|target#^blk]] [[target#^begin:#Some Part]]",
+            None
+        )
+        .iter()
+        .all(|(_, text)| text == "[[target#^begin:#some-part]]"));
+    }
+
+    #[test]
+    fn test_format_frontmatter_order() {
+        let ws = setup(&[]);
+        let content =
+            "---\ncreated: 2\n# note about tags\ntags:\n  - a\nid: x\ntitle: T\n---\nBody";
+
+        let edits = format(&ws, content, None);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(
+            edits[0].1,
+            "id: x\ntitle: T\ncreated: 2\n# note about tags\ntags:\n  - a\n"
+        );
+        assert_eq!(edits[0].0.start, Point { line: 1, col: 0 });
+        assert_eq!(edits[0].0.end, Point { line: 7, col: 0 });
+    }
+
+    #[test]
+    fn test_range_formatting() {
+        let ws = setup(&[("target", "# Target")]);
+        let content = "[A](target.md)\n[B](target.md)";
+        let second_line = TextRange {
+            start: Point { line: 1, col: 0 },
+            end: Point { line: 1, col: 14 },
+        };
+
+        let edits = format(&ws, content, Some(second_line));
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].1, "[[B|target]]");
+        assert_eq!(edits[0].0.start.line, 1);
+    }
+}
@@ -1,8 +1,15 @@
 use crate::identity::IdentityRegistry;
-use crate::mutation::model::{ContentProvider, EditPlan, MutationKind};
+use crate::model::NoteKey;
+use crate::mutation::model::{
+    Change, ContentProvider, Diagnostic, DiagnosticSeverity, EditGroup, EditPlan, MutationKind,
+    ResourceOperation,
+};
 use crate::mutation::structural::calculate_structural_edits;
 use crate::semantic::SemanticModel;
 use crate::store::Store;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 /// Calculate batch edits for renaming a hierarchy node (and its descendants).
 pub fn calculate_hierarchy_edits(
@@ -96,35 +103,227 @@ pub fn calculate_hierarchy_edits(
         return None;
     }
 
-    Some(EditPlan {
-        mutation_kind: MutationKind::HierarchyRefactor,
-        edits: all_edits,
-        preconditions: vec![],
-        diagnostics: all_diagnostics,
-        reversible: true,
-    })
+    Some(
+        EditPlan {
+            mutation_kind: MutationKind::HierarchyRefactor,
+            edits: all_edits,
+            preconditions: vec![],
+            diagnostics: all_diagnostics,
+            reversible: true,
+        }
+        .with_file_preconditions(content_provider),
+    )
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::model::{Link, LinkKind, Note};
-    use crate::semantic::DendronModel;
-    use std::path::PathBuf;
+/// A note key a refactor would change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyMapping {
+    pub old_key: NoteKey,
+    pub new_key: NoteKey,
+}
 
-    use std::collections::HashMap;
+/// The outcome of a pattern-based hierarchy refactor, for review before applying it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HierarchyRefactorReport {
+    /// Every key that would change, sorted by old key
+    pub mappings: Vec<KeyMapping>,
+    /// Keys mapped onto the same key or onto an existing note; no plan is made if any
+    pub collisions: Vec<Diagnostic>,
+    pub plan: Option<EditPlan>,
+}
+
+/// Calculate edits renaming every note whose key matches `pattern` (Dendron's
+/// "Refactor Hierarchy"), e.g. `^daily\.journal\.(\d+)` to `journal.$1`.
+///
+/// Only notes in the `scope` hierarchy (the note and its descendants) are
+/// considered. The first match in each key is replaced, with `$1`-style
+/// capture group references.
+pub(crate) fn calculate_pattern_hierarchy_edits(
+    store: &Store,
+    identity: &IdentityRegistry,
+    content_provider: &dyn ContentProvider,
+    model: &dyn SemanticModel,
+    pattern: &str,
+    replacement: &str,
+    scope: Option<&str>,
+) -> Result<HierarchyRefactorReport, Vec<Diagnostic>> {
+    let regex = Regex::new(pattern)
+        .map_err(|err| vec![error(format!("Invalid pattern '{}': {}", pattern, err))])?;
+
+    let mut renames = Vec::new();
+    for note in store.all_notes() {
+        if note.path.is_none() {
+            continue;
+        }
+        let Some(key) = identity.key_of(&note.id) else {
+            continue;
+        };
+        let in_scope =
+            scope.is_none_or(|scope| key == scope || model.is_descendant(&key, &scope.to_string()));
+        if !in_scope || !regex.is_match(&key) {
+            continue;
+        }
+        let new_key = regex.replace(&key, replacement).to_string();
+        if new_key != key {
+            renames.push((note.id.clone(), key, new_key));
+        }
+    }
+    renames.sort_by(|a, b| a.1.cmp(&b.1));
 
-    struct MockProvider {
-        files: HashMap<String, String>,
+    let mappings: Vec<KeyMapping> = renames
+        .iter()
+        .map(|(_, old_key, new_key)| KeyMapping {
+            old_key: old_key.clone(),
+            new_key: new_key.clone(),
+        })
+        .collect();
+    let collisions = key_collisions(store, identity, &mappings);
+    if !collisions.is_empty() || renames.is_empty() {
+        return Ok(HierarchyRefactorReport {
+            mappings,
+            collisions,
+            plan: None,
+        });
     }
 
-    impl ContentProvider for MockProvider {
-        fn get_content(&self, uri: &str) -> Option<String> {
-            self.files.get(uri).cloned()
+    // Text edits per file are combined so each file's edits refer to the same
+    // content; renames come after all of them, each once its target is vacated
+    let mut text_edits: BTreeMap<String, Vec<Change>> = BTreeMap::new();
+    let mut moves: Vec<EditGroup> = Vec::new();
+    let mut diagnostics = Vec::new();
+    for (note_id, _, new_key) in &renames {
+        let new_path = model.path_from_note_key(new_key);
+        let Some(plan) = calculate_structural_edits(
+            store,
+            identity,
+            content_provider,
+            model,
+            note_id,
+            new_path,
+            new_key,
+        ) else {
+            continue;
+        };
+        diagnostics.extend(plan.diagnostics);
+        for group in plan.edits {
+            for change in group.changes {
+                match change {
+                    Change::TextEdit(_) => text_edits
+                        .entry(group.uri.clone())
+                        .or_default()
+                        .push(change),
+                    Change::ResourceOp(_) => moves.push(EditGroup {
+                        uri: group.uri.clone(),
+                        changes: vec![change],
+                    }),
+                }
+            }
         }
     }
 
+    let mut edits: Vec<EditGroup> = text_edits
+        .into_iter()
+        .map(|(uri, changes)| EditGroup { uri, changes })
+        .collect();
+    edits.extend(order_moves(moves));
+
+    Ok(HierarchyRefactorReport {
+        mappings,
+        collisions,
+        plan: Some(
+            EditPlan {
+                mutation_kind: MutationKind::HierarchyRefactor,
+                edits,
+                preconditions: vec![],
+                diagnostics,
+                reversible: true,
+            }
+            .with_file_preconditions(content_provider),
+        ),
+    })
+}
+
+/// Keys that would be taken twice, by two renamed notes or by a renamed note
+/// and one staying where it is, and keys that are not valid. Keys of ghost
+/// levels and dangling links are free to take.
+fn key_collisions(
+    store: &Store,
+    identity: &IdentityRegistry,
+    mappings: &[KeyMapping],
+) -> Vec<Diagnostic> {
+    let mut collisions = Vec::new();
+    let mut claimed: HashMap<&str, &str> = HashMap::new();
+    for mapping in mappings {
+        let new_key = mapping.new_key.as_str();
+        if new_key.is_empty() || new_key.split('.').any(str::is_empty) {
+            collisions.push(error(format!(
+                "'{}' would become '{}', which is not a valid key.",
+                mapping.old_key, new_key
+            )));
+            continue;
+        }
+        if let Some(other) = claimed.insert(new_key, &mapping.old_key) {
+            collisions.push(error(format!(
+                "'{}' and '{}' would both become '{}'.",
+                other, mapping.old_key, new_key
+            )));
+        }
+        let renamed_away = mappings.iter().any(|m| m.old_key == new_key);
+        let exists = identity
+            .lookup(&mapping.new_key)
+            .and_then(|id| store.get_note(&id))
+            .is_some_and(|note| note.path.is_some());
+        if exists && !renamed_away {
+            collisions.push(error(format!(
+                "'{}' would become '{}', which already exists.",
+                mapping.old_key, new_key
+            )));
+        }
+    }
+    collisions
+}
+
+/// Order renames so that none moves onto a file another has yet to move away.
+/// Renames going round in a cycle keep their order (and fail the plan's checks).
+fn order_moves(mut pending: Vec<EditGroup>) -> Vec<EditGroup> {
+    let new_uri = |group: &EditGroup| match group.changes.first() {
+        Some(Change::ResourceOp(ResourceOperation::RenameFile { new_uri, .. })) => {
+            Some(new_uri.clone())
+        }
+        _ => None,
+    };
+
+    let mut ordered = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|group| {
+            let target = new_uri(group);
+            !pending
+                .iter()
+                .any(|other| Some(&other.uri) == target.as_ref())
+        });
+        ordered.push(pending.remove(ready.unwrap_or(0)));
+    }
+    ordered
+}
+
+fn error(message: String) -> Diagnostic {
+    Diagnostic {
+        severity: DiagnosticSeverity::Error,
+        message,
+        uri: None,
+        range: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Link, LinkKind, Note};
     use crate::model::{Point, TextRange};
+    use crate::semantic::DendronModel;
+    use crate::test_fixtures::{setup, MockProvider};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
 
     #[test]
     fn test_hierarchy_rename() {
@@ -227,4 +426,131 @@ mod tests {
         // assert!(new_texts.contains(&"[[x]]".to_string()));
         // assert!(new_texts.contains(&"[[x.b]]".to_string()));
     }
+
+    #[test]
+    fn test_pattern_hierarchy_refactor() {
+        let (ws, provider) = setup(&[
+            ("daily.journal.2024.01", "# Jan\nNext: [[daily.journal.2024.02]]"),
+            ("daily.journal.2024.02", "# Feb"),
+            ("archive.journal.2023.12", "# Dec"),
+            (
+                "index",
+                "[[daily.journal.2024.01]], [[daily.journal.2024.02]] and [[archive.journal.2023.12]]",
+            ),
+        ]);
+
+        let report = ws
+            .refactor_hierarchy(
+                &provider,
+                r"journal\.(\d+)\.(\d+)$",
+                "journal.$1-$2",
+                Some("daily"),
+            )
+            .unwrap();
+        let mappings: Vec<_> = report
+            .mappings
+            .iter()
+            .map(|m| (m.old_key.as_str(), m.new_key.as_str()))
+            .collect();
+        assert_eq!(
+            mappings,
+            vec![
+                ("daily.journal.2024.01", "daily.journal.2024-01"),
+                ("daily.journal.2024.02", "daily.journal.2024-02"),
+            ]
+        );
+        assert!(report.collisions.is_empty());
+
+        let plan = report.plan.unwrap();
+        let (executed, contents) = crate::mutation::executor::dry_run(&plan, &provider).unwrap();
+        assert_eq!(executed.renamed.len(), 2);
+        assert_eq!(
+            contents[&PathBuf::from("/test/index.md")].as_deref(),
+            Some("[[daily.journal.2024-01]], [[daily.journal.2024-02]] and [[archive.journal.2023.12]]")
+        );
+        assert_eq!(
+            contents[&PathBuf::from("/test/daily.journal.2024-01.md")].as_deref(),
+            Some("# Jan\nNext: [[daily.journal.2024-02]]")
+        );
+    }
+
+    #[test]
+    fn test_pattern_hierarchy_refactor_collisions() {
+        let (ws, provider) = setup(&[
+            ("daily.journal.2024.01", "# Jan"),
+            ("daily.journal.2024.02", "# Feb"),
+            ("journal.2023", "# 2023"),
+            ("daily.journal.2023.12", "# Dec"),
+        ]);
+
+        let report = ws
+            .refactor_hierarchy(
+                &provider,
+                r"^daily\.journal\.(\d+)\.\d+",
+                "journal.$1",
+                None,
+            )
+            .unwrap();
+        assert_eq!(report.mappings.len(), 3);
+        assert!(report.plan.is_none());
+        let messages: Vec<_> = report
+            .collisions
+            .iter()
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "'daily.journal.2023.12' would become 'journal.2023', which already exists.",
+                "'daily.journal.2024.01' and 'daily.journal.2024.02' would both become 'journal.2024'.",
+            ]
+        );
+
+        let errors = ws
+            .refactor_hierarchy(&provider, "(", "x", None)
+            .unwrap_err();
+        assert!(errors[0].message.starts_with("Invalid pattern '('"));
+    }
+
+    #[test]
+    fn test_pattern_hierarchy_refactor_chained_keys() {
+        // "xxa" takes the key "xa" vacates
+        let (ws, provider) = setup(&[("xxa", "# XXA"), ("xa", "# XA")]);
+
+        let report = ws.refactor_hierarchy(&provider, "^x", "", None).unwrap();
+        assert!(report.collisions.is_empty());
+        let (_, contents) =
+            crate::mutation::executor::dry_run(&report.plan.unwrap(), &provider).unwrap();
+        assert_eq!(
+            contents[&PathBuf::from("/test/a.md")].as_deref(),
+            Some("# XA")
+        );
+        assert_eq!(
+            contents[&PathBuf::from("/test/xa.md")].as_deref(),
+            Some("# XXA")
+        );
+        assert_eq!(contents[&PathBuf::from("/test/xxa.md")], None);
+    }
+
+    #[test]
+    fn test_pattern_hierarchy_refactor_onto_ghost_key() {
+        let (mut ws, provider) = setup(&[("a.b.c", "# C"), ("ref", "[[a.b]] [[a.b.c]]")]);
+        // 'a' and 'a.b' are ghost levels, and 'a.b' a dangling link too
+        ws.fill_missing_hierarchy_levels();
+
+        let report = ws
+            .refactor_hierarchy(&provider, r"^a\.b\.c$", "a.b", None)
+            .unwrap();
+        assert!(report.collisions.is_empty());
+        let (_, contents) =
+            crate::mutation::executor::dry_run(&report.plan.unwrap(), &provider).unwrap();
+        assert_eq!(
+            contents[&PathBuf::from("/test/a.b.md")].as_deref(),
+            Some("# C")
+        );
+        assert_eq!(
+            contents[&PathBuf::from("/test/ref.md")].as_deref(),
+            Some("[[a.b]] [[a.b]]")
+        );
+    }
 }
@@ -0,0 +1,156 @@
+use crate::analysis::audit::duplicate_frontmatter_ids;
+use crate::identity::IdentityRegistry;
+use crate::line_map::LineMap;
+use crate::model::TextRange;
+use crate::mutation::model::{
+    Change, ContentProvider, EditGroup, EditPlan, MutationKind, TextEdit,
+};
+use crate::store::Store;
+use crate::utils::id::generate_id;
+
+/// Calculate edits giving every note with a duplicate frontmatter `id` a new one.
+///
+/// In each group of notes sharing an id, the note already identified by it
+/// keeps it; the others get freshly minted ids.
+pub(crate) fn calculate_mint_id_edits(
+    store: &Store,
+    identity: &IdentityRegistry,
+    content_provider: &dyn ContentProvider,
+) -> Option<EditPlan> {
+    let mut edits = Vec::new();
+
+    for (_, note_ids) in duplicate_frontmatter_ids(store, identity) {
+        for note_id in note_ids.iter().skip(1) {
+            let Some(path) = store.get_note(note_id).and_then(|note| note.path.as_ref()) else {
+                continue;
+            };
+            let uri = path.to_string_lossy().to_string();
+            let Some(content) = content_provider.get_content(&uri) else {
+                continue;
+            };
+            let Some((start, end)) = frontmatter_id_span(&content) else {
+                continue;
+            };
+
+            let line_map = LineMap::new(&content);
+            edits.push(EditGroup {
+                uri,
+                changes: vec![Change::TextEdit(TextEdit {
+                    range: TextRange {
+                        start: line_map.offset_to_point(&content, start),
+                        end: line_map.offset_to_point(&content, end),
+                    },
+                    new_text: generate_id(),
+                    undo_text: Some(content[start..end].to_string()),
+                })],
+            });
+        }
+    }
+
+    if edits.is_empty() {
+        return None;
+    }
+
+    Some(
+        EditPlan {
+            mutation_kind: MutationKind::MintNoteIds,
+            edits,
+            preconditions: vec![],
+            diagnostics: vec![],
+            reversible: true,
+        }
+        .with_file_preconditions(content_provider),
+    )
+}
+
+/// Byte span of the `id` value in the frontmatter of `content`
+fn frontmatter_id_span(content: &str) -> Option<(usize, usize)> {
+    let mut lines = content.split_inclusive('\n');
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+
+    let mut offset = content.find('\n')? + 1;
+    for line in lines {
+        let text = line.trim_end();
+        if text == "---" {
+            break;
+        }
+        if let Some(value) = text.strip_prefix("id:") {
+            let trimmed = value.trim_start();
+            if trimmed.is_empty() {
+                return None;
+            }
+            let start = offset + "id:".len() + (value.len() - trimmed.len());
+            return Some((start, offset + text.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::NoteId;
+    use crate::test_fixtures::setup;
+
+    #[test]
+    fn test_duplicate_ids_audited_and_reminted() {
+        let (ws, provider) = setup(&[
+            ("original", "---\nid: same\ntitle: Original\n---\n"),
+            ("copy", "---\nid: same\ntitle: Copy\n---\n"),
+            ("other", "---\nid: other\n---\n"),
+        ]);
+
+        // The copy, indexed second, could not take the stable id
+        let original = ws.identity.lookup(&"original".to_string()).unwrap();
+        assert_eq!(original, NoteId::from_frontmatter_id("same"));
+
+        let mut messages: Vec<_> = ws
+            .audit()
+            .diagnostics
+            .into_iter()
+            .filter(|d| d.message.starts_with("Frontmatter id"))
+            .map(|d| (d.uri.unwrap(), d.message))
+            .collect();
+        messages.sort();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "/test/copy.md".to_string(),
+                    "Frontmatter id 'same' is also used by 'original'.".to_string()
+                ),
+                (
+                    "/test/original.md".to_string(),
+                    "Frontmatter id 'same' is also used by 'copy'.".to_string()
+                ),
+            ]
+        );
+
+        let plan = ws.mint_duplicate_ids(&provider).unwrap();
+        assert_eq!(plan.mutation_kind, MutationKind::MintNoteIds);
+        assert_eq!(plan.edits.len(), 1);
+        assert_eq!(plan.edits[0].uri, "/test/copy.md");
+        let Change::TextEdit(edit) = &plan.edits[0].changes[0] else {
+            panic!("Expected TextEdit");
+        };
+        assert_eq!(edit.range.start.line, 1);
+        assert_eq!(edit.undo_text.as_deref(), Some("same"));
+        assert_ne!(edit.new_text, "same");
+
+        let (ws, provider) = setup(&[("a", "---\nid: a\n---\n")]);
+        assert!(ws.mint_duplicate_ids(&provider).is_none());
+    }
+
+    #[test]
+    fn test_frontmatter_id_span() {
+        let content = "---\ntitle: A\nid: 'abc'\n---\nid: body";
+        let (start, end) = frontmatter_id_span(content).unwrap();
+        assert_eq!(&content[start..end], "'abc'");
+
+        assert!(frontmatter_id_span("# No frontmatter\nid: x").is_none());
+        assert!(frontmatter_id_span("---\ntitle: A\n---\nid: x").is_none());
+    }
+}
@@ -0,0 +1,449 @@
+use crate::identity::IdentityRegistry;
+use crate::line_map::LineMap;
+use crate::model::{Note, NoteId, TextRange};
+use crate::mutation::model::{
+    Change, ContentProvider, EditGroup, EditPlan, MutationKind, ResourceOperation, TextEdit,
+};
+use crate::mutation::structural::calculate_structural_edits;
+use crate::semantic::SemanticModel;
+use crate::store::Store;
+use crate::utils::slugify_heading;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What happens to the source's frontmatter fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum FrontmatterStrategy {
+    /// Drop them; the target's frontmatter is left as is
+    #[default]
+    KeepTarget,
+    /// Copy the fields the target does not have
+    AddMissing,
+    /// Like `AddMissing`, and also join list fields (e.g. `tags`) both notes have
+    Combine,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct MergeOptions {
+    /// Insert the source body at the end of this heading's section (by text
+    /// or anchor slug) instead of at the end of the target
+    pub heading: Option<String>,
+    pub frontmatter: FrontmatterStrategy,
+}
+
+/// Calculate edits for merging one note into another ("Merge Into").
+///
+/// 1. Inserts the source body (without frontmatter) into the target, at the end
+///    of the chosen heading's section or of the note, and combines frontmatter.
+/// 2. Points every backlink of the source at the target, keeping aliases and anchors;
+///    links within the moved body are updated the same way.
+/// 3. Deletes the source file.
+///
+/// Returns None if either note is missing or the heading is not in the target.
+pub(crate) fn calculate_merge_edits(
+    store: &Store,
+    identity: &IdentityRegistry,
+    content_provider: &dyn ContentProvider,
+    model: &dyn SemanticModel,
+    source_id: &NoteId,
+    target_id: &NoteId,
+    options: &MergeOptions,
+) -> Option<EditPlan> {
+    if source_id == target_id {
+        return None;
+    }
+    let source = store.get_note(source_id)?;
+    let target = store.get_note(target_id)?;
+    let source_uri = source.path.as_ref()?.to_string_lossy().to_string();
+    let target_path = target.path.clone()?;
+    let target_uri = target_path.to_string_lossy().to_string();
+    let target_key = identity.key_of(target_id)?;
+
+    let source_content = content_provider.get_content(&source_uri)?;
+    let target_content = content_provider.get_content(&target_uri)?;
+    let insert_at = insertion_offset(target, &target_content, options.heading.as_deref())?;
+
+    // Backlinks are retargeted exactly like a rename onto the target would;
+    // the edits to the source itself apply to the body being moved
+    let mut groups: BTreeMap<String, Vec<Change>> = BTreeMap::new();
+    let mut body_edits = Vec::new();
+    if let Some(plan) = calculate_structural_edits(
+        store,
+        identity,
+        content_provider,
+        model,
+        source_id,
+        target_path,
+        &target_key,
+    ) {
+        for group in plan.edits {
+            let text_edits = group.changes.into_iter().filter_map(Change::text_edit);
+            if group.uri == source_uri {
+                body_edits.extend(text_edits);
+                continue;
+            }
+            groups
+                .entry(group.uri)
+                .or_default()
+                .extend(text_edits.map(Change::TextEdit));
+        }
+    }
+    groups.retain(|_, changes| !changes.is_empty());
+
+    let target_line_map = LineMap::new(&target_content);
+    let text_edit = |start: usize, end: usize, new_text: String| {
+        Change::TextEdit(TextEdit {
+            range: TextRange {
+                start: target_line_map.offset_to_point(&target_content, start),
+                end: target_line_map.offset_to_point(&target_content, end),
+            },
+            new_text,
+            undo_text: Some(target_content[start..end].to_string()),
+        })
+    };
+
+    let mut target_changes: Vec<Change> =
+        frontmatter_edits(source, target, &target_content, options.frontmatter)
+            .into_iter()
+            .map(|(start, end, new_text)| text_edit(start, end, new_text))
+            .collect();
+
+    let body = moved_body(source, &source_content, body_edits);
+    let body = body.trim();
+    if !body.is_empty() {
+        let before = &target_content[..insert_at];
+        let separator = if before.is_empty() || before.ends_with("\n\n") {
+            ""
+        } else if before.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        };
+        let terminator = if insert_at == target_content.len() {
+            "\n"
+        } else {
+            "\n\n"
+        };
+        target_changes.push(text_edit(
+            insert_at,
+            insert_at,
+            format!("{}{}{}", separator, body, terminator),
+        ));
+    }
+    if !target_changes.is_empty() {
+        groups.entry(target_uri).or_default().extend(target_changes);
+    }
+
+    let mut edits: Vec<EditGroup> = groups
+        .into_iter()
+        .map(|(uri, changes)| EditGroup { uri, changes })
+        .collect();
+    edits.push(EditGroup {
+        uri: source_uri,
+        changes: vec![Change::ResourceOp(ResourceOperation::DeleteFile {
+            ignore_if_not_exists: false,
+        })],
+    });
+
+    Some(
+        EditPlan {
+            mutation_kind: MutationKind::MergeNote,
+            edits,
+            preconditions: vec![],
+            diagnostics: vec![],
+            // Inverting with a content provider recreates the deleted source
+            reversible: true,
+        }
+        .with_file_preconditions(content_provider),
+    )
+}
+
+/// Byte offset in the target where the source body goes
+fn insertion_offset(target: &Note, content: &str, heading: Option<&str>) -> Option<usize> {
+    let Some(heading) = heading else {
+        return Some(content.len());
+    };
+    let slug = slugify_heading(heading.trim_start_matches('#'));
+    let index = target
+        .headings
+        .iter()
+        .position(|h| h.text == heading || slugify_heading(&h.text) == slug)?;
+    let level = target.headings[index].level;
+
+    let line_map = LineMap::new(content);
+    Some(
+        target.headings[index + 1..]
+            .iter()
+            .find(|h| h.level <= level)
+            .and_then(|h| line_map.point_to_offset(content, h.range.start))
+            .unwrap_or(content.len()),
+    )
+}
+
+/// The source body, with the edits planned for the source applied to it
+fn moved_body(source: &Note, content: &str, edits: Vec<TextEdit>) -> String {
+    let body_start = (source.content_offset as usize).min(content.len());
+    let line_map = LineMap::new(content);
+    let mut spans: Vec<(usize, usize, String)> = edits
+        .into_iter()
+        .filter_map(|edit| {
+            let start = line_map.point_to_offset(content, edit.range.start)?;
+            let end = line_map.point_to_offset(content, edit.range.end)?;
+            (body_start <= start && start <= end).then_some((start, end, edit.new_text))
+        })
+        .collect();
+    spans.sort_by_key(|&(start, end, _)| std::cmp::Reverse((start, end)));
+
+    let mut body = content.to_string();
+    let mut limit = content.len();
+    for (start, end, new_text) in spans {
+        if end <= limit {
+            body.replace_range(start..end, &new_text);
+            limit = start;
+        }
+    }
+    body[body_start..].to_string()
+}
+
+/// Edits to the target's frontmatter as (start, end, new text) byte spans
+fn frontmatter_edits(
+    source: &Note,
+    target: &Note,
+    content: &str,
+    strategy: FrontmatterStrategy,
+) -> Vec<(usize, usize, String)> {
+    let Some(source_fields) = source.frontmatter.as_ref().and_then(|fm| fm.as_object()) else {
+        return Vec::new();
+    };
+    if strategy == FrontmatterStrategy::KeepTarget {
+        return Vec::new();
+    }
+    let empty = serde_json::Map::new();
+    let target_fields = target
+        .frontmatter
+        .as_ref()
+        .and_then(|fm| fm.as_object())
+        .unwrap_or(&empty);
+    let spans = frontmatter_spans(content);
+
+    let mut added = String::new();
+    let mut edits = Vec::new();
+    for (key, value) in source_fields {
+        // The id identifies the source, which is going away
+        if key == "id" {
+            continue;
+        }
+        match target_fields.get(key) {
+            None => added.push_str(&yaml_entry(key, value)),
+            Some(existing) if strategy == FrontmatterStrategy::Combine => {
+                let Some(joined) = join_lists(existing, value) else {
+                    continue;
+                };
+                let Some(&(_, start, end)) = spans
+                    .as_ref()
+                    .and_then(|spans| spans.entries.iter().find(|(k, _, _)| k == key))
+                else {
+                    continue;
+                };
+                edits.push((start, end, yaml_entry(key, &joined)));
+            }
+            Some(_) => {}
+        }
+    }
+
+    if !added.is_empty() {
+        match &spans {
+            Some(spans) => edits.push((spans.closing, spans.closing, added)),
+            None => edits.push((0, 0, format!("---\n{}---\n\n", added))),
+        }
+    }
+    edits
+}
+
+/// Byte spans of a note's frontmatter block
+struct FrontmatterSpans {
+    /// Start of the closing `---` line
+    closing: usize,
+    /// (key, start, end) of each top-level entry
+    entries: Vec<(String, usize, usize)>,
+}
+
+fn frontmatter_spans(content: &str) -> Option<FrontmatterSpans> {
+    let mut lines = content.split_inclusive('\n');
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+
+    let mut offset = content.find('\n')? + 1;
+    let mut entries: Vec<(String, usize, usize)> = Vec::new();
+    for line in lines {
+        if line.trim_end() == "---" {
+            return Some(FrontmatterSpans {
+                closing: offset,
+                entries,
+            });
+        }
+        let top_level = !line.starts_with([' ', '\t', '-', '#']);
+        match line.split_once(':') {
+            Some((key, _)) if top_level => {
+                entries.push((key.trim().to_string(), offset, offset + line.len()))
+            }
+            _ => {
+                if let Some(last) = entries.last_mut() {
+                    last.2 = offset + line.len();
+                }
+            }
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// `key: value` as a YAML block, ending with a newline
+fn yaml_entry(key: &str, value: &serde_json::Value) -> String {
+    let mut entry = serde_json::Map::new();
+    entry.insert(key.to_string(), value.clone());
+    serde_yaml::to_string(&entry).unwrap_or_default()
+}
+
+/// The target's list with the source's new items, if either is a list
+/// (a single string counts as a one-item list) and something was added.
+fn join_lists(target: &serde_json::Value, source: &serde_json::Value) -> Option<serde_json::Value> {
+    if !target.is_array() && !source.is_array() {
+        return None;
+    }
+    let items = |value: &serde_json::Value| match value {
+        serde_json::Value::Array(items) => items.clone(),
+        serde_json::Value::String(_) => vec![value.clone()],
+        _ => Vec::new(),
+    };
+    let mut joined = items(target);
+    let original = joined.len();
+    for item in items(source) {
+        if !joined.contains(&item) {
+            joined.push(item);
+        }
+    }
+    (joined.len() > original).then_some(serde_json::Value::Array(joined))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::test_fixtures::{setup, MockProvider};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_merge_note() {
+        let (ws, provider) = setup(&[
+            ("copy", "---\ntitle: Copy\n---\n\n# Notes\nExtra line\n"),
+            ("original", "# Notes\nFirst line"),
+            ("ref", "See [[copy#notes]] and [[Alias|copy]]."),
+        ]);
+
+        let plan = ws
+            .merge_note(&provider, "copy", "original", &MergeOptions::default())
+            .unwrap();
+        assert_eq!(plan.mutation_kind, MutationKind::MergeNote);
+        let uris: Vec<_> = plan.edits.iter().map(|group| group.uri.as_str()).collect();
+        assert_eq!(
+            uris,
+            vec!["/test/original.md", "/test/ref.md", "/test/copy.md"]
+        );
+
+        let Change::TextEdit(append) = &plan.edits[0].changes[0] else {
+            panic!("Expected TextEdit");
+        };
+        assert_eq!(append.new_text, "\n\n# Notes\nExtra line\n");
+        assert_eq!(append.range.start, Point { line: 1, col: 10 });
+
+        let retargeted: Vec<_> = plan.edits[1]
+            .changes
+            .iter()
+            .filter_map(|change| change.clone().text_edit())
+            .map(|edit| edit.new_text)
+            .collect();
+        assert_eq!(retargeted, vec!["[[original#notes]]", "[[Alias|original]]"]);
+
+        assert!(matches!(
+            plan.edits[2].changes[0],
+            Change::ResourceOp(ResourceOperation::DeleteFile { .. })
+        ));
+        assert!(ws
+            .merge_note(&provider, "copy", "copy", &MergeOptions::default())
+            .is_none());
+        assert!(ws
+            .merge_note(&provider, "copy", "missing", &MergeOptions::default())
+            .is_none());
+    }
+
+    #[test]
+    fn test_merge_at_heading_with_frontmatter_is_reversible() {
+        let (ws, provider) = setup(&[
+            (
+                "copy",
+                "---\nid: copy-id\ntitle: Copy\ntags: [b, c]\nstatus: draft\n---\n\nMore ^blk\n\nSee [[copy#^blk]]\n",
+            ),
+            (
+                "original",
+                "---\nid: original-id\ntitle: Original\ntags:\n  - a\n  - b\n---\n\n# Intro\nFirst\n\n## Detail\nDeep\n\n# Outro\nLast\n",
+            ),
+            ("ref", "Block [[copy#^blk]]."),
+        ]);
+
+        let options = MergeOptions {
+            heading: Some("#intro".to_string()),
+            frontmatter: FrontmatterStrategy::Combine,
+        };
+        let plan = ws
+            .merge_note(&provider, "copy", "original", &options)
+            .unwrap();
+        assert!(plan.reversible);
+        let undo = plan.undo_plan(&provider).unwrap();
+
+        let (_, contents) = crate::mutation::executor::dry_run(&plan, &provider).unwrap();
+        let merged = contents[&PathBuf::from("/test/original.md")].as_deref();
+        assert_eq!(
+            merged,
+            Some(
+                "---\nid: original-id\ntitle: Original\ntags:\n- a\n- b\n- c\nstatus: draft\n---\n\n# Intro\nFirst\n\n## Detail\nDeep\n\nMore ^blk\n\nSee [[original#^blk]]\n\n# Outro\nLast\n"
+            )
+        );
+        assert_eq!(
+            contents[&PathBuf::from("/test/ref.md")].as_deref(),
+            Some("Block [[original#^blk]].")
+        );
+        assert_eq!(contents[&PathBuf::from("/test/copy.md")], None);
+
+        // Undoing restores every file as it was
+        let mut after = MockProvider {
+            files: provider.files.clone(),
+        };
+        for (path, content) in contents {
+            let uri = path.to_string_lossy().to_string();
+            match content {
+                Some(content) => after.files.insert(uri, content),
+                None => after.files.remove(&uri),
+            };
+        }
+        let (_, restored) = crate::mutation::executor::dry_run(&undo, &after).unwrap();
+        for (path, content) in restored {
+            assert_eq!(
+                content.as_ref(),
+                provider.files.get(&*path.to_string_lossy()),
+                "{}",
+                path.display()
+            );
+        }
+
+        let missing = MergeOptions {
+            heading: Some("Nowhere".to_string()),
+            ..MergeOptions::default()
+        };
+        assert!(ws
+            .merge_note(&provider, "copy", "original", &missing)
+            .is_none());
+    }
+}
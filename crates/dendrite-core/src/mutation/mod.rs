@@ -1,4 +1,11 @@
+pub mod executor;
+pub mod file_ops;
+pub mod format;
 pub mod hierarchy;
+pub mod ids;
+pub mod merge;
 pub mod model;
+pub mod preview;
 pub mod split;
 pub mod structural;
+pub mod vault;
@@ -1,4 +1,5 @@
-use crate::model::TextRange;
+use crate::model::{Point, TextRange};
+use crate::vfs::FileSystem;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -29,6 +30,194 @@ impl EditPlan {
             reversible: self.reversible,
         }
     }
+
+    /// The plan undoing this one, computed before it is applied so deleted
+    /// files are recreated with their current content. Its preconditions pin
+    /// the state this plan leaves behind: undo refuses to run over later edits.
+    pub fn undo_plan(
+        &self,
+        content_provider: &dyn ContentProvider,
+    ) -> Result<EditPlan, Vec<Diagnostic>> {
+        let (_, contents) = crate::mutation::executor::dry_run(self, content_provider)
+            .map_err(|error| vec![error])?;
+        let mut contents: Vec<_> = contents.into_iter().collect();
+        contents.sort();
+
+        let mut undo = self.clone().invert(Some(content_provider));
+        undo.preconditions = contents
+            .into_iter()
+            .map(|(path, content)| match content {
+                Some(content) => Precondition::ContentUnchanged(path, content_checksum(&content)),
+                None => Precondition::PathNotExists(path),
+            })
+            .collect();
+        Ok(undo)
+    }
+
+    /// One-line summary of the plan, e.g. "Rename note: a.md → b.md (2 files)".
+    pub fn describe(&self) -> String {
+        let label = match self.mutation_kind {
+            MutationKind::RenameNote => "Rename note",
+            MutationKind::MoveNote => "Move note",
+            MutationKind::SplitNote => "Split note",
+            MutationKind::WorkspaceAudit => "Workspace audit",
+            MutationKind::HierarchyRefactor => "Refactor hierarchy",
+            MutationKind::CreateNote => "Create note",
+            MutationKind::DeleteNote => "Delete note",
+            MutationKind::FormatNote => "Format note",
+            MutationKind::MergeNote => "Merge note",
+            MutationKind::MintNoteIds => "Mint note ids",
+            MutationKind::MoveToVault => "Move to vault",
+        };
+        let file_name = |uri: &str| {
+            std::path::Path::new(uri).file_name().map_or_else(
+                || uri.to_string(),
+                |name| name.to_string_lossy().to_string(),
+            )
+        };
+
+        let rename =
+            self.edits.iter().find_map(|group| {
+                group.changes.iter().find_map(|change| match change {
+                    Change::ResourceOp(ResourceOperation::RenameFile { new_uri, .. }) => Some(
+                        format!("{} → {}", file_name(&group.uri), file_name(new_uri)),
+                    ),
+                    _ => None,
+                })
+            });
+        let subject = rename.or_else(|| self.edits.first().map(|group| file_name(&group.uri)));
+
+        let mut uris: Vec<&str> = self.edits.iter().map(|group| group.uri.as_str()).collect();
+        uris.sort();
+        uris.dedup();
+
+        match (subject, uris.len()) {
+            (None, _) => label.to_string(),
+            (Some(subject), 0 | 1) => format!("{}: {}", label, subject),
+            (Some(subject), files) => format!("{}: {} ({} files)", label, subject, files),
+        }
+    }
+
+    /// Pin down the files the plan was computed from: the checksum of every
+    /// existing file it edits, deletes or renames, and the absence of every
+    /// file it creates or renames onto.
+    pub(crate) fn with_file_preconditions(
+        mut self,
+        content_provider: &dyn ContentProvider,
+    ) -> Self {
+        // Paths the plan itself frees up before reusing them (e.g. a -> a.b, a.b -> a.b.b)
+        let vacated: Vec<&str> = self
+            .edits
+            .iter()
+            .filter(|group| {
+                group.changes.iter().any(|change| {
+                    matches!(
+                        change,
+                        Change::ResourceOp(
+                            ResourceOperation::RenameFile { .. }
+                                | ResourceOperation::DeleteFile { .. }
+                        )
+                    )
+                })
+            })
+            .map(|group| group.uri.as_str())
+            .collect();
+
+        let mut preconditions = Vec::new();
+        for group in &self.edits {
+            let mut creates = false;
+            for change in &group.changes {
+                match change {
+                    Change::ResourceOp(ResourceOperation::CreateFile { .. }) => creates = true,
+                    Change::ResourceOp(ResourceOperation::RenameFile { new_uri, .. })
+                        if !vacated.contains(&new_uri.as_str()) =>
+                    {
+                        preconditions.push(Precondition::PathNotExists(PathBuf::from(new_uri)));
+                    }
+                    _ => {}
+                }
+            }
+            if creates {
+                if !vacated.contains(&group.uri.as_str()) {
+                    preconditions.push(Precondition::PathNotExists(PathBuf::from(&group.uri)));
+                }
+            } else if let Some(content) = content_provider.get_content(&group.uri) {
+                preconditions.push(Precondition::ContentUnchanged(
+                    PathBuf::from(&group.uri),
+                    content_checksum(&content),
+                ));
+            }
+        }
+
+        for precondition in preconditions {
+            if !self.preconditions.contains(&precondition) {
+                self.preconditions.push(precondition);
+            }
+        }
+        self
+    }
+
+    /// Check the preconditions against the current state of the files.
+    ///
+    /// `overlay` supplies content newer than the file system (e.g. unsaved
+    /// editor buffers). `NoteExists` is not checked: the checksums of the files
+    /// a plan touches already fail once a note is gone. Returns one error per
+    /// failed precondition.
+    pub fn validate(
+        &self,
+        fs: &dyn FileSystem,
+        overlay: &dyn ContentProvider,
+    ) -> Result<(), Vec<Diagnostic>> {
+        let failures: Vec<Diagnostic> = self
+            .preconditions
+            .iter()
+            .filter_map(|precondition| {
+                let (path, message) = match precondition {
+                    Precondition::NoteExists(_) => return None,
+                    Precondition::PathNotExists(path) => {
+                        let uri = path.to_string_lossy();
+                        if overlay.get_content(&uri).is_none() && fs.metadata(path).is_err() {
+                            return None;
+                        }
+                        (path, format!("'{}' already exists.", uri))
+                    }
+                    Precondition::ContentUnchanged(path, checksum) => {
+                        let uri = path.to_string_lossy();
+                        let content = overlay
+                            .get_content(&uri)
+                            .or_else(|| fs.read_to_string(path).ok());
+                        match content {
+                            Some(content) if content_checksum(&content) == *checksum => {
+                                return None
+                            }
+                            Some(_) => (
+                                path,
+                                format!("'{}' changed since the edit was planned.", uri),
+                            ),
+                            None => (path, format!("'{}' no longer exists.", uri)),
+                        }
+                    }
+                };
+                Some(Diagnostic {
+                    severity: DiagnosticSeverity::Error,
+                    message,
+                    uri: Some(path.to_string_lossy().to_string()),
+                    range: None,
+                })
+            })
+            .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+/// SHA-256 checksum of file content, as recorded by `Precondition::ContentUnchanged`
+pub fn content_checksum(content: &str) -> String {
+    crate::parser::compute_digest(content)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -40,6 +229,10 @@ pub enum MutationKind {
     HierarchyRefactor,
     CreateNote,
     DeleteNote,
+    FormatNote,
+    MergeNote,
+    MintNoteIds,
+    MoveToVault,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -59,17 +252,66 @@ impl EditGroup {
             }
         }
 
+        // A run of text edits is applied at once, so the inverse of each one
+        // must be positioned in the content left by all of them
+        let mut changes = Vec::with_capacity(self.changes.len());
+        let mut run = Vec::new();
+        for change in self.changes {
+            match change {
+                Change::TextEdit(edit) => run.push(edit),
+                Change::ResourceOp(op) => {
+                    changes.extend(invert_text_edits(std::mem::take(&mut run)));
+                    changes.push(Change::ResourceOp(op.invert(&self.uri, content_provider)));
+                }
+            }
+        }
+        changes.extend(invert_text_edits(run));
+
         Self {
             uri: target_uri,
-            changes: self
-                .changes
-                .into_iter()
-                .map(|c| c.invert(&self.uri, content_provider))
-                .collect(),
+            changes,
         }
     }
 }
 
+/// Invert a run of edits whose ranges all refer to the content before them.
+fn invert_text_edits(edits: Vec<TextEdit>) -> impl Iterator<Item = Change> {
+    let mut order: Vec<usize> = (0..edits.len()).collect();
+    order.sort_by_key(|&i| {
+        let range = edits[i].range;
+        (
+            range.start.line,
+            range.start.col,
+            range.end.line,
+            range.end.col,
+        )
+    });
+
+    // Where the previous edit ended, before and after the run
+    let mut previous_end: Option<(Point, Point)> = None;
+    let mut inverted: Vec<Option<TextEdit>> = vec![None; edits.len()];
+    for i in order {
+        let edit = &edits[i];
+        let start = match previous_end {
+            Some((old_end, new_end)) if old_end.line == edit.range.start.line => Point {
+                line: new_end.line,
+                col: new_end.col + edit.range.start.col.saturating_sub(old_end.col),
+            },
+            Some((old_end, new_end)) => Point {
+                line: edit.range.start.line + new_end.line - old_end.line,
+                col: edit.range.start.col,
+            },
+            None => edit.range.start,
+        };
+        let mut shifted = edit.clone();
+        shifted.range.start = start;
+        let inverse = shifted.invert();
+        previous_end = Some((edit.range.end, inverse.range.end));
+        inverted[i] = Some(inverse);
+    }
+    inverted.into_iter().flatten().map(Change::TextEdit)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Change {
     TextEdit(TextEdit),
@@ -122,7 +364,7 @@ impl TextEdit {
                 end.line += 1;
                 end.col = 0;
             } else {
-                end.col += 1;
+                end.col += c.len_utf16() as u32;
             }
         }
 
@@ -167,10 +409,11 @@ impl ResourceOperation {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Precondition {
+    /// No longer emitted nor checked; kept so recorded plans still load
     #[allow(private_interfaces)]
     NoteExists(String),
     PathNotExists(PathBuf),
-    ContentUnchanged(PathBuf, String), // path, checksum
+    ContentUnchanged(PathBuf, String), // path, SHA-256 checksum
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -191,7 +434,6 @@ pub enum DiagnosticSeverity {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::Point;
 
     #[test]
     fn test_text_edit_invert() {
@@ -220,6 +462,52 @@ mod tests {
         assert_eq!(inverted.undo_text, Some("NewText\nMultiLine".to_string()));
     }
 
+    #[test]
+    fn test_edit_group_invert_shifts_later_edits() {
+        let edit = |start: (u32, u32), end: (u32, u32), new_text: &str, old_text: &str| {
+            Change::TextEdit(TextEdit {
+                range: TextRange {
+                    start: Point {
+                        line: start.0,
+                        col: start.1,
+                    },
+                    end: Point {
+                        line: end.0,
+                        col: end.1,
+                    },
+                },
+                new_text: new_text.to_string(),
+                undo_text: Some(old_text.to_string()),
+            })
+        };
+        // "[[a]] [[a]]\nx" -> "[[bb]] [[bb]]\nnew\nx"
+        let group = EditGroup {
+            uri: "note.md".to_string(),
+            changes: vec![
+                edit((0, 8), (0, 9), "bb", "a"),
+                edit((0, 2), (0, 3), "bb", "a"),
+                edit((1, 0), (1, 0), "new\n", ""),
+            ],
+        };
+
+        let inverted = group.invert(None);
+        let ranges: Vec<_> = inverted
+            .changes
+            .into_iter()
+            .filter_map(Change::text_edit)
+            .map(|edit| {
+                (
+                    (edit.range.start.line, edit.range.start.col),
+                    (edit.range.end.line, edit.range.end.col),
+                )
+            })
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![((0, 9), (0, 11)), ((0, 2), (0, 4)), ((1, 0), (2, 0))]
+        );
+    }
+
     #[test]
     fn test_resource_op_invert() {
         let op = ResourceOperation::RenameFile {
@@ -255,4 +543,142 @@ mod tests {
             panic!("Expected RenameFile in inverted group");
         }
     }
+
+    struct FsProvider;
+
+    impl ContentProvider for FsProvider {
+        fn get_content(&self, uri: &str) -> Option<String> {
+            std::fs::read_to_string(uri).ok()
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_stale_plans() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let existing = temp_dir.path().join("a.md");
+        let created = temp_dir.path().join("b.md");
+        std::fs::write(&existing, "# A").unwrap();
+
+        let plan = EditPlan {
+            mutation_kind: MutationKind::SplitNote,
+            edits: vec![
+                EditGroup {
+                    uri: existing.to_string_lossy().to_string(),
+                    changes: vec![Change::TextEdit(TextEdit {
+                        range: TextRange::default(),
+                        new_text: "[[b]]\n".to_string(),
+                        undo_text: Some(String::new()),
+                    })],
+                },
+                EditGroup {
+                    uri: created.to_string_lossy().to_string(),
+                    changes: vec![Change::ResourceOp(ResourceOperation::CreateFile {
+                        content: Some("# B".to_string()),
+                    })],
+                },
+            ],
+            preconditions: vec![],
+            diagnostics: vec![],
+            reversible: true,
+        }
+        .with_file_preconditions(&FsProvider);
+        assert_eq!(
+            plan.preconditions,
+            vec![
+                Precondition::ContentUnchanged(existing.clone(), content_checksum("# A")),
+                Precondition::PathNotExists(created.clone()),
+            ]
+        );
+
+        let fs = crate::vfs::PhysicalFileSystem;
+        assert!(plan.validate(&fs, &FsProvider).is_ok());
+
+        std::fs::write(&existing, "# A, edited").unwrap();
+        std::fs::write(&created, "").unwrap();
+        let messages: Vec<_> = plan
+            .validate(&fs, &FsProvider)
+            .unwrap_err()
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                format!(
+                    "'{}' changed since the edit was planned.",
+                    existing.display()
+                ),
+                format!("'{}' already exists.", created.display()),
+            ]
+        );
+
+        std::fs::remove_file(&existing).unwrap();
+        let failures = plan.validate(&fs, &FsProvider).unwrap_err();
+        assert_eq!(
+            failures[0].message,
+            format!("'{}' no longer exists.", existing.display())
+        );
+    }
+
+    #[test]
+    fn test_undo_plan_pins_post_edit_state() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (a, b, gone) = (
+            temp_dir.path().join("a.md"),
+            temp_dir.path().join("b.md"),
+            temp_dir.path().join("gone.md"),
+        );
+        std::fs::write(&a, "See [[c]]").unwrap();
+        std::fs::write(&gone, "# Gone").unwrap();
+
+        let plan = EditPlan {
+            mutation_kind: MutationKind::RenameNote,
+            edits: vec![
+                EditGroup {
+                    uri: a.to_string_lossy().to_string(),
+                    changes: vec![
+                        Change::TextEdit(TextEdit {
+                            range: TextRange {
+                                start: Point { line: 0, col: 6 },
+                                end: Point { line: 0, col: 7 },
+                            },
+                            new_text: "d".to_string(),
+                            undo_text: Some("c".to_string()),
+                        }),
+                        Change::ResourceOp(ResourceOperation::RenameFile {
+                            new_uri: b.to_string_lossy().to_string(),
+                            overwrite: false,
+                        }),
+                    ],
+                },
+                EditGroup {
+                    uri: gone.to_string_lossy().to_string(),
+                    changes: vec![Change::ResourceOp(ResourceOperation::DeleteFile {
+                        ignore_if_not_exists: false,
+                    })],
+                },
+            ],
+            preconditions: vec![],
+            diagnostics: vec![],
+            reversible: true,
+        };
+        assert_eq!(plan.describe(), "Rename note: a.md → b.md (2 files)");
+
+        let undo = plan.undo_plan(&FsProvider).unwrap();
+        assert_eq!(
+            undo.preconditions,
+            vec![
+                Precondition::PathNotExists(a.clone()),
+                Precondition::ContentUnchanged(b.clone(), content_checksum("See [[d]]")),
+                Precondition::PathNotExists(gone.clone()),
+            ]
+        );
+        // The deleted file comes back with the content it had before
+        assert_eq!(
+            undo.edits[1].changes[0],
+            Change::ResourceOp(ResourceOperation::CreateFile {
+                content: Some("# Gone".to_string())
+            })
+        );
+    }
 }
@@ -0,0 +1,436 @@
+use crate::mutation::executor::dry_run;
+use crate::mutation::model::{ContentProvider, Diagnostic, EditPlan};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Unchanged lines shown around each hunk
+const CONTEXT_LINES: usize = 3;
+
+/// What an `EditPlan` would change, for review before applying it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditPlanPreview {
+    /// `EditPlan::describe`
+    pub description: String,
+    /// e.g. "3 files changed, 4 insertions(+), 2 deletions(-), 1 renamed"
+    pub summary: String,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Unified diff, with git-style headers for created, renamed and deleted files
+    pub diff: String,
+}
+
+/// Render `plan` as a unified diff against the current content, with paths
+/// relative to `root`.
+pub(crate) fn preview_edit_plan(
+    plan: &EditPlan,
+    content_provider: &dyn ContentProvider,
+    root: &Path,
+) -> Result<EditPlanPreview, Vec<Diagnostic>> {
+    let (report, contents) = dry_run(plan, content_provider).map_err(|error| vec![error])?;
+    let original = |path: &Path| content_provider.get_content(&path.to_string_lossy());
+
+    // (old path, new path) of each changed file; None where it does not exist
+    let mut files: Vec<(Option<&Path>, Option<&Path>)> = Vec::new();
+    for (from, to) in &report.renamed {
+        files.push((Some(from), Some(to)));
+    }
+    for path in &report.deleted {
+        files.push((Some(path), None));
+    }
+    for path in &report.written {
+        if report.renamed.iter().any(|(_, to)| to == path) {
+            continue;
+        }
+        let existed = original(path).is_some() && !report.deleted.contains(path);
+        files.push((existed.then_some(path.as_path()), Some(path)));
+    }
+    files.sort_by_key(|(from, to)| to.or(*from).map(Path::to_path_buf));
+
+    let relative = |path: &Path| {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+
+    let mut diff = String::new();
+    let (mut insertions, mut deletions, mut created, mut renamed, mut deleted) = (0, 0, 0, 0, 0);
+    for (from, to) in &files {
+        let old_text = from.and_then(original).unwrap_or_default();
+        let new_text = to
+            .and_then(|path| contents.get(path).cloned().flatten())
+            .unwrap_or_default();
+        let (old_name, new_name) = (
+            from.map(relative).unwrap_or_default(),
+            to.map(relative).unwrap_or_default(),
+        );
+
+        let header_names = match (from, to) {
+            (Some(_), Some(_)) => (old_name.clone(), new_name.clone()),
+            (Some(_), None) => (old_name.clone(), old_name.clone()),
+            _ => (new_name.clone(), new_name.clone()),
+        };
+        diff.push_str(&format!(
+            "diff --git a/{} b/{}\n",
+            header_names.0, header_names.1
+        ));
+        match (from, to) {
+            (None, _) => {
+                created += 1;
+                diff.push_str("new file\n");
+            }
+            (_, None) => {
+                deleted += 1;
+                diff.push_str("deleted file\n");
+            }
+            (Some(_), Some(_)) if old_name != new_name => {
+                renamed += 1;
+                diff.push_str(&format!(
+                    "rename from {}\nrename to {}\n",
+                    old_name, new_name
+                ));
+            }
+            _ => {}
+        }
+
+        let old_lines: Vec<&str> = old_text.split_inclusive('\n').collect();
+        let new_lines: Vec<&str> = new_text.split_inclusive('\n').collect();
+        let ops = diff_lines(&old_lines, &new_lines);
+        if ops.iter().all(|op| matches!(op, Op::Equal(_))) {
+            continue;
+        }
+        diff.push_str(&match from {
+            Some(_) => format!("--- a/{}\n", old_name),
+            None => "--- /dev/null\n".to_string(),
+        });
+        diff.push_str(&match to {
+            Some(_) => format!("+++ b/{}\n", new_name),
+            None => "+++ /dev/null\n".to_string(),
+        });
+        insertions += ops.iter().filter(|op| matches!(op, Op::Insert(_))).count();
+        deletions += ops.iter().filter(|op| matches!(op, Op::Delete(_))).count();
+        push_hunks(&mut diff, &ops);
+    }
+
+    let mut summary = format!(
+        "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+        files.len(),
+        plural(files.len()),
+        insertions,
+        plural(insertions),
+        deletions,
+        plural(deletions)
+    );
+    for (count, label) in [
+        (created, "created"),
+        (renamed, "renamed"),
+        (deleted, "deleted"),
+    ] {
+        if count > 0 {
+            summary.push_str(&format!(", {} {}", count, label));
+        }
+    }
+
+    Ok(EditPlanPreview {
+        description: plan.describe(),
+        summary,
+        files_changed: files.len(),
+        insertions,
+        deletions,
+        diff,
+    })
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+/// A line of a line-by-line diff, with its line ending
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Shortest edit script between two texts, by longest common subsequence.
+///
+/// The common prefix and suffix are split off first, so only the region that
+/// actually changed is searched, in linear space.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut ops: Vec<Op> = old[..prefix].iter().map(|line| Op::Equal(line)).collect();
+    diff_region(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+        &mut ops,
+    );
+    ops.extend(old[old.len() - suffix..].iter().map(|line| Op::Equal(line)));
+    ops
+}
+
+/// Hirschberg's algorithm: split `old` in half, find where an LCS crosses the
+/// split in `new`, and diff both sides recursively. Deletions come before
+/// insertions.
+fn diff_region<'a>(old: &[&'a str], new: &[&'a str], ops: &mut Vec<Op<'a>>) {
+    match old {
+        [] => ops.extend(new.iter().map(|line| Op::Insert(line))),
+        _ if new.is_empty() => ops.extend(old.iter().map(|line| Op::Delete(line))),
+        [line] => match new.iter().position(|other| other == line) {
+            Some(at) => {
+                ops.extend(new[..at].iter().map(|line| Op::Insert(line)));
+                ops.push(Op::Equal(line));
+                ops.extend(new[at + 1..].iter().map(|line| Op::Insert(line)));
+            }
+            None => {
+                ops.push(Op::Delete(line));
+                ops.extend(new.iter().map(|line| Op::Insert(line)));
+            }
+        },
+        _ => {
+            let mid = old.len() / 2;
+            let forward = lcs_lengths(old[..mid].iter(), new.iter());
+            let mut backward = lcs_lengths(old[mid..].iter().rev(), new.iter().rev());
+            backward.reverse();
+            // The first best split keeps insertions after deletions
+            let split = (0..=new.len())
+                .rev()
+                .max_by_key(|&j| forward[j] + backward[j])
+                .unwrap_or(0);
+            diff_region(&old[..mid], &new[..split], ops);
+            diff_region(&old[mid..], &new[split..], ops);
+        }
+    }
+}
+
+/// Length of the LCS of `old` with every prefix of `new` (the last row of the
+/// LCS table).
+fn lcs_lengths<'a, 'b>(
+    old: impl Iterator<Item = &'b &'a str>,
+    new: impl Iterator<Item = &'b &'a str> + Clone,
+) -> Vec<usize>
+where
+    'a: 'b,
+{
+    let mut row = vec![0; new.clone().count() + 1];
+    for old_line in old {
+        let mut diagonal = 0;
+        for (j, new_line) in new.clone().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if old_line == new_line {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+/// Append the changed regions of `ops` as unified diff hunks.
+fn push_hunks(out: &mut String, ops: &[Op]) {
+    let changed: Vec<usize> = (0..ops.len())
+        .filter(|&i| !matches!(ops[i], Op::Equal(_)))
+        .collect();
+
+    let mut next = 0;
+    while next < changed.len() {
+        let start = changed[next].saturating_sub(CONTEXT_LINES);
+        let mut last = changed[next];
+        next += 1;
+        // Changes separated by little context share a hunk
+        while next < changed.len() && changed[next] - last <= 2 * CONTEXT_LINES + 1 {
+            last = changed[next];
+            next += 1;
+        }
+        let end = (last + CONTEXT_LINES).min(ops.len() - 1);
+
+        let count = |range: &[Op], old: bool| {
+            range
+                .iter()
+                .filter(|op| match op {
+                    Op::Equal(_) => true,
+                    Op::Delete(_) => old,
+                    Op::Insert(_) => !old,
+                })
+                .count()
+        };
+        let (old_before, new_before) = (count(&ops[..start], true), count(&ops[..start], false));
+        let (old_count, new_count) = (
+            count(&ops[start..=end], true),
+            count(&ops[start..=end], false),
+        );
+        // An empty side is numbered after the line preceding it
+        let first_line = |before: usize, count: usize| if count == 0 { before } else { before + 1 };
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            first_line(old_before, old_count),
+            old_count,
+            first_line(new_before, new_count),
+            new_count
+        ));
+
+        for op in &ops[start..=end] {
+            let (marker, line) = match op {
+                Op::Equal(line) => (' ', line),
+                Op::Delete(line) => ('-', line),
+                Op::Insert(line) => ('+', line),
+            };
+            out.push(marker);
+            out.push_str(line.strip_suffix('\n').unwrap_or(line));
+            out.push('\n');
+            if !line.ends_with('\n') {
+                out.push_str("\\ No newline at end of file\n");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Point, TextRange};
+    use crate::mutation::model::{Change, EditGroup, MutationKind, ResourceOperation, TextEdit};
+    use crate::test_fixtures::MockProvider;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_diff_lines_hunks() {
+        let old: Vec<String> = (1..=12).map(|n| format!("{}\n", n)).collect();
+        let mut new = old.clone();
+        new[1] = "two\n".to_string();
+        new.remove(10);
+        let old: Vec<&str> = old.iter().map(String::as_str).collect();
+        let new: Vec<&str> = new.iter().map(String::as_str).collect();
+
+        let mut out = String::new();
+        push_hunks(&mut out, &diff_lines(&old, &new));
+        assert_eq!(
+            out,
+            "@@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3\n 4\n 5\n\
+             @@ -8,5 +8,4 @@\n 8\n 9\n 10\n-11\n 12\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_is_minimal() {
+        let cases = [
+            ("abcabba", "cbabac"),
+            ("xaxbxcx", "abc"),
+            ("abcdef", "fedcba"),
+            ("", "abc"),
+            ("abc", ""),
+            ("aaaa", "aa"),
+        ];
+        for (old, new) in cases {
+            let old: Vec<&str> = old.split("").filter(|s| !s.is_empty()).collect();
+            let new: Vec<&str> = new.split("").filter(|s| !s.is_empty()).collect();
+            let ops = diff_lines(&old, &new);
+
+            let side = |keep: fn(&Op) -> bool| -> Vec<&str> {
+                ops.iter()
+                    .filter(|op| keep(op))
+                    .map(|op| match op {
+                        Op::Equal(line) | Op::Delete(line) | Op::Insert(line) => *line,
+                    })
+                    .collect()
+            };
+            assert_eq!(side(|op| !matches!(op, Op::Insert(_))), old);
+            assert_eq!(side(|op| !matches!(op, Op::Delete(_))), new);
+
+            // As many unchanged lines as the longest common subsequence
+            let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+            for i in 0..old.len() {
+                for j in 0..new.len() {
+                    lcs[i + 1][j + 1] = if old[i] == new[j] {
+                        lcs[i][j] + 1
+                    } else {
+                        lcs[i][j + 1].max(lcs[i + 1][j])
+                    };
+                }
+            }
+            let equal = ops.iter().filter(|op| matches!(op, Op::Equal(_))).count();
+            assert_eq!(equal, lcs[old.len()][new.len()]);
+        }
+    }
+
+    #[test]
+    fn test_preview_edit_plan() {
+        let provider = MockProvider {
+            files: HashMap::from([
+                ("/vault/a.md".to_string(), "# A\n".to_string()),
+                ("/vault/ref.md".to_string(), "See [[a]]".to_string()),
+                ("/vault/old.md".to_string(), "# Old\n".to_string()),
+            ]),
+        };
+        let point = |line, col| Point { line, col };
+        let plan = EditPlan {
+            mutation_kind: MutationKind::HierarchyRefactor,
+            edits: vec![
+                EditGroup {
+                    uri: "/vault/a.md".to_string(),
+                    changes: vec![Change::ResourceOp(ResourceOperation::RenameFile {
+                        new_uri: "/vault/b.md".to_string(),
+                        overwrite: false,
+                    })],
+                },
+                EditGroup {
+                    uri: "/vault/ref.md".to_string(),
+                    changes: vec![Change::TextEdit(TextEdit {
+                        range: TextRange {
+                            start: point(0, 6),
+                            end: point(0, 7),
+                        },
+                        new_text: "b".to_string(),
+                        undo_text: Some("a".to_string()),
+                    })],
+                },
+                EditGroup {
+                    uri: "/vault/old.md".to_string(),
+                    changes: vec![Change::ResourceOp(ResourceOperation::DeleteFile {
+                        ignore_if_not_exists: false,
+                    })],
+                },
+                EditGroup {
+                    uri: "/vault/new.md".to_string(),
+                    changes: vec![Change::ResourceOp(ResourceOperation::CreateFile {
+                        content: Some("# New\n".to_string()),
+                    })],
+                },
+            ],
+            preconditions: vec![],
+            diagnostics: vec![],
+            reversible: true,
+        };
+
+        let preview = preview_edit_plan(&plan, &provider, Path::new("/vault")).unwrap();
+        assert_eq!(
+            preview.summary,
+            "4 files changed, 2 insertions(+), 2 deletions(-), 1 created, 1 renamed, 1 deleted"
+        );
+        assert_eq!(
+            preview.diff,
+            "diff --git a/a.md b/b.md\nrename from a.md\nrename to b.md\n\
+             diff --git a/new.md b/new.md\nnew file\n--- /dev/null\n+++ b/new.md\n\
+             @@ -0,0 +1,1 @@\n+# New\n\
+             diff --git a/old.md b/old.md\ndeleted file\n--- a/old.md\n+++ /dev/null\n\
+             @@ -1,1 +0,0 @@\n-# Old\n\
+             diff --git a/ref.md b/ref.md\n--- a/ref.md\n+++ b/ref.md\n\
+             @@ -1,1 +1,1 @@\n-See [[a]]\n\\ No newline at end of file\n\
+             +See [[b]]\n\\ No newline at end of file\n"
+        );
+    }
+}
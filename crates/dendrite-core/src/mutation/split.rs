@@ -1,10 +1,15 @@
 use crate::identity::IdentityRegistry;
-use crate::model::{NoteId, TextRange};
+use crate::line_map::LineMap;
+use crate::model::{LinkKind, Note, NoteId, TextRange};
 use crate::mutation::model::{
-    Change, ContentProvider, EditGroup, EditPlan, MutationKind, ResourceOperation, TextEdit,
+    Change, ContentProvider, Diagnostic, DiagnosticSeverity, EditGroup, EditPlan, MutationKind,
+    Precondition, ResourceOperation, TextEdit,
 };
 use crate::semantic::SemanticModel;
 use crate::store::Store;
+use crate::utils::slugify_heading;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Calculate edits for "Extract Selection to Note" (SplitNote).
 ///
@@ -60,13 +65,305 @@ pub(crate) fn calculate_split_edits(
         })],
     });
 
-    Some(EditPlan {
-        mutation_kind: MutationKind::SplitNote,
-        edits,
-        preconditions: vec![],
-        diagnostics: vec![],
-        reversible: true,
-    })
+    Some(
+        EditPlan {
+            mutation_kind: MutationKind::SplitNote,
+            edits,
+            preconditions: vec![],
+            diagnostics: vec![],
+            reversible: true,
+        }
+        .with_file_preconditions(content_provider),
+    )
+}
+
+/// What is left in the parent where a section was split out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionReplacement {
+    /// `![[parent.section]]`
+    #[default]
+    Embed,
+    /// `[[parent.section]]`
+    Link,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitByHeadingOptions {
+    /// Heading level whose sections become child notes
+    pub level: u8,
+    pub replacement: SectionReplacement,
+}
+
+impl Default for SplitByHeadingOptions {
+    fn default() -> Self {
+        Self {
+            level: 2,
+            replacement: SectionReplacement::default(),
+        }
+    }
+}
+
+/// A section of the parent becoming the child note `key`
+struct Section {
+    key: String,
+    slug: String,
+    /// Heading text, which becomes the child's title
+    title: String,
+    /// Byte offsets of the heading line and of the section end
+    start: usize,
+    end: usize,
+    /// Where the content under the heading starts
+    body_start: usize,
+    /// (start, end, new text) of link edits inside the section
+    edits: Vec<(usize, usize, String)>,
+}
+
+/// Calculate edits for "Split by Headings".
+///
+/// 1. Creates a child note `parent.<heading slug>` for every section at
+///    `options.level`, from the model's new note template titled with the
+///    heading text, and the section body.
+/// 2. Replaces each section in the parent with an embed of (or link to) its child.
+/// 3. Points wikilinks to headings and blocks of the sections at the children.
+///
+/// Sections whose child note would already exist are left in place, with a warning.
+/// Returns None if the note has no section to split.
+pub(crate) fn calculate_split_by_heading_edits(
+    store: &Store,
+    identity: &IdentityRegistry,
+    content_provider: &dyn ContentProvider,
+    model: &dyn SemanticModel,
+    note_id: &NoteId,
+    options: &SplitByHeadingOptions,
+) -> Option<EditPlan> {
+    let note = store.get_note(note_id)?;
+    let uri = note.path.as_ref()?.to_string_lossy().to_string();
+    let parent_key = identity.key_of(note_id)?;
+    let content = content_provider.get_content(&uri)?;
+    let line_map = LineMap::new(&content);
+    let offset = |point| line_map.point_to_offset(&content, point);
+
+    let mut sections: Vec<Section> = Vec::new();
+    let mut diagnostics = Vec::new();
+    for (index, heading) in note.headings.iter().enumerate() {
+        if heading.level != options.level {
+            continue;
+        }
+        let (Some(start), Some(body_start)) =
+            (offset(heading.range.start), offset(heading.range.end))
+        else {
+            continue;
+        };
+        let end = note.headings[index + 1..]
+            .iter()
+            .find(|h| h.level <= heading.level)
+            .and_then(|h| offset(h.range.start))
+            .unwrap_or(content.len());
+
+        let slug = slugify_heading(&heading.text);
+        let key = format!("{}.{}", parent_key, slug);
+        // Keys of ghost levels and dangling links are free to take
+        let exists = identity
+            .lookup(&key)
+            .and_then(|id| store.get_note(&id))
+            .is_some_and(|note| note.path.is_some());
+        let taken = slug.is_empty() || exists || sections.iter().any(|section| section.key == key);
+        if taken {
+            diagnostics.push(Diagnostic {
+                severity: DiagnosticSeverity::Warning,
+                message: format!(
+                    "Section '{}' was not split: note '{}' already exists.",
+                    heading.text, key
+                ),
+                uri: Some(uri.clone()),
+                range: Some(heading.range),
+            });
+            continue;
+        }
+        sections.push(Section {
+            key,
+            slug,
+            title: heading.text.clone(),
+            start,
+            end,
+            body_start: body_start.max(start),
+            edits: Vec::new(),
+        });
+    }
+    if sections.is_empty() {
+        return None;
+    }
+
+    // Links into the sections now point at the children; those inside a
+    // section move along with it
+    let mut groups: BTreeMap<String, Vec<Change>> = BTreeMap::new();
+    for (source, link) in store.backlink_refs(note_id) {
+        let embed = match link.kind {
+            LinkKind::WikiLink(_) => false,
+            LinkKind::EmbeddedWikiLink(_) => true,
+            _ => continue,
+        };
+        let Some((target, anchor)) = link
+            .anchor
+            .as_deref()
+            .and_then(|anchor| section_of_anchor(note, &content, &sections, anchor))
+        else {
+            continue;
+        };
+        let new_text =
+            model.format_wikilink(&sections[target].key, link.alias.as_deref(), anchor, embed);
+
+        if source.id == *note_id {
+            let (Some(start), Some(end)) = (offset(link.range.start), offset(link.range.end))
+            else {
+                continue;
+            };
+            if let Some(section) = sections
+                .iter_mut()
+                .find(|section| section.start <= start && end <= section.end)
+            {
+                section.edits.push((start, end, new_text));
+                continue;
+            }
+            groups
+                .entry(uri.clone())
+                .or_default()
+                .push(Change::TextEdit(TextEdit {
+                    range: link.range,
+                    new_text,
+                    undo_text: Some(content[start..end].to_string()),
+                }));
+        } else if let Some(path) = &source.path {
+            let source_uri = path.to_string_lossy().to_string();
+            let undo_text = content_provider.get_content(&source_uri).and_then(|text| {
+                let line_map = LineMap::new(&text);
+                let start = line_map.point_to_offset(&text, link.range.start)?;
+                let end = line_map.point_to_offset(&text, link.range.end)?;
+                text.get(start..end).map(str::to_string)
+            });
+            groups
+                .entry(source_uri)
+                .or_default()
+                .push(Change::TextEdit(TextEdit {
+                    range: link.range,
+                    new_text,
+                    undo_text,
+                }));
+        }
+    }
+
+    let mut edits = Vec::new();
+    let mut preconditions = Vec::new();
+    let mut replacements = Vec::new();
+    for section in sections {
+        let body = section_body(&content, &section);
+        let child_content = format!(
+            "{}\n\n{}",
+            model.generate_titled_note_content(&section.key, &section.title),
+            body
+        );
+        let child_path = model.path_from_note_key(&section.key);
+        preconditions.push(Precondition::PathNotExists(child_path.clone()));
+        edits.push(EditGroup {
+            uri: child_path.to_string_lossy().to_string(),
+            changes: vec![Change::ResourceOp(ResourceOperation::CreateFile {
+                content: Some(child_content.trim_end().to_string() + "\n"),
+            })],
+        });
+
+        let link = model.format_wikilink(
+            &section.key,
+            None,
+            None,
+            options.replacement == SectionReplacement::Embed,
+        );
+        let new_text = if section.end == content.len() && !content.ends_with('\n') {
+            link
+        } else if section.end == content.len() {
+            link + "\n"
+        } else {
+            link + "\n\n"
+        };
+        replacements.push(Change::TextEdit(TextEdit {
+            range: TextRange {
+                start: line_map.offset_to_point(&content, section.start),
+                end: line_map.offset_to_point(&content, section.end),
+            },
+            new_text,
+            undo_text: Some(content[section.start..section.end].to_string()),
+        }));
+    }
+    groups.entry(uri).or_default().extend(replacements);
+    edits.extend(
+        groups
+            .into_iter()
+            .map(|(uri, changes)| EditGroup { uri, changes }),
+    );
+
+    Some(
+        EditPlan {
+            mutation_kind: MutationKind::SplitNote,
+            edits,
+            preconditions,
+            diagnostics,
+            reversible: true,
+        }
+        .with_file_preconditions(content_provider),
+    )
+}
+
+/// The section a link anchor points into, and the anchor to keep in the child:
+/// none for the section heading itself.
+fn section_of_anchor<'a>(
+    note: &Note,
+    content: &str,
+    sections: &[Section],
+    anchor: &'a str,
+) -> Option<(usize, Option<&'a str>)> {
+    let line_map = LineMap::new(content);
+    let contains = |section: &Section, point| {
+        line_map
+            .point_to_offset(content, point)
+            .is_some_and(|offset| section.start <= offset && offset < section.end)
+    };
+
+    if let Some(block_id) = anchor.strip_prefix('^') {
+        let block = note.blocks.iter().find(|block| block.id == block_id)?;
+        let index = sections
+            .iter()
+            .position(|section| contains(section, block.range.start))?;
+        return Some((index, Some(anchor)));
+    }
+
+    let slug = slugify_heading(anchor);
+    if let Some(index) = sections.iter().position(|section| section.slug == slug) {
+        return Some((index, None));
+    }
+    let heading = note
+        .headings
+        .iter()
+        .find(|heading| slugify_heading(&heading.text) == slug)?;
+    let index = sections
+        .iter()
+        .position(|section| contains(section, heading.range.start))?;
+    Some((index, Some(anchor)))
+}
+
+/// The content under a section's heading, with the section's link edits applied
+fn section_body(content: &str, section: &Section) -> String {
+    let mut edits: Vec<_> = section
+        .edits
+        .iter()
+        .filter(|(start, _, _)| *start >= section.body_start)
+        .collect();
+    edits.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+
+    let mut body = content[..section.end].to_string();
+    for (start, end, new_text) in edits {
+        body.replace_range(*start..*end, new_text);
+    }
+    body[section.body_start..].trim().to_string()
 }
 
 /// Helper to extract text from content using TextRange (0-based line/col).
@@ -118,18 +415,10 @@ fn extract_text(content: &str, range: TextRange) -> Option<String> {
 mod tests {
     use super::*;
     use crate::model::{Note, Point};
+    use crate::test_fixtures::{setup, MockProvider};
+    use std::collections::HashMap;
     use std::path::PathBuf;
 
-    struct MockProvider {
-        content: String,
-    }
-
-    impl ContentProvider for MockProvider {
-        fn get_content(&self, _uri: &str) -> Option<String> {
-            Some(self.content.clone())
-        }
-    }
-
     #[test]
     fn test_extract_selection() {
         let mut store = Store::new();
@@ -145,7 +434,9 @@ mod tests {
         store.upsert_note(note_a);
 
         let content = "Line 1\nTarget Text\nLine 3".to_string();
-        let provider = MockProvider { content };
+        let provider = MockProvider {
+            files: HashMap::from([("source.md".to_string(), content)]),
+        };
 
         let model = crate::semantic::DendronModel::new(PathBuf::from("/"));
 
@@ -187,4 +478,116 @@ mod tests {
             panic!("Expected create file op");
         }
     }
+
+    #[test]
+    fn test_split_by_headings() {
+        let parent = "# Proj\nIntro\n\n## Alpha Part\nA text ^blk\n\nSee [[proj#beta]]\n\n### Deep\nDeep text\n\n## Beta\nB text\n";
+        let (ws, provider) = setup(&[
+            ("proj", parent),
+            (
+                "ref",
+                "[[proj#alpha-part]] [[Deep|proj#deep]] ![[proj#^blk]] [[proj#proj]]",
+            ),
+        ]);
+
+        let plan = ws
+            .split_note_by_headings(&provider, "proj", &SplitByHeadingOptions::default())
+            .unwrap();
+        assert!(plan.reversible);
+        assert!(plan.diagnostics.is_empty());
+        let undo = plan.undo_plan(&provider).unwrap();
+
+        let (_, contents) = crate::mutation::executor::dry_run(&plan, &provider).unwrap();
+        let content = |path: &str| contents[&PathBuf::from(path)].clone().unwrap();
+        assert_eq!(
+            content("/test/proj.md"),
+            "# Proj\nIntro\n\n![[proj.alpha-part]]\n\n![[proj.beta]]\n"
+        );
+        assert_eq!(
+            content("/test/ref.md"),
+            "[[proj.alpha-part]] [[Deep|proj.alpha-part#deep]] ![[proj.alpha-part#^blk]] [[proj#proj]]"
+        );
+        let alpha = content("/test/proj.alpha-part.md");
+        assert!(alpha.starts_with("---\n"));
+        // The heading text stays the child's title and first heading
+        assert!(alpha.contains("\ntitle: Alpha Part\n"));
+        assert!(alpha.ends_with(
+            "# Alpha Part\n\nA text ^blk\n\nSee [[proj.beta]]\n\n### Deep\nDeep text\n"
+        ));
+        assert!(content("/test/proj.beta.md").ends_with("# Beta\n\nB text\n"));
+
+        // Undoing removes the children and restores the parent and its backlinks
+        let mut after = MockProvider {
+            files: provider.files.clone(),
+        };
+        for (path, content) in contents {
+            let uri = path.to_string_lossy().to_string();
+            match content {
+                Some(content) => after.files.insert(uri, content),
+                None => after.files.remove(&uri),
+            };
+        }
+        let (_, restored) = crate::mutation::executor::dry_run(&undo, &after).unwrap();
+        for (path, content) in restored {
+            assert_eq!(
+                content.as_ref(),
+                provider.files.get(&*path.to_string_lossy()),
+                "{}",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn test_split_by_headings_skips_existing_children() {
+        let (ws, provider) = setup(&[
+            ("proj", "# Proj\n\n## Alpha\nA\n\n## Beta\nB"),
+            ("proj.beta", "# Beta"),
+        ]);
+        let options = SplitByHeadingOptions {
+            level: 2,
+            replacement: SectionReplacement::Link,
+        };
+
+        let plan = ws
+            .split_note_by_headings(&provider, "proj", &options)
+            .unwrap();
+        assert_eq!(plan.diagnostics.len(), 1);
+        assert_eq!(
+            plan.diagnostics[0].message,
+            "Section 'Beta' was not split: note 'proj.beta' already exists."
+        );
+        let (_, contents) = crate::mutation::executor::dry_run(&plan, &provider).unwrap();
+        assert_eq!(
+            contents[&PathBuf::from("/test/proj.md")].as_deref(),
+            Some("# Proj\n\n[[proj.alpha]]\n\n## Beta\nB")
+        );
+
+        let level_three = SplitByHeadingOptions {
+            level: 3,
+            ..options
+        };
+        assert!(ws
+            .split_note_by_headings(&provider, "proj", &level_three)
+            .is_none());
+    }
+
+    #[test]
+    fn test_split_by_headings_takes_ghost_and_dangling_keys() {
+        let (mut ws, provider) = setup(&[
+            ("proj", "# Proj\n\n## Alpha\nA\n\n## Beta\nB"),
+            ("proj.beta.x", "# X"),
+            ("ref", "Soon: [[proj.alpha]]"),
+        ]);
+        // 'proj.beta' is a ghost level, 'proj.alpha' only a dangling link
+        ws.fill_missing_hierarchy_levels();
+
+        let plan = ws
+            .split_note_by_headings(&provider, "proj", &SplitByHeadingOptions::default())
+            .unwrap();
+        assert!(plan.diagnostics.is_empty());
+        let (_, contents) = crate::mutation::executor::dry_run(&plan, &provider).unwrap();
+        assert!(contents[&PathBuf::from("/test/proj.alpha.md")].is_some());
+        assert!(contents[&PathBuf::from("/test/proj.beta.md")].is_some());
+    }
 }
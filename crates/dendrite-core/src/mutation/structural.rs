@@ -1,6 +1,6 @@
 use crate::identity::IdentityRegistry;
 use crate::line_map::LineMap;
-use crate::model::{LinkKind, NoteId};
+use crate::model::{Link, LinkKind, NoteId};
 use crate::mutation::model::{
     Change, ContentProvider, EditGroup, EditPlan, MutationKind, Precondition, ResourceOperation,
     TextEdit,
@@ -41,7 +41,6 @@ pub(crate) fn calculate_structural_edits(
 
     // 2. Preconditions
     let mut preconditions = Vec::new();
-    preconditions.push(Precondition::NoteExists(note_id.0.to_string()));
     if is_move {
         preconditions.push(Precondition::PathNotExists(new_path.clone()));
     }
@@ -75,49 +74,34 @@ pub(crate) fn calculate_structural_edits(
             let mut changes = Vec::new();
 
             for link in &source_note.links {
-                if link.target == *note_id {
+                // Links written against one of the note's aliases keep working as they are
+                let via_alias = !link.target_key.is_empty()
+                    && identity.key_of(note_id).as_ref() != Some(&link.target_key);
+                if link.target == *note_id && !via_alias {
                     let mut needs_update = false;
                     let mut new_text = String::new();
 
                     match link.kind {
-                        LinkKind::WikiLink { .. } | LinkKind::EmbeddedWikiLink { .. } => {
-                            if is_rename {
-                                needs_update = true;
-                                new_text = model.format_wikilink(
-                                    new_key,
-                                    link.alias.as_deref(),
-                                    link.anchor.as_deref(),
-                                    matches!(link.kind, LinkKind::EmbeddedWikiLink { .. }),
-                                );
-                            }
+                        LinkKind::WikiLink { .. } | LinkKind::EmbeddedWikiLink { .. }
+                            if is_rename =>
+                        {
+                            needs_update = true;
+                            new_text = model.format_wikilink(
+                                &qualified_key(link, new_key),
+                                link.alias.as_deref(),
+                                link.anchor.as_deref(),
+                                matches!(link.kind, LinkKind::EmbeddedWikiLink { .. }),
+                            );
                         }
-                        LinkKind::MarkdownLink => {
-                            if is_rename || is_move {
-                                needs_update = true;
-                                let mut text = String::from("[");
-                                if let Some(alias) = &link.alias {
-                                    text.push_str(alias);
-                                } else {
-                                    text.push_str(new_key);
-                                }
-                                text.push_str("](");
-
-                                // Calculate relative path if we have both paths
-                                if let Some(source_path) = source_note.path.as_ref() {
-                                    let rel_path = calculate_relative_path(source_path, &new_path);
-                                    let rel_str = rel_path.to_string_lossy().replace('\\', "/");
-                                    text.push_str(&rel_str);
-                                } else {
-                                    // Fallback to simple key-based path
-                                    let ext = model.supported_extensions().first().unwrap_or(&"md");
-                                    text.push_str(new_key);
-                                    text.push('.');
-                                    text.push_str(ext);
-                                }
-
-                                text.push(')');
-                                new_text = text;
-                            }
+                        LinkKind::MarkdownLink if is_rename || is_move => {
+                            needs_update = true;
+                            new_text = markdown_link_text(
+                                model,
+                                link,
+                                source_note.path.as_deref(),
+                                &new_path,
+                                new_key,
+                            );
                         }
                         _ => {}
                     }
@@ -156,17 +140,66 @@ pub(crate) fn calculate_structural_edits(
         }
     }
 
-    Some(EditPlan {
-        mutation_kind: if is_rename {
-            MutationKind::RenameNote
-        } else {
-            MutationKind::MoveNote
-        },
-        edits,
-        preconditions,
-        diagnostics: vec![],
-        reversible: true,
-    })
+    Some(
+        EditPlan {
+            mutation_kind: if is_rename {
+                MutationKind::RenameNote
+            } else {
+                MutationKind::MoveNote
+            },
+            edits,
+            preconditions,
+            diagnostics: vec![],
+            reversible: true,
+        }
+        .with_file_preconditions(content_provider),
+    )
+}
+
+/// `key` with the vault qualifier `link` was written with, if any
+pub(crate) fn qualified_key(link: &Link, key: &str) -> String {
+    match &link.target_vault {
+        Some(vault) => format!("dendron://{}/{}", vault, key),
+        None => key.to_string(),
+    }
+}
+
+/// `[label](path#anchor)` pointing at `new_path`, relative to the linking note.
+/// The link's anchor is kept.
+pub(crate) fn markdown_link_text(
+    model: &dyn SemanticModel,
+    link: &Link,
+    source_path: Option<&Path>,
+    new_path: &Path,
+    new_key: &str,
+) -> String {
+    let mut text = String::from("[");
+    if let Some(alias) = &link.alias {
+        text.push_str(alias);
+    } else {
+        text.push_str(new_key);
+    }
+    text.push_str("](");
+
+    // Calculate relative path if we have both paths
+    if let Some(source_path) = source_path {
+        let rel_path = calculate_relative_path(source_path, new_path);
+        let rel_str = rel_path.to_string_lossy().replace('\\', "/");
+        text.push_str(&rel_str);
+    } else {
+        // Fallback to simple key-based path
+        let ext = model.supported_extensions().first().unwrap_or(&"md");
+        text.push_str(new_key);
+        text.push('.');
+        text.push_str(ext);
+    }
+    if let Some(anchor) = &link.anchor {
+        text.push('#');
+        text.push_str(anchor.trim_start_matches('#'));
+    }
+
+    text.push(')');
+    text
 }
 
 fn calculate_relative_path(from: &Path, to: &Path) -> PathBuf {
@@ -246,6 +279,8 @@ mod tests {
         let mut note_a = note_a;
         note_a.links.push(Link {
             target: id_b.clone(),
+            target_key: "B".to_string(),
+            target_vault: None,
             raw_target: "B".to_string(),
             alias: None,
             anchor: None,
@@ -353,6 +388,8 @@ mod tests {
 
         note_ref.links.push(Link {
             target: id_old.clone(),
+            target_key: "Old Note".to_string(),
+            target_vault: None,
             raw_target: "Old Note".to_string(),
             alias: None,
             anchor: Some("^block-id".to_string()),
@@ -392,6 +429,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rename_keeps_alias_links() {
+        let mut store = Store::new();
+        let mut identity = IdentityRegistry::new();
+
+        let id_target = identity.get_or_create(&"Target".to_string());
+        let id_ref = identity.get_or_create(&"Referencer".to_string());
+
+        let mut note_ref = create_dummy_note(id_ref.clone(), "Referencer");
+        for (index, key) in ["Target", "Nickname"].into_iter().enumerate() {
+            note_ref.links.push(Link {
+                target: id_target.clone(),
+                target_key: key.to_string(),
+                target_vault: None,
+                raw_target: key.to_string(),
+                range: TextRange {
+                    start: Point {
+                        line: index as u32,
+                        col: 0,
+                    },
+                    end: Point {
+                        line: index as u32,
+                        col: key.len() as u32 + 4,
+                    },
+                },
+                kind: LinkKind::WikiLink(crate::model::WikiLinkFormat::AliasFirst),
+                ..Default::default()
+            });
+        }
+
+        store.upsert_note(create_dummy_note(id_target.clone(), "Target"));
+        store.upsert_note(note_ref);
+        store.set_outgoing_links(&id_ref, vec![id_target.clone(), id_target.clone()]);
+
+        let model = crate::semantic::DendronModel::new(PathBuf::from("/test"));
+        let plan = calculate_structural_edits(
+            &store,
+            &identity,
+            &MockContentProvider,
+            &model,
+            &id_target,
+            PathBuf::from("Renamed.md"),
+            "Renamed",
+        )
+        .unwrap();
+
+        // [[Nickname]] still resolves through the alias, so only [[Target]] is rewritten
+        let edits: Vec<_> = plan
+            .edits
+            .iter()
+            .flat_map(|g| &g.changes)
+            .filter_map(|c| match c {
+                Change::TextEdit(edit) => Some((edit.range.start.line, edit.new_text.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(edits, vec![(0, "[[Renamed]]")]);
+    }
+
     #[test]
     fn test_move_cross_folder_markdown_link() {
         let mut store = Store::new();
@@ -407,6 +503,8 @@ mod tests {
 
         note_source.links.push(Link {
             target: id_target.clone(),
+            target_key: "Target".to_string(),
+            target_vault: None,
             raw_target: "Target.md".to_string(),
             alias: None,
             anchor: None,
@@ -449,6 +547,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rename_markdown_link_keeps_anchor() {
+        let mut store = Store::new();
+        let mut identity = IdentityRegistry::new();
+
+        let id_target = identity.get_or_create(&"t".to_string());
+        let id_source = identity.get_or_create(&"s".to_string());
+
+        let note_target = create_dummy_note(id_target.clone(), "t");
+        let mut note_source = create_dummy_note(id_source.clone(), "s");
+        note_source.links.push(Link {
+            target: id_target.clone(),
+            target_key: "t".to_string(),
+            target_vault: None,
+            raw_target: "t.md#h".to_string(),
+            alias: Some("x".to_string()),
+            anchor: Some("h".to_string()),
+            range: TextRange::default(),
+            kind: LinkKind::MarkdownLink,
+        });
+
+        store.upsert_note(note_target);
+        store.upsert_note(note_source);
+        store.set_outgoing_links(&id_source, vec![id_target.clone()]);
+
+        let model = crate::semantic::DendronModel::new(PathBuf::from("/test"));
+        let plan = calculate_structural_edits(
+            &store,
+            &identity,
+            &MockContentProvider,
+            &model,
+            &id_target,
+            PathBuf::from("u.md"),
+            "u",
+        )
+        .expect("Plan generated");
+
+        let new_texts: Vec<_> = plan
+            .edits
+            .iter()
+            .flat_map(|g| &g.changes)
+            .filter_map(|c| match c {
+                Change::TextEdit(edit) => Some(edit.new_text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(new_texts, vec!["[x](u.md#h)"]);
+    }
+
     #[test]
     fn test_move_note_undo_cycle() {
         let mut store = Store::new();
@@ -463,6 +610,8 @@ mod tests {
         note_source.path = Some(PathBuf::from("Source.md"));
         note_source.links.push(Link {
             target: id_target.clone(),
+            target_key: "Target".to_string(),
+            target_vault: None,
             raw_target: "Target.md".to_string(),
             alias: None,
             anchor: None,
@@ -0,0 +1,410 @@
+use crate::config::VaultConfig;
+use crate::identity::IdentityRegistry;
+use crate::line_map::LineMap;
+use crate::model::{LinkKind, NoteId};
+use crate::mutation::model::{
+    Change, ContentProvider, EditGroup, EditPlan, MutationKind, TextEdit,
+};
+use crate::mutation::structural::{calculate_structural_edits, markdown_link_text};
+use crate::semantic::SemanticModel;
+use crate::store::Store;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Calculate edits moving the note `key` (and with `include_descendants`, its
+/// hierarchy) into another vault, keeping each file's place inside its vault.
+///
+/// 1. Moves each file from its vault's `path` to the same place under the target's.
+/// 2. Rewrites relative markdown links from and to the moved notes.
+/// 3. Points vault-qualified wikilinks (`[[dendron://vault/key]]`) to the moved
+///    notes at the target vault, and qualifies the wikilinks to a moved note
+///    whose key also names a note in another vault.
+///
+/// A key shared by notes in several vaults names the note `identity` looks up
+/// for it, as in the other key-based commands.
+///
+/// Returns None if the vault is unknown or no note outside it matches.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn calculate_move_to_vault_edits(
+    store: &Store,
+    identity: &IdentityRegistry,
+    content_provider: &dyn ContentProvider,
+    model: &dyn SemanticModel,
+    vaults: &[VaultConfig],
+    key: &str,
+    include_descendants: bool,
+    target_vault: &str,
+) -> Option<EditPlan> {
+    let target = vaults.iter().find(|vault| vault.name == target_vault)?;
+    let target_dir = target.resolve(model.root());
+
+    // (note, key, new path) of every moved note
+    let mut moves: Vec<(NoteId, String, PathBuf)> = Vec::new();
+    for note in store.all_notes() {
+        let (Some(path), Some(note_key)) = (&note.path, identity.key_of(&note.id)) else {
+            continue;
+        };
+        let in_scope = note_key == key
+            || (include_descendants && model.is_descendant(&note_key, &key.to_string()));
+        let named = identity.lookup(&note_key).as_ref() == Some(&note.id);
+        if !in_scope || !named || note.vault_name == target.name {
+            continue;
+        }
+        let Some(relative) = vaults
+            .iter()
+            .find(|vault| vault.name == note.vault_name)
+            .and_then(|vault| path.strip_prefix(vault.resolve(model.root())).ok())
+        else {
+            continue;
+        };
+        moves.push((note.id.clone(), note_key, target_dir.join(relative)));
+    }
+    if moves.is_empty() {
+        return None;
+    }
+    moves.sort_by(|a, b| a.1.cmp(&b.1));
+    let new_paths: HashMap<&NoteId, &PathBuf> =
+        moves.iter().map(|(id, _, path)| (id, path)).collect();
+    let moved_uris: Vec<String> = moves
+        .iter()
+        .filter_map(|(id, _, _)| store.get_note(id)?.path.as_ref())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+
+    let mut text_edits: BTreeMap<String, Vec<Change>> = BTreeMap::new();
+    let mut renames = Vec::new();
+    for (note_id, note_key, new_path) in &moves {
+        let Some(plan) = calculate_structural_edits(
+            store,
+            identity,
+            content_provider,
+            model,
+            note_id,
+            new_path.clone(),
+            note_key,
+        ) else {
+            continue;
+        };
+        for group in plan.edits {
+            for change in group.changes {
+                match change {
+                    Change::ResourceOp(_) => renames.push(EditGroup {
+                        uri: group.uri.clone(),
+                        changes: vec![change],
+                    }),
+                    // Links in moved notes are rewritten below, from their new place
+                    Change::TextEdit(_) if !moved_uris.contains(&group.uri) => text_edits
+                        .entry(group.uri.clone())
+                        .or_default()
+                        .push(change),
+                    Change::TextEdit(_) => {}
+                }
+            }
+        }
+
+        // Unqualified links cannot tell the moved note from others sharing its key
+        let shared = identity
+            .notes_named(note_key)
+            .iter()
+            .any(|id| id != note_id && store.get_note(id).is_some_and(|note| note.path.is_some()));
+        let qualified = format!("dendron://{}/{}", target.name, note_key);
+        for (source, link) in store.backlink_refs(note_id) {
+            let embed = match link.kind {
+                LinkKind::WikiLink(_) => false,
+                LinkKind::EmbeddedWikiLink(_) => true,
+                _ => continue,
+            };
+            if link.target_vault.is_none() && !shared {
+                continue;
+            }
+            let Some(uri) = source
+                .path
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            let new_text = model.format_wikilink(
+                &qualified,
+                link.alias.as_deref(),
+                link.anchor.as_deref(),
+                embed,
+            );
+            let undo_text = original_text(content_provider, &uri, link.range);
+            text_edits
+                .entry(uri)
+                .or_default()
+                .push(Change::TextEdit(TextEdit {
+                    range: link.range,
+                    new_text,
+                    undo_text,
+                }));
+        }
+    }
+
+    // Markdown links written in moved notes, relative to where they end up
+    for (note_id, _, new_path) in &moves {
+        let Some(note) = store.get_note(note_id) else {
+            continue;
+        };
+        let Some(uri) = note.path.as_ref().map(|p| p.to_string_lossy().to_string()) else {
+            continue;
+        };
+        for link in &note.links {
+            if link.kind != LinkKind::MarkdownLink {
+                continue;
+            }
+            let Some(target_note) = store.get_note(&link.target) else {
+                continue;
+            };
+            let (Some(target_path), Some(target_key)) = (
+                new_paths
+                    .get(&link.target)
+                    .copied()
+                    .or(target_note.path.as_ref()),
+                identity.key_of(&link.target),
+            ) else {
+                continue;
+            };
+            let new_text =
+                markdown_link_text(model, link, Some(new_path), target_path, &target_key);
+            let undo_text = original_text(content_provider, &uri, link.range);
+            if undo_text.as_deref() != Some(new_text.as_str()) {
+                text_edits
+                    .entry(uri.clone())
+                    .or_default()
+                    .push(Change::TextEdit(TextEdit {
+                        range: link.range,
+                        new_text,
+                        undo_text,
+                    }));
+            }
+        }
+    }
+
+    // All text edits happen before the files move
+    let mut edits: Vec<EditGroup> = text_edits
+        .into_iter()
+        .map(|(uri, changes)| EditGroup { uri, changes })
+        .collect();
+    edits.extend(renames);
+
+    Some(
+        EditPlan {
+            mutation_kind: MutationKind::MoveToVault,
+            edits,
+            preconditions: vec![],
+            diagnostics: Vec::new(),
+            reversible: true,
+        }
+        .with_file_preconditions(content_provider),
+    )
+}
+
+fn original_text(
+    content_provider: &dyn ContentProvider,
+    uri: &str,
+    range: crate::model::TextRange,
+) -> Option<String> {
+    let content = content_provider.get_content(uri)?;
+    let line_map = LineMap::new(&content);
+    let start = line_map.point_to_offset(&content, range.start)?;
+    let end = line_map.point_to_offset(&content, range.end)?;
+    content.get(start..end).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DendriteConfig, WorkspaceConfig};
+    use crate::test_fixtures::{dendron_workspace, test_path, MockProvider};
+    use crate::vfs::PhysicalFileSystem;
+    use crate::workspace::{Indexer, Workspace};
+
+    /// Notes as (vault, key, content), indexed in order
+    fn setup(notes: &[(&str, &str, &str)]) -> (Workspace, MockProvider) {
+        let vaults = ["work", "archive", "personal"]
+            .into_iter()
+            .map(|name| VaultConfig {
+                name: name.to_string(),
+                path: PathBuf::from(name),
+            })
+            .collect();
+        let config = DendriteConfig {
+            workspace: WorkspaceConfig {
+                vaults,
+                ..DendriteConfig::default().workspace
+            },
+            ..DendriteConfig::default()
+        };
+        let mut ws = dendron_workspace(config);
+        let mut provider = MockProvider::default();
+        for (vault, key, content) in notes {
+            let path = test_path(&format!("{}/{}.md", vault, key));
+            provider.index(&mut ws, path, content, vault);
+        }
+        (ws, provider)
+    }
+
+    #[test]
+    fn test_move_subtree_to_vault() {
+        let (ws, provider) = setup(&[
+            (
+                "work",
+                "proj",
+                "# Proj\n[Plan](proj.plan.md#goals) [Home](index.md)",
+            ),
+            ("work", "proj.plan", "# Plan\n## Goals"),
+            ("work", "index", "[[proj]] and [Plan](proj.plan.md)"),
+        ]);
+
+        let plan = ws
+            .move_to_vault(&provider, "proj", true, "archive")
+            .unwrap();
+        assert_eq!(plan.mutation_kind, MutationKind::MoveToVault);
+        assert!(ws
+            .move_to_vault(&provider, "proj", true, "nowhere")
+            .is_none());
+        assert!(ws
+            .move_to_vault(&provider, "missing", true, "archive")
+            .is_none());
+
+        let (report, contents) = crate::mutation::executor::dry_run(&plan, &provider).unwrap();
+        assert_eq!(
+            report.renamed,
+            vec![
+                (test_path("work/proj.md"), test_path("archive/proj.md")),
+                (
+                    test_path("work/proj.plan.md"),
+                    test_path("archive/proj.plan.md")
+                ),
+            ]
+        );
+        // Links between moved notes keep working; links to notes left behind are redirected
+        assert_eq!(
+            contents[&test_path("archive/proj.md")].as_deref(),
+            Some("# Proj\n[Plan](proj.plan.md#goals) [Home](../work/index.md)")
+        );
+        // The key stays unique, so wikilinks are left alone
+        assert_eq!(
+            contents[&test_path("work/index.md")].as_deref(),
+            Some("[[proj]] and [Plan](../archive/proj.plan.md)")
+        );
+
+        // Only the note itself without its descendants
+        let plan = ws
+            .move_to_vault(&provider, "proj", false, "archive")
+            .unwrap();
+        let (report, _) = crate::mutation::executor::dry_run(&plan, &provider).unwrap();
+        assert_eq!(report.renamed.len(), 1);
+    }
+
+    /// Where the links of the note at `path` point, as (path, vault)
+    fn link_targets(ws: &Workspace, path: &PathBuf) -> Vec<(PathBuf, String)> {
+        let id = ws.store.note_id_by_path(path).unwrap();
+        ws.store
+            .get_note(id)
+            .unwrap()
+            .links
+            .iter()
+            .map(|link| {
+                let target = ws.store.get_note(&link.target).unwrap();
+                (target.path.clone().unwrap(), target.vault_name.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_move_to_vault_qualifies_shared_keys() {
+        let (mut ws, provider) = setup(&[
+            ("work", "todo", "---\nid: work-todo\n---\n# Work"),
+            (
+                "work",
+                "index",
+                "See [[Tasks|todo#today]] and [[dendron://personal/todo]]",
+            ),
+            (
+                "personal",
+                "todo",
+                "---\nid: personal-todo\n---\n# Personal",
+            ),
+        ]);
+        let index_path = test_path("work/index.md");
+        assert_eq!(
+            link_targets(&ws, &index_path),
+            vec![
+                (test_path("work/todo.md"), "work".to_string()),
+                (test_path("personal/todo.md"), "personal".to_string()),
+            ]
+        );
+
+        let plan = ws
+            .move_to_vault(&provider, "todo", false, "archive")
+            .unwrap();
+        assert!(plan.diagnostics.is_empty());
+        let (report, contents) = crate::mutation::executor::dry_run(&plan, &provider).unwrap();
+        assert_eq!(
+            report.renamed,
+            vec![(test_path("work/todo.md"), test_path("archive/todo.md"))]
+        );
+        // The key also names the personal note, so the link to the moved one is qualified
+        let index = contents[&index_path].clone().unwrap();
+        assert_eq!(
+            index,
+            "See [[Tasks|dendron://archive/todo#today]] and [[dendron://personal/todo]]"
+        );
+
+        // Once the move is indexed, the qualified link resolves to the moved note
+        let fs = PhysicalFileSystem;
+        let mut indexer = Indexer::new(&mut ws, &fs);
+        indexer.rename_file(
+            test_path("work/todo.md"),
+            test_path("archive/todo.md"),
+            "---\nid: work-todo\n---\n# Work",
+            "archive".to_string(),
+        );
+        indexer.update_content(index_path.clone(), &index, "work".to_string());
+        assert_eq!(
+            link_targets(&ws, &index_path),
+            vec![
+                (test_path("archive/todo.md"), "archive".to_string()),
+                (test_path("personal/todo.md"), "personal".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_move_to_vault_retargets_qualified_links() {
+        let (mut ws, provider) = setup(&[
+            ("work", "todo", "# Todo"),
+            ("work", "index", "[[dendron://work/todo#today]]"),
+        ]);
+
+        let plan = ws
+            .move_to_vault(&provider, "todo", false, "archive")
+            .unwrap();
+        assert!(plan.diagnostics.is_empty());
+        let (_, contents) = crate::mutation::executor::dry_run(&plan, &provider).unwrap();
+        let index = contents[&test_path("work/index.md")].clone().unwrap();
+        assert_eq!(index, "[[dendron://archive/todo#today]]");
+
+        // Once the move is indexed, the qualified link resolves to the moved note
+        let fs = PhysicalFileSystem;
+        let mut indexer = Indexer::new(&mut ws, &fs);
+        indexer.rename_file(
+            test_path("work/todo.md"),
+            test_path("archive/todo.md"),
+            "# Todo",
+            "archive".to_string(),
+        );
+        indexer.update_content(test_path("work/index.md"), &index, "work".to_string());
+        let index_id = ws
+            .store
+            .note_id_by_path(&test_path("work/index.md"))
+            .unwrap();
+        let link = &ws.store.get_note(index_id).unwrap().links[0];
+        let target = ws.store.get_note(&link.target).unwrap();
+        assert_eq!(target.path, Some(test_path("archive/todo.md")));
+        assert_eq!(target.vault_name, "archive");
+    }
+}
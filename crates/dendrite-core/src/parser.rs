@@ -29,8 +29,9 @@ pub(crate) fn compute_digest(text: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
-/// Parse markdown content into structured data
-pub(crate) fn parse_markdown(text: &str, supported_kinds: &[LinkKind]) -> ParseResult {
+/// Build pulldown-cmark options for the given link kinds.
+/// Returns the options and the wikilink format in effect (if wikilinks are enabled).
+pub(crate) fn markdown_options(supported_kinds: &[LinkKind]) -> (Options, Option<WikiLinkFormat>) {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_FOOTNOTES);
@@ -39,26 +40,50 @@ pub(crate) fn parse_markdown(text: &str, supported_kinds: &[LinkKind]) -> ParseR
     options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
 
     // Dynamic configuration based on supported kinds
-    let mut enable_wikilinks = false;
-    let mut wikilink_format = WikiLinkFormat::AliasFirst; // Default
+    let mut wikilink_format = None;
 
     for kind in supported_kinds {
         match kind {
             LinkKind::WikiLink(format) | LinkKind::EmbeddedWikiLink(format) => {
-                enable_wikilinks = true;
-                wikilink_format = *format;
+                wikilink_format = Some(*format);
             }
             _ => {}
         }
     }
 
-    if enable_wikilinks {
+    if wikilink_format.is_some() {
         options.insert(Options::ENABLE_WIKILINKS);
     }
     // Pulldown doesn't have a separate flag for autolinks in new versions,
     // it's often enabled by default or via linkify. But check options if needed.
     // For now we just use standard parser behavior for autolinks if they appear.
 
+    (options, wikilink_format)
+}
+
+/// Split wikilink parts into (target, alias) according to the wikilink format.
+///
+/// pulldown-cmark reports `[[left|right]]` as a link to `left` with text `right`.
+pub(crate) fn split_wikilink<'s>(
+    left: &'s str,
+    right: &'s str,
+    format: WikiLinkFormat,
+) -> (&'s str, Option<&'s str>) {
+    if left == right || right.is_empty() {
+        return (left, None);
+    }
+    match format {
+        WikiLinkFormat::AliasFirst => (right, Some(left)),
+        WikiLinkFormat::TargetFirst => (left, Some(right)),
+    }
+}
+
+/// Parse markdown content into structured data
+pub(crate) fn parse_markdown(text: &str, supported_kinds: &[LinkKind]) -> ParseResult {
+    let (options, format) = markdown_options(supported_kinds);
+    let enable_wikilinks = format.is_some();
+    let wikilink_format = format.unwrap_or(WikiLinkFormat::AliasFirst);
+
     let parser = Parser::new_ext(text, options);
     let line_map = LineMap::new(text);
 
@@ -209,7 +234,7 @@ pub(crate) fn parse_markdown(text: &str, supported_kinds: &[LinkKind]) -> ParseR
                     collector: String::new(),
                 });
             }
-            Event::End(TagEnd::Link { .. }) | Event::End(TagEnd::Image) => {
+            Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => {
                 if let Some(pending) = pending_link.take() {
                     let mut end_offset = range.end;
                     // For wikilinks, pulldown_cmark might report range ending before the last ']'
@@ -224,22 +249,8 @@ pub(crate) fn parse_markdown(text: &str, supported_kinds: &[LinkKind]) -> ParseR
                     let right = pending.collector.trim();
 
                     let (mut final_target, alias, kind) = if pending.is_wikilink {
-                        let (target, alias) = match wikilink_format {
-                            WikiLinkFormat::AliasFirst => {
-                                if left == right || right.is_empty() {
-                                    (left.to_string(), None)
-                                } else {
-                                    (right.to_string(), Some(left.to_string()))
-                                }
-                            }
-                            WikiLinkFormat::TargetFirst => {
-                                if left == right || right.is_empty() {
-                                    (left.to_string(), None)
-                                } else {
-                                    (left.to_string(), Some(right.to_string()))
-                                }
-                            }
-                        };
+                        let (target, alias) = split_wikilink(left, right, wikilink_format);
+                        let (target, alias) = (target.to_string(), alias.map(str::to_string));
                         let kind = if pending.is_embedded {
                             LinkKind::EmbeddedWikiLink(wikilink_format)
                         } else {
@@ -0,0 +1,372 @@
+use super::transclusion::{Transcluder, DEFAULT_MAX_EMBED_DEPTH};
+use crate::identity::IdentityRegistry;
+use crate::model::{Link, LinkKind, NoteId, NoteKey};
+use crate::mutation::model::ContentProvider;
+use crate::parser::{markdown_options, split_wikilink};
+use crate::semantic::SemanticModel;
+use crate::store::Store;
+use pulldown_cmark::{html, Event, LinkType, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How frontmatter is presented in rendered HTML
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FrontmatterMode {
+    #[default]
+    Strip,
+    Table,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub frontmatter: FrontmatterMode,
+    /// How many levels of nested `![[embed]]`s are expanded inline
+    pub max_embed_depth: usize,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            frontmatter: FrontmatterMode::default(),
+            max_embed_depth: DEFAULT_MAX_EMBED_DEPTH,
+        }
+    }
+}
+
+/// Render a note to HTML for preview.
+///
+/// - Wikilinks are resolved to `to_uri(path)` of their target; unresolved ones get the `broken` class
+/// - Markdown links to notes are resolved to `to_uri(path)` too; other links are kept
+/// - `![[embeds]]` (including block refs) are expanded inline via transclusion
+/// - Frontmatter is stripped, or rendered as a table depending on `options`
+///
+/// Raw HTML in notes is passed through unchanged, as the embed wrappers are.
+/// The output is not sanitized: clients displaying it must sanitize it or
+/// restrict it (e.g. with a Content Security Policy).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn render_note_html(
+    store: &Store,
+    identity: &IdentityRegistry,
+    model: &dyn SemanticModel,
+    content_provider: &dyn ContentProvider,
+    note_id: &NoteId,
+    options: &RenderOptions,
+    to_uri: &dyn Fn(&Path) -> String,
+) -> Option<String> {
+    let note = store.get_note(note_id)?;
+    let source_key = identity.key_of(note_id)?;
+
+    let href_of = |target: &NoteId, anchor: Option<&str>| {
+        let path = store.get_note(target)?.path.as_deref()?;
+        Some(match anchor {
+            Some(anchor) => format!("{}#{}", to_uri(path), anchor),
+            None => to_uri(path),
+        })
+    };
+
+    // Links of embedded notes are resolved against the note they were written in
+    let resolve_embedded_link = |link: &Link| -> Option<String> {
+        match link.kind {
+            LinkKind::WikiLink(_) | LinkKind::EmbeddedWikiLink(_) => {
+                let key = identity.key_of(&link.target)?;
+                let label = match (&link.alias, &link.anchor) {
+                    (Some(alias), _) => alias.clone(),
+                    (None, Some(anchor)) => format!("{}#{}", key, anchor),
+                    (None, None) => key.clone(),
+                };
+                let href = href_of(&link.target, link.anchor.as_deref());
+                let is_embed = matches!(link.kind, LinkKind::EmbeddedWikiLink(_));
+                Some(wikilink_html(&key, &label, href, is_embed))
+            }
+            LinkKind::MarkdownLink => {
+                let href = href_of(&link.target, link.anchor.as_deref())?;
+                let label = link.alias.clone().unwrap_or_default();
+                Some(format!("[{}](<{}>)", label, href))
+            }
+            _ => None,
+        }
+    };
+
+    // Markdown links of the rendered note itself; embedded ones are resolved above
+    let resolve_markdown_link = |dest: &str| -> Option<String> {
+        // Anchors within the page, and URLs with a scheme (`https:`, `mailto:`)
+        if dest.is_empty() || dest.starts_with('#') || dest.contains(':') {
+            return None;
+        }
+        let (path, anchor) = match dest.split_once('#') {
+            Some((path, anchor)) => (path, Some(anchor)),
+            None => (dest, None),
+        };
+        let key = model.note_key_from_link(&source_key, path);
+        let id = identity.resolve(&key, |id| {
+            store.get_note(id).is_some_and(|n| n.path.is_some())
+        })?;
+        href_of(&id, anchor)
+    };
+
+    let markdown = Transcluder {
+        store,
+        content_provider,
+        max_depth: options.max_embed_depth,
+        wrap_embeds: true,
+        resolve_link: Some(&resolve_embedded_link),
+    }
+    .expand(note_id, None, 0, &mut Vec::new())?;
+
+    let mut out = String::new();
+
+    if options.frontmatter == FrontmatterMode::Table {
+        if let Some(serde_json::Value::Object(fields)) = &note.frontmatter {
+            out.push_str("<table class=\"dendrite-frontmatter\">\n");
+            for (key, value) in fields {
+                let value = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                out.push_str(&format!(
+                    "<tr><th>{}</th><td>{}</td></tr>\n",
+                    escape_html(key),
+                    escape_html(&value)
+                ));
+            }
+            out.push_str("</table>\n");
+        }
+    }
+
+    let (parser_options, format) = markdown_options(&model.supported_link_kinds());
+    let format = format.unwrap_or_default();
+
+    struct PendingWikiLink {
+        dest: String,
+        text: String,
+        is_embed: bool,
+    }
+    let mut pending: Option<PendingWikiLink> = None;
+    let mut events = Vec::new();
+
+    for event in Parser::new_ext(&markdown, parser_options) {
+        match event {
+            Event::Start(Tag::Link {
+                link_type: LinkType::WikiLink { .. },
+                dest_url,
+                ..
+            }) => {
+                pending = Some(PendingWikiLink {
+                    dest: dest_url.to_string(),
+                    text: String::new(),
+                    is_embed: false,
+                });
+            }
+            Event::Start(Tag::Image {
+                link_type: LinkType::WikiLink { .. },
+                dest_url,
+                ..
+            }) => {
+                pending = Some(PendingWikiLink {
+                    dest: dest_url.to_string(),
+                    text: String::new(),
+                    is_embed: true,
+                });
+            }
+            Event::End(TagEnd::Link) | Event::End(TagEnd::Image) if pending.is_some() => {
+                let link = pending.take().unwrap();
+                let (target, alias) = split_wikilink(link.dest.trim(), link.text.trim(), format);
+                let (target_key, anchor) = match target.split_once('#') {
+                    Some((key, anchor)) => (key, Some(anchor)),
+                    None => (target, None),
+                };
+
+                let key: NoteKey = if target_key.is_empty() {
+                    source_key.clone()
+                } else {
+                    model.note_key_from_link(&source_key, target_key)
+                };
+                let href = identity
+                    .resolve(&key, |id| {
+                        store.get_note(id).is_some_and(|n| n.path.is_some())
+                    })
+                    .and_then(|id| href_of(&id, anchor));
+
+                events.push(Event::InlineHtml(
+                    wikilink_html(&key, alias.unwrap_or(target), href, link.is_embed).into(),
+                ));
+            }
+            Event::Text(text) | Event::Code(text) if pending.is_some() => {
+                pending.as_mut().unwrap().text.push_str(&text);
+            }
+            // Formatting inside a wikilink label is flattened
+            _ if pending.is_some() => {}
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => {
+                let dest_url = resolve_markdown_link(&dest_url).map_or(dest_url, Into::into);
+                events.push(Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }));
+            }
+            // Frontmatter of the rendered note is handled above; never echo it
+            Event::Start(Tag::MetadataBlock(_)) | Event::End(TagEnd::MetadataBlock(_)) => {}
+            other => events.push(other),
+        }
+    }
+
+    html::push_html(&mut out, events.into_iter());
+    Some(out)
+}
+
+fn wikilink_html(key: &str, label: &str, href: Option<String>, is_embed: bool) -> String {
+    let mut classes = String::from("wikilink");
+    if is_embed {
+        classes.push_str(" embed");
+    }
+
+    match href {
+        Some(href) => format!(
+            "<a class=\"{}\" href=\"{}\" data-note-key=\"{}\">{}</a>",
+            classes,
+            escape_html(&href),
+            escape_html(key),
+            escape_html(label)
+        ),
+        None => format!(
+            "<a class=\"{} broken\" data-note-key=\"{}\" title=\"Note not found\">{}</a>",
+            classes,
+            escape_html(key),
+            escape_html(label)
+        ),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::setup;
+
+    fn render(notes: &[(&str, &str)], key: &str, options: &RenderOptions) -> String {
+        let (ws, provider) = setup(notes);
+        let id = ws.identity.lookup(&key.to_string()).unwrap();
+        render_note_html(
+            &ws.store,
+            &ws.identity,
+            ws.model.as_ref(),
+            &provider,
+            &id,
+            options,
+            &|p: &Path| format!("file://{}", p.display()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render_resolves_wikilinks() {
+        let html = render(
+            &[
+                ("a", "See [[Label|b#intro]] and [[missing]]"),
+                ("b", "# B\n\n## Intro"),
+            ],
+            "a",
+            &RenderOptions::default(),
+        );
+
+        assert!(html.contains(
+            "<a class=\"wikilink\" href=\"file:///test/b.md#intro\" data-note-key=\"b\">Label</a>"
+        ));
+        assert!(html.contains("<a class=\"wikilink broken\" data-note-key=\"missing\""));
+    }
+
+    #[test]
+    fn test_render_resolves_markdown_links() {
+        let html = render(
+            &[
+                (
+                    "a",
+                    "See [C](sub/c.md#intro), [site](https://example.com) and [top](#a)",
+                ),
+                ("sub/c", "# C\n\n## Intro"),
+            ],
+            "a",
+            &RenderOptions::default(),
+        );
+
+        assert!(html.contains("<a href=\"file:///test/sub/c.md#intro\">C</a>"));
+        assert!(html.contains("<a href=\"https://example.com\">site</a>"));
+        assert!(html.contains("<a href=\"#a\">top</a>"));
+    }
+
+    #[test]
+    fn test_render_expands_embeds_and_block_refs() {
+        let html = render(
+            &[
+                ("a", "Intro\n\n![[b#^quote]]"),
+                ("b", "Before\n\nQuoted **text** ^quote\n\nAfter"),
+            ],
+            "a",
+            &RenderOptions::default(),
+        );
+
+        assert!(html.contains("<div class=\"dendrite-embed\">"));
+        assert!(html.contains("<p>Quoted <strong>text</strong></p>"));
+        assert!(!html.contains("After"));
+        assert!(!html.contains("^quote"));
+    }
+
+    #[test]
+    fn test_render_resolves_links_of_embedded_notes() {
+        let html = render(
+            &[
+                ("a", "![[b]]"),
+                ("sub/b", "See [C](c.md), [[c#intro]] and [[gone]]"),
+                ("sub/c", "# C\n\n## Intro"),
+            ],
+            "a",
+            &RenderOptions::default(),
+        );
+
+        assert!(html.contains("<a href=\"file:///test/sub/c.md\">C</a>"));
+        assert!(html.contains(
+            "<a class=\"wikilink\" href=\"file:///test/sub/c.md#intro\" data-note-key=\"c\">c#intro</a>"
+        ));
+        assert!(html.contains("<a class=\"wikilink broken\" data-note-key=\"gone\""));
+    }
+
+    #[test]
+    fn test_render_frontmatter_modes() {
+        let notes = [("a", "---\nid: abc\ntitle: A <1>\n---\n# A")];
+
+        let stripped = render(&notes, "a", &RenderOptions::default());
+        assert!(!stripped.contains("abc"));
+        assert!(stripped.contains("<h1>A</h1>"));
+
+        let table = render(
+            &notes,
+            "a",
+            &RenderOptions {
+                frontmatter: FrontmatterMode::Table,
+                ..Default::default()
+            },
+        );
+        assert!(table.contains("<tr><th>title</th><td>A &lt;1&gt;</td></tr>"));
+    }
+}
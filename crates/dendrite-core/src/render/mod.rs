@@ -0,0 +1,5 @@
+pub mod html;
+pub mod transclusion;
+
+pub use html::{FrontmatterMode, RenderOptions};
+pub use transclusion::{TransclusionOptions, DEFAULT_MAX_EMBED_DEPTH};
@@ -0,0 +1,367 @@
+use crate::line_map::LineMap;
+use crate::model::{Link, LinkKind, Note, NoteId, Point};
+use crate::mutation::model::ContentProvider;
+use crate::slugify_heading;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+
+/// Default for how many levels of nested `![[embed]]`s are expanded, shared by
+/// hover previews and rendered HTML.
+pub const DEFAULT_MAX_EMBED_DEPTH: usize = 3;
+
+/// Limits applied when expanding note references into text.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TransclusionOptions {
+    /// How many levels of nested `![[embed]]`s are expanded
+    pub max_depth: usize,
+    /// Maximum length of the rendered text, in characters
+    pub max_length: usize,
+}
+
+impl Default for TransclusionOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_EMBED_DEPTH,
+            max_length: 3000,
+        }
+    }
+}
+
+/// Render the content referenced by `note_id` and `anchor`.
+///
+/// Anchors follow Dendron note-ref semantics:
+/// - `heading-slug`: the heading and its section, up to the next heading of the same or higher level
+/// - `^block-id`: only the referenced block
+/// - `^begin` / `^end`: the start and the end of the note
+/// - `start:#end`: from the start of one anchor to the end of another (e.g. `^begin:#usage`)
+///
+/// Nested embeds are expanded up to `max_depth` levels. Embeds that would form a
+/// cycle, or whose target cannot be read, are left as written.
+pub(crate) fn transclude(
+    store: &Store,
+    content_provider: &dyn ContentProvider,
+    note_id: &NoteId,
+    anchor: Option<&str>,
+    options: &TransclusionOptions,
+) -> Option<String> {
+    let transcluder = Transcluder {
+        store,
+        content_provider,
+        max_depth: options.max_depth,
+        wrap_embeds: false,
+        resolve_link: None,
+    };
+    let rendered = transcluder.expand(note_id, anchor, 0, &mut Vec::new())?;
+    Some(truncate(rendered.trim(), options.max_length))
+}
+
+/// Produces the replacement text for a link, or `None` to keep it as written.
+pub(crate) type LinkResolver<'a> = dyn Fn(&Link) -> Option<String> + 'a;
+
+/// Expands a note (or a slice of it) into markdown with nested embeds inlined.
+pub(crate) struct Transcluder<'a> {
+    pub store: &'a Store,
+    pub content_provider: &'a dyn ContentProvider,
+    pub max_depth: usize,
+    /// Wrap each expanded embed in a `<div class="dendrite-embed">` block (for HTML output)
+    pub wrap_embeds: bool,
+    /// Rewrites links inside embedded notes, which would otherwise be resolved
+    /// against the embedding note once inlined. `None` leaves a link as written.
+    pub resolve_link: Option<&'a LinkResolver<'a>>,
+}
+
+impl Transcluder<'_> {
+    pub(crate) fn expand(
+        &self,
+        note_id: &NoteId,
+        anchor: Option<&str>,
+        depth: usize,
+        stack: &mut Vec<(NoteId, Option<String>)>,
+    ) -> Option<String> {
+        let note = self.store.get_note(note_id)?;
+        let path = note.path.as_ref()?;
+        let content = self.content_provider.get_content(&path.to_string_lossy())?;
+        let line_map = LineMap::new(&content);
+        let (start, end) = anchor_span(&content, &line_map, note, anchor)?;
+
+        // Links inside embedded notes are resolved here, while their source is known
+        let resolve_link = self.resolve_link.filter(|_| depth > 0);
+
+        // Embeds (and links to resolve) fully contained in the slice, in document order
+        let mut links: Vec<(usize, usize, &Link)> = note
+            .links
+            .iter()
+            .filter(|link| match link.kind {
+                LinkKind::EmbeddedWikiLink(_) => true,
+                LinkKind::WikiLink(_) | LinkKind::MarkdownLink => resolve_link.is_some(),
+                _ => false,
+            })
+            .filter_map(|link| {
+                let link_start = line_map.point_to_offset(&content, link.range.start)?;
+                let link_end = line_map.point_to_offset(&content, link.range.end)?;
+                (start <= link_start && link_start < link_end && link_end <= end)
+                    .then_some((link_start, link_end, link))
+            })
+            .collect();
+        links.sort_by_key(|(link_start, _, _)| *link_start);
+
+        // Trailing ` ^block-id` markers of the blocks in the slice, which are
+        // noise in rendered output
+        let mut markers: Vec<(usize, usize)> = note
+            .blocks
+            .iter()
+            .filter_map(|block| {
+                let block_start = line_map.point_to_offset(&content, block.range.start)?;
+                let block_end = line_map.point_to_offset(&content, block.range.end)?;
+                let marker = format!(" ^{}", block.id);
+                let marker_start =
+                    block_start + content.get(block_start..block_end)?.rfind(&marker)?;
+                let marker_end = marker_start + marker.len();
+                (start <= marker_start && marker_end <= end).then_some((marker_start, marker_end))
+            })
+            .collect();
+        markers.sort_unstable();
+        markers.dedup();
+
+        stack.push((note_id.clone(), anchor.map(str::to_string)));
+
+        let mut out = String::new();
+        let mut cursor = start;
+        for (link_start, link_end, link) in links {
+            if link_start < cursor {
+                continue;
+            }
+            push_unmarked(&mut out, &content, cursor, link_start, &markers);
+            cursor = link_end;
+
+            let written = &content[link_start..link_end];
+            if !matches!(link.kind, LinkKind::EmbeddedWikiLink(_)) {
+                let resolved = resolve_link.and_then(|resolve| resolve(link));
+                out.push_str(resolved.as_deref().unwrap_or(written));
+                continue;
+            }
+
+            let reference = (link.target.clone(), link.anchor.clone());
+            let expanded = if depth < self.max_depth && !stack.contains(&reference) {
+                self.expand(&link.target, link.anchor.as_deref(), depth + 1, stack)
+            } else {
+                None
+            };
+
+            match expanded {
+                Some(text) if self.wrap_embeds => {
+                    out.push_str("\n\n<div class=\"dendrite-embed\">\n\n");
+                    out.push_str(text.trim());
+                    out.push_str("\n\n</div>\n\n");
+                }
+                Some(text) => out.push_str(text.trim()),
+                None => {
+                    let resolved = resolve_link.and_then(|resolve| resolve(link));
+                    out.push_str(resolved.as_deref().unwrap_or(written));
+                }
+            }
+        }
+        push_unmarked(&mut out, &content, cursor, end, &markers);
+
+        stack.pop();
+        Some(out)
+    }
+}
+
+/// Resolve an anchor (or a `start:#end` range) to byte offsets within `content`.
+fn anchor_span(
+    content: &str,
+    line_map: &LineMap,
+    note: &Note,
+    anchor: Option<&str>,
+) -> Option<(usize, usize)> {
+    let body_start = (note.content_offset as usize).min(content.len());
+
+    let (start, end) = match anchor {
+        None => (body_start, content.len()),
+        Some(anchor) => match anchor.split_once(":#") {
+            Some((from, to)) => (
+                element_span(content, line_map, note, body_start, from)?.0,
+                element_span(content, line_map, note, body_start, to)?.1,
+            ),
+            None => element_span(content, line_map, note, body_start, anchor)?,
+        },
+    };
+
+    (start <= end && content.is_char_boundary(start) && content.is_char_boundary(end))
+        .then_some((start, end))
+}
+
+fn element_span(
+    content: &str,
+    line_map: &LineMap,
+    note: &Note,
+    body_start: usize,
+    anchor: &str,
+) -> Option<(usize, usize)> {
+    let offset = |point: Point| line_map.point_to_offset(content, point);
+
+    match anchor {
+        "^begin" => {
+            let end = note
+                .headings
+                .first()
+                .and_then(|h| offset(h.range.start))
+                .unwrap_or(content.len());
+            Some((body_start, end.max(body_start)))
+        }
+        "^end" => {
+            let start = note
+                .headings
+                .last()
+                .and_then(|h| offset(h.range.start))
+                .unwrap_or(body_start);
+            Some((start, content.len()))
+        }
+        _ => {
+            if let Some(block_id) = anchor.strip_prefix('^') {
+                let block = note.blocks.iter().find(|b| b.id == block_id)?;
+                Some((offset(block.range.start)?, offset(block.range.end)?))
+            } else {
+                let index = note
+                    .headings
+                    .iter()
+                    .position(|h| slugify_heading(&h.text) == anchor)?;
+                let heading = &note.headings[index];
+                let end = note.headings[index + 1..]
+                    .iter()
+                    .find(|h| h.level <= heading.level)
+                    .and_then(|h| offset(h.range.start))
+                    .unwrap_or(content.len());
+                Some((offset(heading.range.start)?, end))
+            }
+        }
+    }
+}
+
+/// Append `content[from..to]`, leaving out the `markers` within it
+fn push_unmarked(
+    out: &mut String,
+    content: &str,
+    from: usize,
+    to: usize,
+    markers: &[(usize, usize)],
+) {
+    let mut cursor = from;
+    for &(marker_start, marker_end) in markers {
+        if cursor <= marker_start && marker_end <= to {
+            out.push_str(&content[cursor..marker_start]);
+            cursor = marker_end;
+        }
+    }
+    out.push_str(&content[cursor..to]);
+}
+
+fn truncate(text: &str, max_length: usize) -> String {
+    match text.char_indices().nth(max_length) {
+        Some((offset, _)) => format!("{}\n\n…", text[..offset].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{setup, MockProvider};
+    use crate::workspace::Workspace;
+
+    fn render(ws: &Workspace, provider: &MockProvider, key: &str, anchor: Option<&str>) -> String {
+        let id = ws.identity.lookup(&key.to_string()).unwrap();
+        transclude(
+            &ws.store,
+            provider,
+            &id,
+            anchor,
+            &TransclusionOptions::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_heading_section() {
+        let (ws, provider) = setup(&[(
+            "a",
+            "# A\n\n## One\n\nFirst\n\n### Sub\n\nNested\n\n## Two\n\nSecond",
+        )]);
+
+        let out = render(&ws, &provider, "a", Some("one"));
+        assert_eq!(out, "## One\n\nFirst\n\n### Sub\n\nNested");
+    }
+
+    #[test]
+    fn test_block_anchor() {
+        let (ws, provider) = setup(&[("a", "# A\n\nIntro\n\nImportant line ^key\n\nOutro")]);
+
+        let out = render(&ws, &provider, "a", Some("^key"));
+        assert_eq!(out, "Important line");
+    }
+
+    #[test]
+    fn test_only_block_markers_are_stripped() {
+        let content =
+            "Powers like 2 ^10\nare big.\n\n```\nlet x = a ^b\n```\n\nImportant line ^key\n";
+        let (ws, provider) = setup(&[("a", content)]);
+        let id = ws.identity.lookup(&"a".to_string()).unwrap();
+
+        let transcluder = Transcluder {
+            store: &ws.store,
+            content_provider: &provider,
+            max_depth: DEFAULT_MAX_EMBED_DEPTH,
+            wrap_embeds: false,
+            resolve_link: None,
+        };
+        let out = transcluder.expand(&id, None, 0, &mut Vec::new()).unwrap();
+        assert_eq!(
+            out,
+            "Powers like 2 ^10\nare big.\n\n```\nlet x = a ^b\n```\n\nImportant line\n"
+        );
+    }
+
+    #[test]
+    fn test_range_anchor_and_frontmatter() {
+        let (ws, provider) = setup(&[(
+            "a",
+            "---\ntitle: A\n---\nPreamble\n\n## Usage\n\nUse it\n\n## Notes\n\nMore",
+        )]);
+
+        let out = render(&ws, &provider, "a", Some("^begin:#usage"));
+        assert_eq!(out, "Preamble\n\n## Usage\n\nUse it");
+
+        let whole = render(&ws, &provider, "a", None);
+        assert!(whole.starts_with("Preamble"));
+        assert!(!whole.contains("title: A"));
+    }
+
+    #[test]
+    fn test_nested_embeds_and_cycles() {
+        let (ws, provider) = setup(&[("a", "Start\n\n![[b]]\n\nEnd"), ("b", "Inside B\n\n![[a]]")]);
+
+        let out = render(&ws, &provider, "a", None);
+        assert_eq!(out, "Start\n\nInside B\n\n![[a]]\n\nEnd");
+    }
+
+    #[test]
+    fn test_depth_and_length_limits() {
+        let (ws, provider) = setup(&[("a", "![[b]]"), ("b", "![[c]]"), ("c", "Deep")]);
+        let id = ws.identity.lookup(&"a".to_string()).unwrap();
+
+        let shallow = TransclusionOptions {
+            max_depth: 1,
+            ..Default::default()
+        };
+        let out = transclude(&ws.store, &provider, &id, None, &shallow).unwrap();
+        assert_eq!(out, "![[c]]");
+
+        let short = TransclusionOptions {
+            max_length: 2,
+            ..Default::default()
+        };
+        let out = transclude(&ws.store, &provider, &id, None, &short).unwrap();
+        assert_eq!(out, "De\n\n…");
+    }
+}
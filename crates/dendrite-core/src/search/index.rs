@@ -0,0 +1,222 @@
+use crate::model::NoteId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// A token of indexed text: the normalized term and its byte span in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub term: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Split text into lowercase terms.
+///
+/// Runs of alphanumeric characters form a term; CJK ideographs are indexed one
+/// character per term since they are not separated by whitespace.
+pub(crate) fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+
+    let flush = |start: &mut Option<usize>, end: usize, tokens: &mut Vec<Token>| {
+        if let Some(s) = start.take() {
+            tokens.push(Token {
+                term: text[s..end].to_lowercase(),
+                start: s,
+                end,
+            });
+        }
+    };
+
+    for (offset, c) in text.char_indices() {
+        if is_ideograph(c) {
+            flush(&mut start, offset, &mut tokens);
+            tokens.push(Token {
+                term: c.to_string(),
+                start: offset,
+                end: offset + c.len_utf8(),
+            });
+        } else if c.is_alphanumeric() || c == '_' {
+            start.get_or_insert(offset);
+        } else {
+            flush(&mut start, offset, &mut tokens);
+        }
+    }
+    flush(&mut start, text.len(), &mut tokens);
+
+    tokens
+}
+
+fn is_ideograph(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF   // Hiragana, Katakana
+        | 0x3400..=0x4DBF // CJK Extension A
+        | 0x4E00..=0x9FFF // CJK Unified Ideographs
+        | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+    )
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct IndexedDoc {
+    /// Number of tokens in the document
+    length: u32,
+    /// Distinct terms, for removal
+    terms: Vec<String>,
+}
+
+/// In-memory inverted index over note bodies.
+///
+/// Postings keep token positions so phrase queries can be answered without
+/// re-reading the notes. Terms are kept sorted for prefix expansion.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    postings: BTreeMap<String, HashMap<NoteId, Vec<u32>>>,
+    docs: HashMap<NoteId, IndexedDoc>,
+    total_length: u64,
+}
+
+impl SearchIndex {
+    pub(crate) fn contains(&self, id: &NoteId) -> bool {
+        self.docs.contains_key(id)
+    }
+
+    /// (Re-)index the body of a note.
+    pub(crate) fn index_note(&mut self, id: &NoteId, text: &str) {
+        self.remove_note(id);
+
+        let tokens = tokenize(text);
+        let mut positions: HashMap<String, Vec<u32>> = HashMap::new();
+        for (position, token) in tokens.iter().enumerate() {
+            positions
+                .entry(token.term.clone())
+                .or_default()
+                .push(position as u32);
+        }
+
+        let terms: Vec<String> = positions.keys().cloned().collect();
+        for (term, term_positions) in positions {
+            self.postings
+                .entry(term)
+                .or_default()
+                .insert(id.clone(), term_positions);
+        }
+
+        self.total_length += tokens.len() as u64;
+        self.docs.insert(
+            id.clone(),
+            IndexedDoc {
+                length: tokens.len() as u32,
+                terms,
+            },
+        );
+    }
+
+    pub(crate) fn remove_note(&mut self, id: &NoteId) {
+        let Some(doc) = self.docs.remove(id) else {
+            return;
+        };
+        self.total_length -= doc.length as u64;
+        for term in doc.terms {
+            if let Some(docs) = self.postings.get_mut(&term) {
+                docs.remove(id);
+                if docs.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn doc_count(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub(crate) fn doc_length(&self, id: &NoteId) -> u32 {
+        self.docs.get(id).map(|doc| doc.length).unwrap_or(0)
+    }
+
+    pub(crate) fn average_length(&self) -> f64 {
+        if self.docs.is_empty() {
+            0.0
+        } else {
+            self.total_length as f64 / self.docs.len() as f64
+        }
+    }
+
+    /// Distinct terms of an indexed document with their frequencies.
+    pub(crate) fn doc_terms<'a>(
+        &'a self,
+        id: &'a NoteId,
+    ) -> impl Iterator<Item = (&'a str, usize)> {
+        self.docs
+            .get(id)
+            .into_iter()
+            .flat_map(|doc| doc.terms.iter())
+            .filter_map(move |term| {
+                let frequency = self.postings.get(term)?.get(id)?.len();
+                Some((term.as_str(), frequency))
+            })
+    }
+
+    /// The token sequence of an indexed document, rebuilt from its postings.
+    pub(crate) fn doc_tokens(&self, id: &NoteId) -> Vec<&str> {
+        let mut tokens = vec![""; self.doc_length(id) as usize];
+        for term in self.docs.get(id).into_iter().flat_map(|doc| &doc.terms) {
+            let positions = self.postings.get(term).and_then(|docs| docs.get(id));
+            for &position in positions.into_iter().flatten() {
+                tokens[position as usize] = term;
+            }
+        }
+        tokens
+    }
+
+    pub(crate) fn postings(&self, term: &str) -> Option<&HashMap<NoteId, Vec<u32>>> {
+        self.postings.get(term)
+    }
+
+    /// All indexed terms starting with `prefix`, with their postings.
+    pub(crate) fn prefix_postings<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a String, &'a HashMap<NoteId, Vec<u32>>)> + 'a {
+        self.postings
+            .range(prefix.to_string()..)
+            .take_while(move |(term, _)| term.starts_with(prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_tokenize() {
+        let terms: Vec<_> = tokenize("Hello, World! foo_bar 中文 v2")
+            .into_iter()
+            .map(|t| t.term)
+            .collect();
+        assert_eq!(terms, vec!["hello", "world", "foo_bar", "中", "文", "v2"]);
+
+        let tokens = tokenize("a Büro");
+        assert_eq!((tokens[1].start, tokens[1].end), (2, 7));
+    }
+
+    #[test]
+    fn test_reindex_and_remove() {
+        let mut index = SearchIndex::default();
+        let id = NoteId(Uuid::new_v4());
+
+        index.index_note(&id, "alpha beta alpha");
+        assert_eq!(index.postings("alpha").unwrap()[&id], vec![0, 2]);
+        assert_eq!(index.average_length(), 3.0);
+        assert_eq!(index.doc_tokens(&id), vec!["alpha", "beta", "alpha"]);
+
+        index.index_note(&id, "gamma");
+        assert!(index.postings("alpha").is_none());
+        assert_eq!(index.doc_length(&id), 1);
+
+        index.remove_note(&id);
+        assert_eq!(index.doc_count(), 0);
+        assert!(index.postings("gamma").is_none());
+    }
+}
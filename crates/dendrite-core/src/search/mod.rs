@@ -0,0 +1,402 @@
+//! Full-text search over note bodies.
+
+mod index;
+mod query;
+
+pub use index::SearchIndex;
+
+use crate::identity::IdentityRegistry;
+use crate::line_map::LineMap;
+use crate::model::{Note, NoteId, NoteKey, TextRange};
+use crate::mutation::model::ContentProvider;
+use crate::store::Store;
+use index::tokenize;
+use query::{glob_match, Query, QueryTerm};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Characters of context kept before the first match in a snippet
+const SNIPPET_CONTEXT: usize = 60;
+/// Maximum snippet length in characters
+const SNIPPET_LENGTH: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchHit {
+    pub key: NoteKey,
+    pub path: Option<PathBuf>,
+    pub title: Option<String>,
+    pub vault_name: String,
+    pub score: f64,
+    /// Ranges of all matched terms in the note
+    pub matches: Vec<TextRange>,
+    pub snippet: Option<Snippet>,
+}
+
+/// A single-line excerpt around the first match.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Snippet {
+    pub text: String,
+    /// Matched ranges within `text`, as (start, end) UTF-16 offsets
+    pub highlights: Vec<(u32, u32)>,
+}
+
+/// Run a query against the index.
+///
+/// Query syntax: words (all must match), `"exact phrases"`, `prefix*`, and the
+/// filters `vault:name`, `key:glob` and `tag:glob`. A query made only of filters
+/// lists the matching notes by key.
+pub(crate) fn search(
+    store: &Store,
+    identity: &IdentityRegistry,
+    index: &SearchIndex,
+    content_provider: &dyn ContentProvider,
+    query: &str,
+    limit: usize,
+) -> Vec<SearchHit> {
+    let query = Query::parse(query);
+
+    let mut scored: Vec<(&Note, NoteKey, f64, Vec<u32>)> = Vec::new();
+
+    if query.terms.is_empty() {
+        if query.vault.is_none() && query.key.is_none() && query.tags.is_empty() {
+            return Vec::new();
+        }
+        for note in store.all_notes().filter(|note| note.path.is_some()) {
+            if let Some(key) = identity.key_of(&note.id) {
+                if matches_filters(&query, note, &key) {
+                    scored.push((note, key, 0.0, Vec::new()));
+                }
+            }
+        }
+    } else {
+        for (id, (score, positions)) in score_documents(index, &query.terms) {
+            let (Some(note), Some(key)) = (store.get_note(&id), identity.key_of(&id)) else {
+                continue;
+            };
+            if matches_filters(&query, note, &key) {
+                scored.push((note, key, score, positions));
+            }
+        }
+    }
+
+    scored.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.1.cmp(&b.1)));
+    scored.truncate(limit);
+
+    scored
+        .into_iter()
+        .map(|(note, key, score, positions)| {
+            let (matches, snippet) = locate_matches(note, content_provider, positions);
+            SearchHit {
+                key,
+                path: note.path.clone(),
+                title: note.title.clone(),
+                vault_name: note.vault_name.clone(),
+                score,
+                matches,
+                snippet,
+            }
+        })
+        .collect()
+}
+
+/// BM25 score and matched token positions of every document matching all terms.
+fn score_documents(index: &SearchIndex, terms: &[QueryTerm]) -> HashMap<NoteId, (f64, Vec<u32>)> {
+    let doc_count = index.doc_count() as f64;
+    let average_length = index.average_length().max(1.0);
+
+    let mut results: Option<HashMap<NoteId, (f64, Vec<u32>)>> = None;
+
+    for term in terms {
+        // Matched positions per document for this term
+        let matched = match_term(index, term);
+        let document_frequency = matched.len() as f64;
+        let idf = (1.0 + (doc_count - document_frequency + 0.5) / (document_frequency + 0.5)).ln();
+
+        let mut next = HashMap::new();
+        for (id, (term_frequency, positions)) in matched {
+            let previous = match &results {
+                Some(results) => match results.get(&id) {
+                    Some(previous) => previous.clone(),
+                    None => continue,
+                },
+                None => (0.0, Vec::new()),
+            };
+
+            let tf = term_frequency as f64;
+            let length = index.doc_length(&id) as f64;
+            let score = idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / average_length));
+
+            let mut all_positions = previous.1;
+            all_positions.extend(positions);
+            next.insert(id, (previous.0 + score, all_positions));
+        }
+        results = Some(next);
+    }
+
+    results.unwrap_or_default()
+}
+
+/// Documents matching a single term: (term frequency, matched token positions).
+fn match_term(index: &SearchIndex, term: &QueryTerm) -> HashMap<NoteId, (usize, Vec<u32>)> {
+    let mut matched: HashMap<NoteId, (usize, Vec<u32>)> = HashMap::new();
+
+    match term {
+        QueryTerm::Word(word) => {
+            for (id, positions) in index.postings(word).into_iter().flatten() {
+                matched.insert(id.clone(), (positions.len(), positions.clone()));
+            }
+        }
+        QueryTerm::Prefix(prefix) => {
+            for (_, postings) in index.prefix_postings(prefix) {
+                for (id, positions) in postings {
+                    let entry = matched.entry(id.clone()).or_default();
+                    entry.0 += positions.len();
+                    entry.1.extend(positions);
+                }
+            }
+        }
+        QueryTerm::Phrase(words) => {
+            let Some(lists) = words
+                .iter()
+                .map(|word| index.postings(word))
+                .collect::<Option<Vec<_>>>()
+            else {
+                return matched;
+            };
+
+            for (id, first_positions) in lists[0] {
+                let rest: Option<Vec<HashSet<u32>>> = lists[1..]
+                    .iter()
+                    .map(|postings| postings.get(id).map(|p| p.iter().copied().collect()))
+                    .collect();
+                let Some(rest) = rest else {
+                    continue;
+                };
+
+                let starts: Vec<u32> = first_positions
+                    .iter()
+                    .copied()
+                    .filter(|start| {
+                        rest.iter()
+                            .enumerate()
+                            .all(|(i, positions)| positions.contains(&(start + i as u32 + 1)))
+                    })
+                    .collect();
+
+                if !starts.is_empty() {
+                    let positions = starts
+                        .iter()
+                        .flat_map(|start| *start..*start + words.len() as u32)
+                        .collect();
+                    matched.insert(id.clone(), (starts.len(), positions));
+                }
+            }
+        }
+    }
+
+    matched
+}
+
+fn matches_filters(query: &Query, note: &Note, key: &NoteKey) -> bool {
+    if let Some(vault) = &query.vault {
+        if !note.vault_name.eq_ignore_ascii_case(vault) {
+            return false;
+        }
+    }
+    if let Some(pattern) = &query.key {
+        if !glob_match(pattern, key) {
+            return false;
+        }
+    }
+    if !query.tags.is_empty() {
+        let tags = note.tags();
+        if !query
+            .tags
+            .iter()
+            .all(|pattern| tags.iter().any(|tag| glob_match(pattern, tag)))
+        {
+            return false;
+        }
+    }
+    true
+}
+
+/// Map matched token positions back to text ranges, and cut a snippet around the first one.
+fn locate_matches(
+    note: &Note,
+    content_provider: &dyn ContentProvider,
+    mut positions: Vec<u32>,
+) -> (Vec<TextRange>, Option<Snippet>) {
+    let Some(content) = note
+        .path
+        .as_ref()
+        .and_then(|path| content_provider.get_content(&path.to_string_lossy()))
+    else {
+        return (Vec::new(), None);
+    };
+
+    let body_start = (note.content_offset as usize).min(content.len());
+    let Some(body) = content.get(body_start..) else {
+        return (Vec::new(), None);
+    };
+    let tokens = tokenize(body);
+
+    positions.sort_unstable();
+    positions.dedup();
+    let spans: Vec<(usize, usize)> = positions
+        .iter()
+        .filter_map(|position| tokens.get(*position as usize))
+        .map(|token| (body_start + token.start, body_start + token.end))
+        .collect();
+
+    let line_map = LineMap::new(&content);
+    let matches = spans
+        .iter()
+        .map(|(start, end)| TextRange {
+            start: line_map.offset_to_point(&content, *start),
+            end: line_map.offset_to_point(&content, *end),
+        })
+        .collect();
+
+    let snippet = spans
+        .first()
+        .map(|(first, _)| make_snippet(&content, body_start, *first, &spans));
+
+    (matches, snippet)
+}
+
+fn make_snippet(
+    content: &str,
+    body_start: usize,
+    first: usize,
+    spans: &[(usize, usize)],
+) -> Snippet {
+    // Walk back SNIPPET_CONTEXT characters, then forward to the snippet length
+    let start = content[body_start..first]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT - 1)
+        .map(|(i, _)| body_start + i)
+        .unwrap_or(body_start);
+    // Don't open on the blank lines following frontmatter
+    let start = first - content[start..first].trim_start().len();
+    let end = content[start..]
+        .char_indices()
+        .nth(SNIPPET_LENGTH)
+        .map(|(i, _)| start + i)
+        .unwrap_or(content.len());
+
+    let mut text = String::new();
+    if !content[body_start..start].trim().is_empty() {
+        text.push('…');
+    }
+    let prefix_len = text.encode_utf16().count() as u32;
+    let excerpt = &content[start..end];
+    text.push_str(&excerpt.replace(['\n', '\r', '\t'], " "));
+    if end < content.len() {
+        text.push('…');
+    }
+
+    let utf16 = |offset: usize| prefix_len + content[start..offset].encode_utf16().count() as u32;
+    let highlights = spans
+        .iter()
+        .filter(|(s, e)| *s >= start && *e <= end)
+        .map(|(s, e)| (utf16(*s), utf16(*e)))
+        .collect();
+
+    Snippet { text, highlights }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Point;
+    use crate::test_fixtures::{setup, MockProvider};
+    use crate::vfs::PhysicalFileSystem;
+    use crate::workspace::{Indexer, Workspace};
+    use std::path::PathBuf;
+
+    fn keys(ws: &Workspace, provider: &MockProvider, query: &str) -> Vec<String> {
+        ws.search(provider, query, 10)
+            .into_iter()
+            .map(|hit| hit.key)
+            .collect()
+    }
+
+    #[test]
+    fn test_bm25_ranking() {
+        let (ws, provider) = setup(&[
+            ("once", "rust is a language with many other words around it"),
+            ("twice", "rust rust"),
+            ("none", "python"),
+        ]);
+
+        assert_eq!(keys(&ws, &provider, "rust"), vec!["twice", "once"]);
+        assert!(keys(&ws, &provider, "rust python").is_empty());
+    }
+
+    #[test]
+    fn test_phrase_and_prefix() {
+        let (ws, provider) = setup(&[
+            ("a", "error handling in rust"),
+            ("b", "handling of an error"),
+            ("c", "errors everywhere"),
+        ]);
+
+        assert_eq!(keys(&ws, &provider, "\"error handling\""), vec!["a"]);
+        let mut prefixed = keys(&ws, &provider, "err*");
+        prefixed.sort();
+        assert_eq!(prefixed, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_filters() {
+        let (ws, provider) = setup(&[
+            ("proj.alpha", "---\ntags: [todo, work]\n---\nmeeting notes"),
+            ("proj.beta", "---\ntags: idea\n---\nmeeting notes"),
+            ("daily", "meeting notes"),
+        ]);
+
+        assert_eq!(keys(&ws, &provider, "meeting key:proj.*").len(), 2);
+        assert_eq!(keys(&ws, &provider, "meeting tag:todo"), vec!["proj.alpha"]);
+        assert_eq!(keys(&ws, &provider, "tag:idea"), vec!["proj.beta"]);
+        assert!(keys(&ws, &provider, "meeting vault:other").is_empty());
+    }
+
+    #[test]
+    fn test_snippet_and_ranges() {
+        let (ws, provider) = setup(&[("a", "---\ntitle: A\n---\nFirst line\nThe needle is here")]);
+
+        let hits = ws.search(&provider, "needle", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].matches,
+            vec![crate::model::TextRange {
+                start: Point { line: 4, col: 4 },
+                end: Point { line: 4, col: 10 },
+            }]
+        );
+
+        let snippet = hits[0].snippet.as_ref().unwrap();
+        assert_eq!(snippet.text, "First line The needle is here");
+        assert_eq!(snippet.highlights, vec![(15, 21)]);
+    }
+
+    #[test]
+    fn test_index_follows_updates_and_deletes() {
+        let (mut ws, provider) = setup(&[("a", "old words")]);
+        let path = PathBuf::from("/test/a.md");
+        let fs = PhysicalFileSystem;
+
+        Indexer::new(&mut ws, &fs).update_content(path.clone(), "new words", "main".to_string());
+        assert!(keys(&ws, &provider, "old").is_empty());
+        assert_eq!(keys(&ws, &provider, "new"), vec!["a"]);
+
+        Indexer::new(&mut ws, &fs).delete_file(&path);
+        assert!(keys(&ws, &provider, "words").is_empty());
+    }
+}
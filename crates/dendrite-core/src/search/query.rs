@@ -0,0 +1,152 @@
+use super::index::tokenize;
+
+/// A text criterion of a search query. Every criterion must match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum QueryTerm {
+    Word(String),
+    /// `foo*`
+    Prefix(String),
+    /// `"foo bar"`, or a single word that tokenizes into several terms
+    Phrase(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Query {
+    pub terms: Vec<QueryTerm>,
+    /// `vault:name`
+    pub vault: Option<String>,
+    /// `key:foo.*` (glob)
+    pub key: Option<String>,
+    /// `tag:name` (glob), every tag filter must match
+    pub tags: Vec<String>,
+}
+
+impl Query {
+    pub(crate) fn parse(input: &str) -> Self {
+        let mut query = Query::default();
+
+        for (raw, quoted) in split_words(input) {
+            if !quoted {
+                if let Some((field, value)) = raw.split_once(':') {
+                    let value = value.trim_matches('"').to_string();
+                    match field {
+                        "vault" => {
+                            query.vault = Some(value);
+                            continue;
+                        }
+                        "key" => {
+                            query.key = Some(value);
+                            continue;
+                        }
+                        "tag" => {
+                            query.tags.push(value);
+                            continue;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            let is_prefix = !quoted && raw.ends_with('*');
+            let terms: Vec<String> = tokenize(raw.trim_end_matches('*'))
+                .into_iter()
+                .map(|t| t.term)
+                .collect();
+
+            match terms.len() {
+                0 => {}
+                1 if is_prefix => query.terms.push(QueryTerm::Prefix(terms[0].clone())),
+                1 => query.terms.push(QueryTerm::Word(terms[0].clone())),
+                _ => query.terms.push(QueryTerm::Phrase(terms)),
+            }
+        }
+
+        query
+    }
+}
+
+/// Split on whitespace, keeping `"quoted phrases"` (and `field:"quoted values"`) together.
+fn split_words(input: &str) -> Vec<(&str, bool)> {
+    let mut words = Vec::new();
+    let mut rest = input.trim_start();
+
+    while !rest.is_empty() {
+        if let Some(stripped) = rest.strip_prefix('"') {
+            let end = stripped.find('"').unwrap_or(stripped.len());
+            words.push((&stripped[..end], true));
+            rest = stripped.get(end + 1..).unwrap_or("");
+        } else {
+            let mut end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            // `field:"quoted value"`
+            if let Some(quote) = rest[..end].find(":\"") {
+                let value_start = quote + 2;
+                end = rest[value_start..]
+                    .find('"')
+                    .map(|i| value_start + i + 1)
+                    .unwrap_or(rest.len());
+            }
+            words.push((&rest[..end], false));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+
+    words
+}
+
+/// Case-insensitive glob match supporting `*` and `?`.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let query = Query::parse(r#"rust "error handling" trai* vault:work key:"proj.*" tag:todo"#);
+
+        assert_eq!(
+            query.terms,
+            vec![
+                QueryTerm::Word("rust".to_string()),
+                QueryTerm::Phrase(vec!["error".to_string(), "handling".to_string()]),
+                QueryTerm::Prefix("trai".to_string()),
+            ]
+        );
+        assert_eq!(query.vault.as_deref(), Some("work"));
+        assert_eq!(query.key.as_deref(), Some("proj.*"));
+        assert_eq!(query.tags, vec!["todo"]);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("foo.*", "foo.bar.baz"));
+        assert!(!glob_match("foo.*", "foo"));
+        assert!(glob_match("*.daily.?", "journal.daily.1"));
+        assert!(glob_match("Foo", "foo"));
+    }
+}
@@ -39,8 +39,21 @@ impl SemanticModel for DendronModel {
     }
 
     fn note_key_from_link(&self, source: &NoteKey, raw: &str) -> NoteKey {
+        // Cross-vault links: [[dendron://vault/key]]; the vault is picked up by
+        // `vault_from_link` to choose among notes sharing the key
+        if let Some((_vault, key)) = raw
+            .strip_prefix("dendron://")
+            .and_then(|rest| rest.split_once('/'))
+        {
+            return key.to_string();
+        }
+
         let link_path = Path::new(raw);
         if link_path.is_absolute() || raw.contains('/') || raw.contains('\\') {
+            // Paths to note files (e.g. `[B](../x/b.md)`): the key is the file name
+            if link_path.extension().is_some_and(|ext| ext == "md") {
+                return self.note_key_from_path(link_path, "");
+            }
             normalize_path_to_id(link_path)
         } else {
             let source_path = Path::new(source);
@@ -53,6 +66,11 @@ impl SemanticModel for DendronModel {
         }
     }
 
+    fn vault_from_link(&self, raw: &str) -> Option<String> {
+        let (vault, _key) = raw.strip_prefix("dendron://")?.split_once('/')?;
+        Some(vault.to_string())
+    }
+
     fn resolve_display_name(&self, note: &crate::model::Note) -> String {
         note.title.clone().unwrap_or_default()
     }
@@ -73,6 +91,10 @@ impl SemanticModel for DendronModel {
         Some(parts[..parts.len() - 1].join("."))
     }
 
+    fn root_note_key(&self) -> Option<NoteKey> {
+        Some("root".to_string())
+    }
+
     fn is_descendant(&self, candidate: &NoteKey, parent: &NoteKey) -> bool {
         // Special case: everyone is a descendant of "root" (except root itself)
         if parent == "root" {
@@ -145,14 +167,25 @@ impl SemanticModel for DendronModel {
         ]
     }
 
+    fn frontmatter_field_order(&self) -> Vec<&'static str> {
+        // Same order as generated by `generate_new_note_content`
+        vec!["id", "title", "desc", "updated", "created"]
+    }
+
     fn supported_extensions(&self) -> &[&str] {
         &["md"]
     }
 
     fn generate_new_note_content(&self, key: &NoteKey) -> String {
+        let title = key.split('.').next_back().unwrap_or(key);
+        self.generate_titled_note_content(key, title)
+    }
+
+    fn generate_titled_note_content(&self, _key: &NoteKey, title: &str) -> String {
         let now = crate::utils::time::now();
         let id = crate::utils::id::generate_id();
-        let title = key.split('.').last().unwrap_or(key);
+        // Quoted where YAML needs it, e.g. for `Setup: Linux`
+        let yaml_title = serde_yaml::to_string(title).unwrap_or_default();
 
         format!(
             r#"---
@@ -164,7 +197,11 @@ created: {}
 ---
 
 # {}"#,
-            id, title, now, now, title
+            id,
+            yaml_title.trim_end(),
+            now,
+            now,
+            title
         )
     }
 }
@@ -17,12 +17,21 @@ pub trait SemanticModel: Send + Sync {
 
     fn note_key_from_path(&self, path: &Path, content: &str) -> NoteKey;
     fn note_key_from_link(&self, source: &NoteKey, raw: &str) -> NoteKey;
+    /// Vault a link names explicitly, if the model supports vault-qualified links
+    fn vault_from_link(&self, _raw: &str) -> Option<String> {
+        None
+    }
     fn path_from_note_key(&self, key: &NoteKey) -> PathBuf;
 
     // --- Hierarchy ---
 
     fn resolve_parent(&self, note: &NoteKey) -> Option<NoteKey>;
 
+    /// Key of the note every hierarchy starts from, if the model has one
+    fn root_note_key(&self) -> Option<NoteKey> {
+        None
+    }
+
     /// Check if `candidate` is a descendant of `parent` in the hierarchy
     fn is_descendant(&self, candidate: &NoteKey, parent: &NoteKey) -> bool;
 
@@ -53,6 +62,12 @@ pub trait SemanticModel: Send + Sync {
         self.supported_link_kinds()
     }
 
+    /// Canonical order of frontmatter fields, used by the formatter.
+    /// Fields not listed keep their relative order after the listed ones.
+    fn frontmatter_field_order(&self) -> Vec<&'static str> {
+        vec![]
+    }
+
     // --- Extension Points ---
 
     /// Supported file extensions (e.g., &["md", "org"])
@@ -69,4 +84,10 @@ pub trait SemanticModel: Send + Sync {
     fn generate_new_note_content(&self, _key: &NoteKey) -> String {
         "# New Note".to_string()
     }
+
+    /// Generate initial content for a new note with a given title (e.g. the
+    /// heading of a section split out of another note)
+    fn generate_titled_note_content(&self, _key: &NoteKey, title: &str) -> String {
+        format!("# {}", title)
+    }
 }
@@ -1,14 +1,22 @@
-use crate::model::{Note, NoteId};
+use crate::model::{Link, Note, NoteId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// A link, addressed by its source note and its position in the source's `links`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LinkRef {
+    pub source: NoteId,
+    pub index: usize,
+}
+
 // In memory
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Store {
     pub(crate) notes: HashMap<NoteId, Note>,
     pub(crate) path_map: HashMap<PathBuf, NoteId>,
-    pub(crate) backlinks: HashMap<NoteId, Vec<NoteId>>,
+    /// Link-level reverse edges: target -> every link pointing at it
+    pub(crate) backlinks: HashMap<NoteId, Vec<LinkRef>>,
 }
 
 impl Store {
@@ -29,6 +37,13 @@ impl Store {
                     self.path_map.remove(old_path);
                 }
             }
+            // The old links go away with the old version of the note
+            let old_targets: Vec<NoteId> = old_note
+                .links
+                .iter()
+                .map(|link| link.target.clone())
+                .collect();
+            self.unlink_source(&id, &old_targets);
         }
 
         if let Some(path) = &note.path {
@@ -44,7 +59,7 @@ impl Store {
             }
             self.backlinks.remove(id);
             for backlinks in self.backlinks.values_mut() {
-                backlinks.retain(|backlink_id| backlink_id != id);
+                backlinks.retain(|link_ref| link_ref.source != *id);
             }
         }
     }
@@ -69,17 +84,16 @@ impl Store {
             .map(|note| note.links.iter().map(|link| link.target.clone()).collect())
             .unwrap_or_default();
 
-        for old_target in &old_targets {
-            if let Some(backlinks) = self.backlinks.get_mut(old_target) {
-                backlinks.retain(|backlink_id| backlink_id != source);
-            }
-        }
+        self.unlink_source(source, &old_targets);
 
-        for target in &targets {
-            let backlinks = self.backlinks.entry(target.clone()).or_default();
-            if !backlinks.contains(source) {
-                backlinks.push(source.clone());
-            }
+        for (index, target) in targets.iter().enumerate() {
+            self.backlinks
+                .entry(target.clone())
+                .or_default()
+                .push(LinkRef {
+                    source: source.clone(),
+                    index,
+                });
         }
 
         if let Some(note) = self.notes.get_mut(source) {
@@ -93,6 +107,8 @@ impl Store {
                     .into_iter()
                     .map(|target| Link {
                         target,
+                        target_key: String::new(),
+                        target_vault: None,
                         raw_target: "".to_string(), // Store update is often semantic, raw target unknown
                         alias: None,
                         anchor: None,
@@ -107,9 +123,79 @@ impl Store {
         }
     }
 
-    /// Get backlinks (incoming edges)
+    /// Point every link at `from` to `to` instead
+    pub(crate) fn move_backlinks(&mut self, from: &NoteId, to: &NoteId) {
+        let Some(link_refs) = self.backlinks.remove(from) else {
+            return;
+        };
+        for link_ref in &link_refs {
+            if let Some(link) = self
+                .notes
+                .get_mut(&link_ref.source)
+                .and_then(|note| note.links.get_mut(link_ref.index))
+            {
+                link.target = to.clone();
+            }
+        }
+        self.backlinks
+            .entry(to.clone())
+            .or_default()
+            .extend(link_refs);
+    }
+
+    /// Point the `index`-th link of `source` at a different note
+    pub(crate) fn retarget_link(&mut self, source: &NoteId, index: usize, target: NoteId) {
+        let Some(link) = self
+            .notes
+            .get_mut(source)
+            .and_then(|note| note.links.get_mut(index))
+        else {
+            return;
+        };
+        let old_target = std::mem::replace(&mut link.target, target.clone());
+        if let Some(backlinks) = self.backlinks.get_mut(&old_target) {
+            backlinks.retain(|link_ref| !(link_ref.source == *source && link_ref.index == index));
+        }
+        self.backlinks.entry(target).or_default().push(LinkRef {
+            source: source.clone(),
+            index,
+        });
+    }
+
+    /// Drop the reverse edges of `source`'s links to `targets`
+    fn unlink_source(&mut self, source: &NoteId, targets: &[NoteId]) {
+        for target in targets {
+            if let Some(backlinks) = self.backlinks.get_mut(target) {
+                backlinks.retain(|link_ref| link_ref.source != *source);
+            }
+        }
+    }
+
+    /// Get backlinks (incoming edges), one entry per linking note
     pub(crate) fn backlinks_of(&self, id: &NoteId) -> Vec<NoteId> {
-        self.backlinks.get(id).cloned().unwrap_or_default()
+        let mut sources: Vec<NoteId> = Vec::new();
+        for link_ref in self.backlinks.get(id).into_iter().flatten() {
+            if !sources.contains(&link_ref.source) {
+                sources.push(link_ref.source.clone());
+            }
+        }
+        sources
+    }
+
+    /// Every link pointing at `id`, with the note it sits in
+    pub(crate) fn backlink_refs<'a>(
+        &'a self,
+        id: &'a NoteId,
+    ) -> impl Iterator<Item = (&'a Note, &'a Link)> + 'a {
+        self.backlinks
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(move |link_ref| {
+                let source = self.notes.get(&link_ref.source)?;
+                let link = source.links.get(link_ref.index)?;
+                (link.target == *id).then_some((source, link))
+            })
     }
 
     pub(crate) fn all_notes(&self) -> impl Iterator<Item = &Note> {
@@ -0,0 +1,74 @@
+//! Fixtures shared by unit tests: an in-memory content provider and Dendron
+//! workspaces indexed from it.
+
+use crate::config::DendriteConfig;
+use crate::mutation::model::ContentProvider;
+use crate::semantic::DendronModel;
+use crate::vfs::PhysicalFileSystem;
+use crate::workspace::{Indexer, Workspace};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Root of fixture workspaces; nothing is read from it
+pub(crate) const TEST_ROOT: &str = "/test";
+
+/// `path` under `TEST_ROOT`
+pub(crate) fn test_path(path: &str) -> PathBuf {
+    PathBuf::from(TEST_ROOT).join(path)
+}
+
+/// Serves file contents from memory, by path.
+#[derive(Default)]
+pub(crate) struct MockProvider {
+    pub(crate) files: HashMap<String, String>,
+}
+
+impl ContentProvider for MockProvider {
+    fn get_content(&self, uri: &str) -> Option<String> {
+        self.files.get(uri).cloned()
+    }
+}
+
+impl MockProvider {
+    /// Index `content` as the file at `path` in `vault`, and serve it.
+    pub(crate) fn index(&mut self, ws: &mut Workspace, path: PathBuf, content: &str, vault: &str) {
+        Indexer::new(ws, &PhysicalFileSystem).update_content(
+            path.clone(),
+            content,
+            vault.to_string(),
+        );
+        self.files
+            .insert(path.to_string_lossy().to_string(), content.to_string());
+    }
+}
+
+/// An empty Dendron workspace at `TEST_ROOT`.
+pub(crate) fn dendron_workspace(config: DendriteConfig) -> Workspace {
+    Workspace::new(
+        config,
+        Box::new(DendronModel::new(PathBuf::from(TEST_ROOT))),
+    )
+}
+
+/// A workspace with the `(key, content)` notes indexed in order, in vault "main".
+pub(crate) fn setup(notes: &[(&str, &str)]) -> (Workspace, MockProvider) {
+    let files: Vec<(String, &str)> = notes
+        .iter()
+        .map(|(key, content)| (format!("{}.md", key), *content))
+        .collect();
+    let files: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), *content))
+        .collect();
+    setup_files(&files)
+}
+
+/// Like `setup`, with files given by their path relative to `TEST_ROOT`.
+pub(crate) fn setup_files(files: &[(&str, &str)]) -> (Workspace, MockProvider) {
+    let mut ws = dendron_workspace(DendriteConfig::default());
+    let mut provider = MockProvider::default();
+    for (path, content) in files {
+        provider.index(&mut ws, test_path(path), content, "main");
+    }
+    (ws, provider)
+}
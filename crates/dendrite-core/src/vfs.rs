@@ -12,6 +12,15 @@ pub trait FileSystem: Send + Sync {
     /// Write exactly these bytes to the file.
     fn write_all(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()>;
 
+    /// Move a file, replacing `to` if it exists.
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()>;
+
+    /// Remove a file.
+    fn remove_file(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Remove an empty directory.
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()>;
+
     /// List all files with the given extension under the root directory.
     /// This should be a recursive search.
     fn list_files(&self, root: &Path, extension: &str) -> Vec<PathBuf>;
@@ -44,6 +53,21 @@ impl FileSystem for PhysicalFileSystem {
         std::fs::write(path, bytes)
     }
 
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::remove_dir(path)
+    }
+
     fn list_files(&self, root: &Path, extension: &str) -> Vec<PathBuf> {
         let mut files = Vec::new();
 
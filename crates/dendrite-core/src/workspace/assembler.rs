@@ -56,6 +56,8 @@ impl<'a> NoteAssembler<'a> {
 
                     Link {
                         target: self.identity.get_or_create(&link_key),
+                        target_key: link_key,
+                        target_vault: self.model.vault_from_link(&link.target),
                         raw_target: link.raw_target.clone(),
                         alias: link.alias.clone(),
                         anchor: link.anchor.clone(),
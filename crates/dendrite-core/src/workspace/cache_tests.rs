@@ -178,3 +178,56 @@ fn test_cache_tier2_digest_match() {
     let meta = vault2.workspace.cache_metadata.get(&note_path).unwrap();
     assert_eq!(meta.mtime, mtime_new);
 }
+
+#[test]
+fn test_cache_restores_search_index() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    let fs = Arc::new(PhysicalFileSystem);
+
+    let note_path = root.join("note1.md");
+    fs::write(&note_path, "---\ntitle: Note 1\n---\nsearchable words").unwrap();
+
+    let make_engine = || {
+        let config = crate::config::DendriteConfig {
+            workspace: crate::config::WorkspaceConfig {
+                vaults: vec![crate::config::VaultConfig {
+                    name: "main".to_string(),
+                    path: root.clone(),
+                }],
+                ..crate::config::DendriteConfig::default().workspace
+            },
+            ..crate::config::DendriteConfig::default()
+        };
+        let model = Box::new(DendronModel::new(root.clone()));
+        DendriteEngine::new(Workspace::new(config, model), fs.clone())
+    };
+
+    let mut vault = make_engine();
+    vault.initialize(root.clone());
+    let cache_path = root.join("cache.bin");
+    vault.save_cache(&cache_path).unwrap();
+
+    // Searchable straight after loading, before any file is checked
+    let mut vault2 = make_engine();
+    vault2.load_cache(&cache_path).unwrap();
+    let hits = vault2.workspace.search(&vault2, "searchable", 10);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].key, "note1");
+
+    // An index from another format version is dropped, then rebuilt on tier hits
+    let mut state = crate::cache::PersistentState::load(&cache_path, &*fs).unwrap();
+    state.search.version += 1;
+    state.save(&cache_path, &*fs).unwrap();
+
+    let mut vault3 = make_engine();
+    vault3.load_cache(&cache_path).unwrap();
+    assert!(vault3
+        .workspace
+        .search(&vault3, "searchable", 10)
+        .is_empty());
+
+    let (_, stats) = vault3.initialize(root.clone());
+    assert_eq!(stats.full_parses, 0);
+    assert_eq!(vault3.workspace.search(&vault3, "searchable", 10).len(), 1);
+}
@@ -38,18 +38,22 @@ impl DendriteEngine {
         self.workspace.store = state.store;
         self.workspace.identity = state.identity;
         self.workspace.cache_metadata = state.metadata;
+        // Notes missing from the index are re-indexed as files are checked against the metadata
+        self.workspace.search_index = state.search.restore().unwrap_or_default();
         self.workspace.invalidate_tree();
+        self.workspace.invalidate_mentions();
         Ok(())
     }
 
     pub fn save_cache(&self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
-        use crate::cache::PersistentState;
+        use crate::cache::{PersistedSearchIndex, PersistentState};
         let state = PersistentState {
             version: PersistentState::CURRENT_VERSION,
             model_id: self.workspace.model.id().0.to_string(),
             store: self.workspace.store.clone(),
             identity: self.workspace.identity.clone(),
             metadata: self.workspace.cache_metadata.clone(),
+            search: PersistedSearchIndex::new(&self.workspace.search_index)?,
         };
         state.save(path, &*self.fs)
     }
@@ -87,6 +91,20 @@ impl DendriteEngine {
             .rename_file(old_path, new_path, content, vault_name, &*self.fs);
     }
 
+    /// Apply one batch of file watcher events, keeping the identity of notes
+    /// renamed outside the editor.
+    ///
+    /// Returns the detected renames; `Workspace::external_rename_edits` turns
+    /// them into edits for links still written against the old keys.
+    pub fn sync_file_changes(
+        &mut self,
+        deleted: &[PathBuf],
+        upserted: &[(PathBuf, String)],
+    ) -> Vec<(PathBuf, PathBuf)> {
+        self.workspace
+            .sync_file_changes(deleted, upserted, &*self.fs)
+    }
+
     // ------------------------------------------------------------------------
     // Mutation (Changes GOING TO disk)
     // ------------------------------------------------------------------------
@@ -115,6 +133,43 @@ impl DendriteEngine {
         self.workspace.rename_hierarchy(self, old_key, new_key)
     }
 
+    pub fn refactor_hierarchy(
+        &self,
+        pattern: &str,
+        replacement: &str,
+        scope: Option<&str>,
+    ) -> Result<
+        crate::mutation::hierarchy::HierarchyRefactorReport,
+        Vec<crate::mutation::model::Diagnostic>,
+    > {
+        self.workspace
+            .refactor_hierarchy(self, pattern, replacement, scope)
+    }
+
+    pub fn move_to_vault(
+        &self,
+        note_key: &str,
+        include_descendants: bool,
+        vault_name: &str,
+    ) -> Option<crate::mutation::model::EditPlan> {
+        self.workspace
+            .move_to_vault(self, note_key, include_descendants, vault_name)
+    }
+
+    pub fn will_rename_files(
+        &self,
+        renames: &[(std::path::PathBuf, std::path::PathBuf)],
+    ) -> Option<crate::mutation::model::EditPlan> {
+        self.workspace.file_rename_edits(self, renames)
+    }
+
+    pub fn will_delete_files(
+        &self,
+        paths: &[std::path::PathBuf],
+    ) -> Option<crate::mutation::model::EditPlan> {
+        self.workspace.file_delete_edits(self, paths)
+    }
+
     pub fn split_note(
         &self,
         source_path: &std::path::Path,
@@ -125,6 +180,41 @@ impl DendriteEngine {
             .split_note(self, source_path, selection, new_note_title)
     }
 
+    pub fn split_note_by_headings(
+        &self,
+        note_key: &str,
+        options: &crate::mutation::split::SplitByHeadingOptions,
+    ) -> Option<crate::mutation::model::EditPlan> {
+        self.workspace
+            .split_note_by_headings(self, note_key, options)
+    }
+
+    pub fn merge_note(
+        &self,
+        source_key: &str,
+        target_key: &str,
+        options: &crate::mutation::merge::MergeOptions,
+    ) -> Option<crate::mutation::model::EditPlan> {
+        self.workspace
+            .merge_note(self, source_key, target_key, options)
+    }
+
+    pub fn mint_duplicate_ids(&self) -> Option<crate::mutation::model::EditPlan> {
+        self.workspace.mint_duplicate_ids(self)
+    }
+
+    /// Apply a plan directly to the file system, for callers without an editor,
+    /// and re-index the notes it touched. Nothing is changed if it fails.
+    pub fn apply_edit_plan(
+        &mut self,
+        plan: &crate::mutation::model::EditPlan,
+    ) -> Result<crate::mutation::executor::ExecutionReport, Vec<crate::mutation::model::Diagnostic>>
+    {
+        let report = crate::mutation::executor::Executor::new(&*self.fs).execute(plan)?;
+        self.workspace.sync_executed_plan(&report, &*self.fs);
+        Ok(report)
+    }
+
     // ------------------------------------------------------------------------
     // Note Editing & Health
     // ------------------------------------------------------------------------
@@ -141,7 +231,7 @@ impl DendriteEngine {
     }
 
     pub fn delete_note(&self, note_key: &str) -> Option<crate::mutation::model::EditPlan> {
-        self.workspace.delete_note(note_key)
+        self.workspace.delete_note(self, note_key)
     }
 }
 
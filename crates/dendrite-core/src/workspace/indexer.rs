@@ -1,6 +1,6 @@
 use super::assembler::NoteAssembler;
 use super::Workspace;
-use crate::model::NoteId;
+use crate::model::{frontmatter_id, Link, Note, NoteId, NoteKey};
 use crate::parser::parse_markdown;
 use crate::vfs::FileSystem;
 use std::path::PathBuf;
@@ -43,9 +43,10 @@ impl<'a> Indexer<'a> {
         let mut all_files = Vec::new();
 
         for vault in vaults {
+            let vault_dir = vault.resolve(self.workspace.model.root());
             let mut vault_files = Vec::new();
             for ext in &extensions {
-                vault_files.extend(self.fs.list_files(&vault.path, ext));
+                vault_files.extend(self.fs.list_files(&vault_dir, ext));
             }
 
             for path in &vault_files {
@@ -61,6 +62,7 @@ impl<'a> Indexer<'a> {
 
         // Invalidate tree to trigger rebuild on next access
         self.workspace.invalidate_tree();
+        self.workspace.invalidate_mentions();
 
         (all_files, self.stats.clone())
     }
@@ -72,8 +74,13 @@ impl<'a> Indexer<'a> {
             if let Some(cached_meta) = self.workspace.cache_metadata.get(&path) {
                 if cached_meta.mtime == fs_meta.mtime && cached_meta.size == fs_meta.len {
                     // Check if note exists in store
-                    if self.workspace.store.note_id_by_path(&path).is_some() {
+                    if let Some(id) = self.workspace.store.note_id_by_path(&path).cloned() {
                         self.stats.tier1_hits += 1;
+                        if !self.workspace.search_index.contains(&id) {
+                            if let Ok(content) = self.fs.read_to_string(&path) {
+                                self.index_search_body(&id, &content);
+                            }
+                        }
                         return; // Tier 1 Match!
                     }
                 }
@@ -100,8 +107,11 @@ impl<'a> Indexer<'a> {
                         },
                     );
 
-                    if self.workspace.store.note_id_by_path(&path).is_some() {
+                    if let Some(id) = self.workspace.store.note_id_by_path(&path).cloned() {
                         self.stats.tier2_hits += 1;
+                        if !self.workspace.search_index.contains(&id) {
+                            self.index_search_body(&id, &content);
+                        }
                         return; // Tier 2 Match!
                     }
                 }
@@ -127,6 +137,13 @@ impl<'a> Indexer<'a> {
         self.stats.full_parses += 1;
         let new_key = self.workspace.model.note_key_from_path(&path, content);
 
+        // Parse with provided digest
+        let mut parse_result =
+            parse_markdown(content, &self.workspace.model.supported_link_kinds());
+
+        // Override digest with our calculated one (just in case)
+        parse_result.digest = digest.clone();
+
         let (note_id, _old_digest) =
             if let Some(existing_id) = self.workspace.store.note_id_by_path(&path) {
                 let existing_id = existing_id.clone();
@@ -142,30 +159,30 @@ impl<'a> Indexer<'a> {
                     .key_of(&existing_id)
                     .expect("Consistency error: note ID without key");
                 if old_key != new_key {
-                    let _ = self.workspace.identity.rebind(&old_key, &new_key);
+                    self.workspace
+                        .identity
+                        .rekey(&existing_id, &old_key, &new_key);
                 }
 
                 (existing_id, old_digest)
             } else {
-                (self.workspace.identity.get_or_create(&new_key), None)
+                let frontmatter_id = frontmatter_id(parse_result.frontmatter.as_ref());
+                let note_id = self.claim_note_id(&new_key, frontmatter_id, &vault_name);
+                (note_id, None)
             };
 
-        // Parse with provided digest
-        let mut parse_result =
-            parse_markdown(content, &self.workspace.model.supported_link_kinds());
-
-        // Override digest with our calculated one (just in case)
-        parse_result.digest = digest.clone();
-
-        let note = NoteAssembler::new(&*self.workspace.model, &mut self.workspace.identity)
+        let mut note = NoteAssembler::new(&*self.workspace.model, &mut self.workspace.identity)
             .assemble(parse_result, &path, &note_id, vault_name);
+        self.resolve_aliased_links(&mut note);
 
         let targets: Vec<NoteId> = note.links.iter().map(|link| link.target.clone()).collect();
         self.workspace.store.upsert_note(note);
+        self.index_search_body(&note_id, content);
         self.workspace
             .store
             .bind_path(path.clone(), note_id.clone());
         self.workspace.store.set_outgoing_links(&note_id, targets);
+        self.sync_aliases(&note_id, &[new_key]);
 
         // Update cache metadata
         if let Ok(fs_meta) = self.fs.metadata(&path) {
@@ -180,6 +197,7 @@ impl<'a> Indexer<'a> {
         }
 
         self.workspace.invalidate_tree();
+        self.workspace.invalidate_mentions();
     }
 
     /// Handles file renaming.
@@ -204,22 +222,26 @@ impl<'a> Indexer<'a> {
         let new_key = self.workspace.model.note_key_from_path(&new_path, content);
 
         if old_key != new_key {
-            let _ = self.workspace.identity.rebind(&old_key, &new_key);
+            self.workspace.identity.rekey(&old_id, &old_key, &new_key);
         }
 
         let parse_result = parse_markdown(content, &self.workspace.model.supported_link_kinds());
-        let note = NoteAssembler::new(&*self.workspace.model, &mut self.workspace.identity)
+        let mut note = NoteAssembler::new(&*self.workspace.model, &mut self.workspace.identity)
             .assemble(parse_result, &new_path, &old_id, vault_name);
+        self.resolve_aliased_links(&mut note);
 
         let targets: Vec<NoteId> = note.links.iter().map(|link| link.target.clone()).collect();
         self.workspace.store.upsert_note(note);
+        self.index_search_body(&old_id, content);
         self.workspace.store.bind_path(new_path, old_id.clone());
         self.workspace.store.set_outgoing_links(&old_id, targets);
+        self.sync_aliases(&old_id, &[old_key.clone(), new_key.clone()]);
 
         // Key change affects tree structure
         if old_key != new_key {
             self.workspace.invalidate_tree();
         }
+        self.workspace.invalidate_mentions();
     }
 
     /// Handles file deletion.
@@ -228,6 +250,151 @@ impl<'a> Indexer<'a> {
             return;
         };
         self.workspace.store.remove_note(&id);
+        self.workspace.search_index.remove_note(&id);
+        let keys: Vec<NoteKey> = self.workspace.identity.key_of(&id).into_iter().collect();
+        self.sync_aliases(&id, &keys);
+        self.workspace.identity.remove_duplicate(&id);
         self.workspace.invalidate_tree();
+        self.workspace.invalidate_mentions();
+    }
+
+    /// Id for a note seen for the first time: derived from its frontmatter `id`
+    /// when it has one, so identity survives a lost cache or an external rename.
+    /// A note whose key already names a note in another vault gets an id of
+    /// its own, which vault-qualified links can tell apart.
+    fn claim_note_id(
+        &mut self,
+        key: &NoteKey,
+        frontmatter_id: Option<&str>,
+        vault_name: &str,
+    ) -> NoteId {
+        let workspace = &mut *self.workspace;
+        let stable_id = frontmatter_id.map(NoteId::from_frontmatter_id);
+        let is_real = |id: &NoteId| {
+            workspace
+                .store
+                .get_note(id)
+                .is_some_and(|note| note.path.is_some())
+        };
+        let in_other_vault = workspace.identity.lookup(key).is_some_and(|id| {
+            workspace
+                .store
+                .get_note(&id)
+                .is_some_and(|note| note.path.is_some() && note.vault_name != vault_name)
+        });
+        if in_other_vault {
+            let stable_id = stable_id.filter(|id| !is_real(id));
+            return workspace.identity.add_duplicate(key, stable_id);
+        }
+        let Some(stable_id) = stable_id else {
+            return workspace.identity.get_or_create(key);
+        };
+        // A copied note (duplicate frontmatter id) or a second file with this key
+        // keeps a random id; the audit reports duplicate ids
+        let current_id = workspace.identity.lookup(key);
+        if is_real(&stable_id) || current_id.is_some_and(|id| id != stable_id && is_real(&id)) {
+            return workspace.identity.get_or_create(key);
+        }
+
+        let (displaced_id, displaced_key) = workspace.identity.bind(key, &stable_id);
+        // Links to the key the id was last seen under stay with that key
+        if let Some(old_key) = displaced_key {
+            let ghost_id = workspace.identity.get_or_create(&old_key);
+            workspace.store.move_backlinks(&stable_id, &ghost_id);
+        }
+        // Links already written against this key (a ghost or virtual note so far) follow it
+        if let Some(old_id) = displaced_id {
+            workspace.store.move_backlinks(&old_id, &stable_id);
+            workspace.store.remove_note(&old_id);
+        }
+        stable_id
+    }
+
+    /// Point links written against an alias at the note declaring it, and
+    /// vault-qualified links at the note in that vault.
+    fn resolve_aliased_links(&self, note: &mut Note) {
+        for link in &mut note.links {
+            if let Some(target) = self.resolve_link(link) {
+                link.target = target;
+            }
+        }
+    }
+
+    fn resolve_link(&self, link: &Link) -> Option<NoteId> {
+        let store = &self.workspace.store;
+        if let Some(vault) = &link.target_vault {
+            let in_vault = self
+                .workspace
+                .identity
+                .notes_named(&link.target_key)
+                .into_iter()
+                .find(|id| {
+                    store
+                        .get_note(id)
+                        .is_some_and(|note| note.path.is_some() && note.vault_name == *vault)
+                });
+            if in_vault.is_some() {
+                return in_vault;
+            }
+        }
+        self.resolve_link_key(&link.target_key)
+    }
+
+    fn resolve_link_key(&self, key: &NoteKey) -> Option<NoteId> {
+        let store = &self.workspace.store;
+        self.workspace.identity.resolve(key, |id| {
+            store.get_note(id).is_some_and(|note| note.path.is_some())
+        })
+    }
+
+    /// Re-register the aliases of `id` (none once it is deleted) and relink
+    /// every link whose resolution may have changed: links written against an
+    /// added or removed alias, or against one of the note's `keys` that is
+    /// also someone's alias or names notes in several vaults.
+    fn sync_aliases(&mut self, id: &NoteId, keys: &[NoteKey]) {
+        let aliases = self
+            .workspace
+            .store
+            .get_note(id)
+            .map(|note| note.aliases())
+            .unwrap_or_default();
+        let mut affected = self.workspace.identity.set_aliases(id, &aliases);
+        let identity = &self.workspace.identity;
+        affected.extend(
+            keys.iter()
+                .filter(|key| identity.is_shared(key) || !identity.alias_owners(key).is_empty())
+                .map(|key| key.to_lowercase()),
+        );
+        if affected.is_empty() {
+            return;
+        }
+
+        let relinks: Vec<(NoteId, usize, NoteId)> = self
+            .workspace
+            .store
+            .all_notes()
+            .flat_map(|note| {
+                note.links
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, link)| affected.contains(&link.target_key.to_lowercase()))
+                    .filter_map(|(index, link)| {
+                        let target = self.resolve_link(link)?;
+                        (target != link.target).then(|| (note.id.clone(), index, target))
+                    })
+            })
+            .collect();
+        for (source, index, target) in relinks {
+            self.workspace.store.retarget_link(&source, index, target);
+        }
+    }
+
+    /// Index the body (content after frontmatter) of an already stored note for search.
+    fn index_search_body(&mut self, id: &NoteId, content: &str) {
+        let Some(note) = self.workspace.store.get_note(id) else {
+            return;
+        };
+        let body = content.get(note.content_offset as usize..).unwrap_or("");
+        self.workspace.search_index.index_note(id, body);
     }
 }
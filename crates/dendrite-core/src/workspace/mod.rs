@@ -1,10 +1,12 @@
+use crate::analysis::mentions::MentionMatcher;
 use crate::cache::FileMetadata;
 use crate::identity::IdentityRegistry;
+use crate::search::SearchIndex;
 use crate::semantic::SemanticModel;
 use crate::store::Store;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
 mod assembler;
 mod engine;
@@ -32,7 +34,9 @@ pub struct Workspace {
     pub(crate) identity: IdentityRegistry,
     pub(crate) store: Store,
     pub(crate) tree_cache: RwLock<Option<NoteTree>>,
+    pub(crate) mention_cache: RwLock<Option<Arc<MentionMatcher>>>,
     pub(crate) cache_metadata: HashMap<PathBuf, FileMetadata>,
+    pub(crate) search_index: SearchIndex,
 }
 
 impl Workspace {
@@ -43,16 +47,27 @@ impl Workspace {
             identity: IdentityRegistry::new(),
             store: Store::new(),
             tree_cache: RwLock::new(None),
+            mention_cache: RwLock::new(None),
             cache_metadata: HashMap::new(),
+            search_index: SearchIndex::default(),
         }
     }
 
+    /// The vault containing `path`; the innermost one if vaults are nested.
     pub fn vault_name_for_path(&self, path: &std::path::Path) -> Option<String> {
-        for vault in &self.config.workspace.vaults {
-            if path.starts_with(&vault.path) {
-                return Some(vault.name.clone());
-            }
-        }
-        None
+        self.config
+            .workspace
+            .vaults
+            .iter()
+            .rev()
+            .map(|vault| (vault, vault.resolve(self.model.root())))
+            .filter(|(_, dir)| path.starts_with(dir))
+            .max_by_key(|(_, dir)| dir.components().count())
+            .map(|(vault, _)| vault.name.clone())
+    }
+
+    pub(crate) fn vault_name_or_default(&self, path: &std::path::Path) -> String {
+        self.vault_name_for_path(path)
+            .unwrap_or_else(|| "main".to_string())
     }
 }
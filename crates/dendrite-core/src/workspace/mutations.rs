@@ -50,6 +50,49 @@ impl Workspace {
         )
     }
 
+    /// Rename every note whose key matches `pattern`, within the `scope` hierarchy.
+    /// Colliding keys are reported instead of producing a plan.
+    pub fn refactor_hierarchy(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+        pattern: &str,
+        replacement: &str,
+        scope: Option<&str>,
+    ) -> Result<
+        crate::mutation::hierarchy::HierarchyRefactorReport,
+        Vec<crate::mutation::model::Diagnostic>,
+    > {
+        crate::mutation::hierarchy::calculate_pattern_hierarchy_edits(
+            &self.store,
+            &self.identity,
+            content_provider,
+            self.model.as_ref(),
+            pattern,
+            replacement,
+            scope,
+        )
+    }
+
+    /// Move the note `note_key` (and optionally its descendants) into the vault `vault_name`.
+    pub fn move_to_vault(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+        note_key: &str,
+        include_descendants: bool,
+        vault_name: &str,
+    ) -> Option<crate::mutation::model::EditPlan> {
+        crate::mutation::vault::calculate_move_to_vault_edits(
+            &self.store,
+            &self.identity,
+            content_provider,
+            self.model.as_ref(),
+            &self.config.workspace.vaults,
+            note_key,
+            include_descendants,
+            vault_name,
+        )
+    }
+
     /// Initiate a Move Refactoring from old_path to new_path.
     pub fn move_note(
         &self,
@@ -75,6 +118,66 @@ impl Workspace {
         )
     }
 
+    /// Calculate link updates for files or folders about to be renamed outside of Dendrite.
+    /// The returned plan contains no resource operations; the caller performs the renames.
+    pub fn file_rename_edits(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+        renames: &[(std::path::PathBuf, std::path::PathBuf)],
+    ) -> Option<crate::mutation::model::EditPlan> {
+        crate::mutation::file_ops::calculate_file_rename_edits(
+            &self.store,
+            &self.identity,
+            content_provider,
+            self.model.as_ref(),
+            renames,
+        )
+    }
+
+    /// Calculate edits pointing links at notes renamed outside the editor,
+    /// after the renames were indexed (see `sync_file_changes`).
+    pub fn external_rename_edits(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+        renames: &[(std::path::PathBuf, std::path::PathBuf)],
+    ) -> Option<crate::mutation::model::EditPlan> {
+        crate::mutation::file_ops::calculate_external_rename_edits(
+            &self.store,
+            &self.identity,
+            content_provider,
+            self.model.as_ref(),
+            renames,
+        )
+    }
+
+    /// Calculate edits that unlink references to files or folders about to be deleted.
+    pub fn file_delete_edits(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+        paths: &[std::path::PathBuf],
+    ) -> Option<crate::mutation::model::EditPlan> {
+        crate::mutation::file_ops::calculate_file_delete_edits(&self.store, content_provider, paths)
+    }
+
+    /// Normalize link syntax and frontmatter field order of a note.
+    /// When `range` is given, only edits inside it are returned (range formatting).
+    pub fn format_note(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+        path: &std::path::Path,
+        range: Option<crate::model::TextRange>,
+    ) -> Option<crate::mutation::model::EditPlan> {
+        let content = content_provider.get_content(&path.to_string_lossy())?;
+        crate::mutation::format::calculate_format_edits(
+            &self.store,
+            &self.identity,
+            self.model.as_ref(),
+            path,
+            &content,
+            range,
+        )
+    }
+
     /// Extract a selection into a new note (Split Note).
     pub fn split_note(
         &self,
@@ -98,6 +201,55 @@ impl Workspace {
             new_note_title,
         )
     }
+
+    /// Split every section of `note_key` at the chosen heading level into a child note.
+    pub fn split_note_by_headings(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+        note_key: &str,
+        options: &crate::mutation::split::SplitByHeadingOptions,
+    ) -> Option<crate::mutation::model::EditPlan> {
+        let note_id = self.identity.lookup(&note_key.to_string())?;
+
+        crate::mutation::split::calculate_split_by_heading_edits(
+            &self.store,
+            &self.identity,
+            content_provider,
+            self.model.as_ref(),
+            &note_id,
+            options,
+        )
+    }
+
+    /// Merge the note `source_key` into `target_key` and delete the source.
+    pub fn merge_note(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+        source_key: &str,
+        target_key: &str,
+        options: &crate::mutation::merge::MergeOptions,
+    ) -> Option<crate::mutation::model::EditPlan> {
+        let source_id = self.identity.lookup(&source_key.to_string())?;
+        let target_id = self.identity.lookup(&target_key.to_string())?;
+
+        crate::mutation::merge::calculate_merge_edits(
+            &self.store,
+            &self.identity,
+            content_provider,
+            self.model.as_ref(),
+            &source_id,
+            &target_id,
+            options,
+        )
+    }
+
+    /// Give notes with a duplicate frontmatter `id` new ids (the audit's fix).
+    pub fn mint_duplicate_ids(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+    ) -> Option<crate::mutation::model::EditPlan> {
+        crate::mutation::ids::calculate_mint_id_edits(&self.store, &self.identity, content_provider)
+    }
 }
 
 // Edit Operations (Output)
@@ -125,7 +277,11 @@ impl Workspace {
     }
 
     /// Delete a note.
-    pub fn delete_note(&self, note_key: &str) -> Option<EditPlan> {
+    pub fn delete_note(
+        &self,
+        content_provider: &dyn crate::mutation::model::ContentProvider,
+        note_key: &str,
+    ) -> Option<EditPlan> {
         let note_id = self.identity.lookup(&note_key.to_string())?;
         let note = self.store.get_note(&note_id)?;
         let path = note.path.as_ref()?;
@@ -138,12 +294,15 @@ impl Workspace {
             })],
         };
 
-        Some(EditPlan {
-            mutation_kind: MutationKind::DeleteNote,
-            edits: vec![edit_group],
-            preconditions: vec![],
-            diagnostics: vec![],
-            reversible: true,
-        })
+        Some(
+            EditPlan {
+                mutation_kind: MutationKind::DeleteNote,
+                edits: vec![edit_group],
+                preconditions: vec![],
+                diagnostics: vec![],
+                reversible: true,
+            }
+            .with_file_preconditions(content_provider),
+        )
     }
 }
@@ -1,7 +1,20 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::analysis::audit::NoteAudit;
+use crate::analysis::backlinks::BacklinkContext;
+use crate::analysis::duplicates::{DuplicateCluster, DEFAULT_DUPLICATE_THRESHOLD};
+use crate::analysis::export::ExportOptions;
+use crate::analysis::graph::GraphReport;
+use crate::analysis::mentions::{MentionMatcher, UnlinkedMention};
+use crate::analysis::related::RelatedNote;
+use crate::analysis::traversal::{Neighborhood, PathStep, TraversalOptions};
 use crate::model::Point;
 use crate::model::{Link, Note, NoteKey, TextRange};
+use crate::mutation::model::{ContentProvider, Diagnostic, EditPlan};
+use crate::mutation::preview::EditPlanPreview;
+use crate::render::{RenderOptions, TransclusionOptions};
+use crate::search::SearchHit;
 
 use crate::slugify_heading;
 
@@ -101,6 +114,44 @@ impl Workspace {
         }
     }
 
+    /// Render the content a link points to, expanding nested embeds.
+    /// Used for hover previews; honours the link's anchor (heading, block or range).
+    pub fn link_preview(
+        &self,
+        content_provider: &dyn ContentProvider,
+        link: &Link,
+        options: &TransclusionOptions,
+    ) -> Option<String> {
+        crate::render::transclusion::transclude(
+            &self.store,
+            content_provider,
+            &link.target,
+            link.anchor.as_deref(),
+            options,
+        )
+    }
+
+    /// Render a note to HTML for the preview panel.
+    /// `to_uri` converts note paths into link targets (e.g. `file://` URIs).
+    pub fn render_note_html(
+        &self,
+        content_provider: &dyn ContentProvider,
+        key: &str,
+        options: &RenderOptions,
+        to_uri: &dyn Fn(&std::path::Path) -> String,
+    ) -> Option<String> {
+        let note_id = self.identity.lookup(&key.to_string())?;
+        crate::render::html::render_note_html(
+            &self.store,
+            &self.identity,
+            self.model.as_ref(),
+            content_provider,
+            &note_id,
+            options,
+            to_uri,
+        )
+    }
+
     pub fn backlinks_of(&self, path: &PathBuf) -> Vec<PathBuf> {
         let Some(id) = self.store.note_id_by_path(path) else {
             return vec![];
@@ -129,6 +180,24 @@ impl Workspace {
             .collect()
     }
 
+    /// Every link pointing at the note `key`, with its surrounding text and heading path.
+    pub fn backlinks_with_context(
+        &self,
+        content_provider: &dyn ContentProvider,
+        key: &str,
+    ) -> Vec<BacklinkContext> {
+        let Some(id) = self.identity.lookup(&key.to_string()) else {
+            return vec![];
+        };
+        crate::analysis::backlinks::calculate_backlinks(
+            &self.store,
+            &self.identity,
+            self.model.as_ref(),
+            content_provider,
+            &id,
+        )
+    }
+
     pub fn all_notes(&self) -> Vec<&Note> {
         self.store.all_notes().collect()
     }
@@ -147,6 +216,20 @@ impl Workspace {
             .collect()
     }
 
+    /// Get all frontmatter aliases for completion
+    /// Returns a vector of (alias, note_key) tuples
+    pub fn all_note_aliases(&self) -> Vec<(String, NoteKey)> {
+        self.store
+            .all_notes()
+            .filter_map(|note| Some((note, self.identity.key_of(&note.id)?)))
+            .flat_map(|(note, key)| {
+                note.aliases()
+                    .into_iter()
+                    .map(move |alias| (alias, key.clone()))
+            })
+            .collect()
+    }
+
     /// Lookup a note by its Note Key
     pub fn lookup_note(&self, key: &str) -> Option<&Note> {
         self.identity
@@ -188,8 +271,183 @@ impl Workspace {
         self.model.format_wikilink(target, alias, None, false)
     }
 
-    /// Audit the entire workspace for reference graph health.
+    /// Audit the entire workspace for reference graph health and near-duplicate notes.
     pub fn audit(&self) -> crate::mutation::model::EditPlan {
-        crate::analysis::audit::calculate_audit_diagnostics(&self.store, self.model.as_ref())
+        let mut plan =
+            crate::analysis::audit::calculate_audit_diagnostics(&self.store, self.model.as_ref());
+        plan.diagnostics
+            .extend(crate::analysis::duplicates::duplicate_diagnostics(
+                &self.duplicate_clusters(DEFAULT_DUPLICATE_THRESHOLD),
+            ));
+        plan.diagnostics
+            .extend(crate::analysis::audit::alias_collision_diagnostics(
+                &self.store,
+                &self.identity,
+            ));
+        plan.diagnostics
+            .extend(crate::analysis::audit::duplicate_id_diagnostics(
+                &self.store,
+                &self.identity,
+            ));
+        plan
+    }
+
+    /// Audit a single note by path.
+    pub fn audit_note(&self, path: &PathBuf) -> Option<NoteAudit> {
+        let note = self.note_by_path(path)?;
+        crate::analysis::audit::calculate_note_audit(
+            &self.store,
+            &self.identity,
+            self.model.as_ref(),
+            note,
+        )
+    }
+
+    /// Audit every note, one report per note (including notes without issues).
+    pub fn audit_notes(&self) -> Vec<NoteAudit> {
+        crate::analysis::audit::calculate_note_audits(
+            &self.store,
+            &self.identity,
+            self.model.as_ref(),
+        )
+    }
+
+    /// Clusters of notes sharing at least `threshold` of their content.
+    pub fn duplicate_clusters(&self, threshold: f64) -> Vec<DuplicateCluster> {
+        crate::analysis::duplicates::calculate_duplicate_clusters(
+            &self.store,
+            &self.identity,
+            &self.search_index,
+            threshold,
+        )
+    }
+
+    /// Unified diff and summary of what `plan` would change, against the
+    /// current content from `content_provider`.
+    pub fn preview_edit_plan(
+        &self,
+        content_provider: &dyn ContentProvider,
+        plan: &EditPlan,
+    ) -> Result<EditPlanPreview, Vec<Diagnostic>> {
+        crate::mutation::preview::preview_edit_plan(plan, content_provider, self.root())
+    }
+
+    /// Full-text search over note bodies, best matches first.
+    ///
+    /// Content is read through `content_provider` to compute match ranges and snippets.
+    pub fn search(
+        &self,
+        content_provider: &dyn ContentProvider,
+        query: &str,
+        limit: usize,
+    ) -> Vec<SearchHit> {
+        crate::search::search(
+            &self.store,
+            &self.identity,
+            &self.search_index,
+            content_provider,
+            query,
+            limit,
+        )
+    }
+
+    /// Link graph health: orphans, dead ends, hubs, components and root reachability.
+    pub fn graph_report(&self, hub_limit: usize) -> GraphReport {
+        crate::analysis::graph::calculate_graph_report(
+            &self.store,
+            &self.identity,
+            self.model.root_note_key().as_deref(),
+            hub_limit,
+        )
+    }
+
+    /// Shortest chain of notes connecting `from` to `to`.
+    pub fn shortest_path(
+        &self,
+        from: &str,
+        to: &str,
+        options: TraversalOptions,
+    ) -> Option<Vec<PathStep>> {
+        crate::analysis::traversal::calculate_shortest_path(
+            &self.store,
+            &self.identity,
+            self.model.as_ref(),
+            from,
+            to,
+            options,
+        )
+    }
+
+    /// Notes within `depth` hops of `key`, with the edges between them.
+    pub fn neighborhood(
+        &self,
+        key: &str,
+        depth: usize,
+        options: TraversalOptions,
+    ) -> Option<Neighborhood> {
+        crate::analysis::traversal::calculate_neighborhood(
+            &self.store,
+            &self.identity,
+            self.model.as_ref(),
+            key,
+            depth,
+            options,
+        )
+    }
+
+    /// Serialize the note graph as DOT, GraphML or node-link JSON.
+    pub fn export_graph(&self, options: &ExportOptions) -> String {
+        crate::analysis::export::export_graph(
+            &self.store,
+            &self.identity,
+            self.model.as_ref(),
+            options,
+        )
+    }
+
+    /// Plain-text mentions of other notes' titles or aliases, in the given notes
+    /// (or all notes when `paths` is None).
+    pub fn unlinked_mentions(
+        &self,
+        content_provider: &dyn ContentProvider,
+        paths: Option<&[PathBuf]>,
+    ) -> Vec<UnlinkedMention> {
+        crate::analysis::mentions::calculate_unlinked_mentions(
+            &self.store,
+            &self.mention_matcher(),
+            self.model.as_ref(),
+            content_provider,
+            paths,
+        )
+    }
+
+    /// Matcher for all note titles and aliases, built on first use after the
+    /// index changed.
+    fn mention_matcher(&self) -> Arc<MentionMatcher> {
+        if let Some(matcher) = self.mention_cache.read().unwrap().as_ref() {
+            return matcher.clone();
+        }
+        let matcher = Arc::new(MentionMatcher::build(&self.store, &self.identity));
+        *self.mention_cache.write().unwrap() = Some(matcher.clone());
+        matcher
+    }
+
+    /// Drop the cached mention matcher; titles or aliases may have changed.
+    pub(crate) fn invalidate_mentions(&self) {
+        *self.mention_cache.write().unwrap() = None;
+    }
+
+    /// Notes most similar to the one at `path` (TF-IDF cosine), excluding linked notes.
+    pub fn related_notes(&self, path: &PathBuf, limit: usize) -> Vec<RelatedNote> {
+        let Some(id) = self.store.note_id_by_path(path) else {
+            return Vec::new();
+        };
+        crate::analysis::related::calculate_related_notes(
+            &self.store,
+            &self.identity,
+            &self.search_index,
+            id,
+            limit,
+        )
     }
 }
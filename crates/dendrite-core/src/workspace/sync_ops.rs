@@ -1,5 +1,9 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::model::frontmatter_id;
+use crate::mutation::executor::ExecutionReport;
+use crate::parser::{compute_digest, parse_markdown};
 use crate::vfs::FileSystem;
 
 use super::{Indexer, Workspace};
@@ -44,4 +48,136 @@ impl Workspace {
         let mut indexer = Indexer::new(self, fs);
         indexer.rename_file(old_path, new_path, content, vault_name);
     }
+
+    /// Pair files deleted and created in the same batch of file system events
+    /// that are really one note moved elsewhere (`git checkout`, `mv`, ...).
+    ///
+    /// Files match by frontmatter `id`, or failing that by content digest when
+    /// exactly one deleted and one created file share it.
+    pub fn detect_renames(
+        &self,
+        deleted: &[PathBuf],
+        upserted: &[(PathBuf, String)],
+    ) -> Vec<(PathBuf, PathBuf)> {
+        let old_notes: Vec<_> = deleted
+            .iter()
+            .filter_map(|path| Some((path, self.note_by_path(path)?)))
+            .collect();
+        // Only files the index has not seen before can be the new side of a rename
+        let created: Vec<_> = upserted
+            .iter()
+            .filter(|(path, _)| self.store.note_id_by_path(path).is_none())
+            .map(|(path, content)| {
+                let parsed = parse_markdown(content, &self.model.supported_link_kinds());
+                let id = frontmatter_id(parsed.frontmatter.as_ref()).map(str::to_string);
+                (path, id, compute_digest(content))
+            })
+            .collect();
+
+        let mut renames = Vec::new();
+        let mut paired_old = vec![false; old_notes.len()];
+        let mut paired_new = vec![false; created.len()];
+
+        for (i, (old_path, note)) in old_notes.iter().enumerate() {
+            let Some(id) = note.frontmatter_id() else {
+                continue;
+            };
+            if let Some(j) =
+                (0..created.len()).find(|&j| !paired_new[j] && created[j].1.as_deref() == Some(id))
+            {
+                paired_old[i] = true;
+                paired_new[j] = true;
+                renames.push(((*old_path).clone(), created[j].0.clone()));
+            }
+        }
+
+        let mut old_by_digest: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, (_, note)) in old_notes.iter().enumerate() {
+            if let (false, Some(digest)) = (paired_old[i], &note.digest) {
+                old_by_digest.entry(digest).or_default().push(i);
+            }
+        }
+        let mut new_by_digest: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (j, (_, _, digest)) in created.iter().enumerate() {
+            if !paired_new[j] {
+                new_by_digest.entry(digest).or_default().push(j);
+            }
+        }
+        for (digest, old) in old_by_digest {
+            if let ([i], Some([j])) = (old.as_slice(), new_by_digest.get(digest).map(Vec::as_slice))
+            {
+                renames.push((old_notes[*i].0.clone(), created[*j].0.clone()));
+            }
+        }
+
+        renames.sort();
+        renames
+    }
+
+    /// Apply one batch of file system events, indexing detected renames as such
+    /// so the notes keep their identity and backlinks.
+    ///
+    /// Returns the detected renames.
+    pub fn sync_file_changes(
+        &mut self,
+        deleted: &[PathBuf],
+        upserted: &[(PathBuf, String)],
+        fs: &dyn FileSystem,
+    ) -> Vec<(PathBuf, PathBuf)> {
+        let renames = self.detect_renames(deleted, upserted);
+        let contents: HashMap<&PathBuf, &str> = upserted
+            .iter()
+            .map(|(path, content)| (path, content.as_str()))
+            .collect();
+
+        for (old_path, new_path) in &renames {
+            let vault_name = self.vault_name_or_default(new_path);
+            let mut indexer = Indexer::new(self, fs);
+            indexer.rename_file(
+                old_path.clone(),
+                new_path.clone(),
+                contents[new_path],
+                vault_name,
+            );
+        }
+        for path in deleted {
+            if !renames.iter().any(|(old_path, _)| old_path == path) {
+                Indexer::new(self, fs).delete_file(path);
+            }
+        }
+        for (path, content) in upserted {
+            if !renames.iter().any(|(_, new_path)| new_path == path) {
+                let vault_name = self.vault_name_or_default(path);
+                Indexer::new(self, fs).update_content(path.clone(), content, vault_name);
+            }
+        }
+
+        renames
+    }
+
+    /// Re-index the files touched by an `EditPlan` applied through the `Executor`.
+    pub fn sync_executed_plan(&mut self, report: &ExecutionReport, fs: &dyn FileSystem) {
+        for (old_path, new_path) in &report.renamed {
+            let Ok(content) = fs.read_to_string(new_path) else {
+                continue;
+            };
+            let vault_name = self.vault_name_or_default(new_path);
+            Indexer::new(self, fs).rename_file(
+                old_path.clone(),
+                new_path.clone(),
+                &content,
+                vault_name,
+            );
+        }
+        for path in &report.deleted {
+            Indexer::new(self, fs).delete_file(path);
+        }
+        for path in &report.written {
+            let Ok(content) = fs.read_to_string(path) else {
+                continue;
+            };
+            let vault_name = self.vault_name_or_default(path);
+            Indexer::new(self, fs).update_content(path.clone(), &content, vault_name);
+        }
+    }
 }
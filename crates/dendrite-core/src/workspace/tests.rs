@@ -1076,6 +1076,8 @@ fn test_resolve_link_blocks() {
     let target_id = ws.store.note_id_by_path(&target_path).unwrap().clone();
     let link = Link {
         target: target_id,
+        target_key: "target".to_string(),
+        target_vault: None,
         raw_target: "target".to_string(),
         alias: None,
         anchor: Some("^block-123".to_string()),
@@ -1113,6 +1115,8 @@ Content under heading 2."#;
     // Test ^begin anchor
     let begin_link = Link {
         target: target_id.clone(),
+        target_key: "target".to_string(),
+        target_vault: None,
         raw_target: "target".to_string(),
         alias: None,
         anchor: Some("^begin".to_string()),
@@ -1125,6 +1129,8 @@ Content under heading 2."#;
     // Test ^end anchor
     let end_link = Link {
         target: target_id,
+        target_key: "target".to_string(),
+        target_vault: None,
         raw_target: "target".to_string(),
         alias: None,
         anchor: Some("^end".to_string()),
@@ -1134,3 +1140,148 @@ Content under heading 2."#;
     let end_range = ws.resolve_link_anchor(&end_link).unwrap();
     assert!(end_range.start.line > 0);
 }
+
+#[test]
+fn test_alias_links_resolve_in_any_order() {
+    let (mut ws, temp_dir) = create_test_workspace();
+    let fs = PhysicalFileSystem;
+    let ref_path = temp_dir.path().join("ref.md");
+    let target_path = temp_dir.path().join("target.md");
+
+    // The link is indexed before any note declares the alias
+    Indexer::new(&mut ws, &fs).update_content(
+        ref_path.clone(),
+        "[[Home Page]] and [[home page#intro]]",
+        "main".to_string(),
+    );
+    assert!(ws.backlinks_of(&target_path).is_empty());
+
+    Indexer::new(&mut ws, &fs).update_content(
+        target_path.clone(),
+        "---\naliases: [Home Page]\n---\n# Intro",
+        "main".to_string(),
+    );
+    let target_id = ws.store.note_id_by_path(&target_path).unwrap().clone();
+    let ref_note = ws.note_by_path(&ref_path).unwrap();
+    assert!(ref_note.links.iter().all(|link| link.target == target_id));
+    assert_eq!(ws.backlinks_of(&target_path), vec![ref_path.clone()]);
+
+    // A note whose key is the alias takes precedence
+    let shadow_path = temp_dir.path().join("Home Page.md");
+    Indexer::new(&mut ws, &fs).update_content(shadow_path.clone(), "# Shadow", "main".to_string());
+    assert_eq!(ws.backlinks_of(&shadow_path), vec![ref_path.clone()]);
+    assert_eq!(ws.backlinks_of(&target_path), vec![ref_path.clone()]);
+
+    Indexer::new(&mut ws, &fs).delete_file(&shadow_path);
+    Indexer::new(&mut ws, &fs).update_content(target_path.clone(), "# Intro", "main".to_string());
+    assert!(ws.backlinks_of(&target_path).is_empty());
+}
+
+#[test]
+fn test_alias_collisions_in_audit() {
+    let (mut ws, temp_dir) = create_test_workspace();
+    let fs = PhysicalFileSystem;
+    for (key, content) in [
+        ("a", "---\naliases: [Shared, b]\n---\n"),
+        ("b", "---\naliases: [shared]\n---\n"),
+        ("c", "---\naliases: Unique\n---\n"),
+    ] {
+        let path = temp_dir.path().join(format!("{}.md", key));
+        Indexer::new(&mut ws, &fs).update_content(path, content, "main".to_string());
+    }
+
+    let mut messages: Vec<_> = ws
+        .audit()
+        .diagnostics
+        .into_iter()
+        .filter(|d| d.message.starts_with("Alias"))
+        .map(|d| d.message)
+        .collect();
+    messages.sort();
+    assert_eq!(
+        messages,
+        vec![
+            "Alias 'Shared' is also declared by 'b'; links to it resolve to 'a'.",
+            "Alias 'b' matches the key of note 'b', which takes precedence.",
+            "Alias 'shared' is also declared by 'a'; links to it resolve to 'a'.",
+        ]
+    );
+}
+
+#[test]
+fn test_note_audit_reports_shared_aliases_and_ids() {
+    let (mut ws, temp_dir) = create_test_workspace();
+    let fs = PhysicalFileSystem;
+    let path = |key: &str| temp_dir.path().join(format!("{}.md", key));
+    for (key, content) in [
+        ("a", "---\nid: same\naliases: [Shared]\n---\n"),
+        ("b", "---\nid: other\n---\n"),
+        ("copy", "---\nid: same\n---\n"),
+    ] {
+        Indexer::new(&mut ws, &fs).update_content(path(key), content, "main".to_string());
+    }
+
+    let messages = |ws: &Workspace| -> Vec<String> {
+        ws.audit_note(&path("a"))
+            .unwrap()
+            .diagnostics
+            .into_iter()
+            .map(|d| d.message)
+            .collect()
+    };
+    let before = ws.audit_note(&path("a")).unwrap();
+    assert_eq!(
+        messages(&ws),
+        vec!["Frontmatter id 'same' is also used by 'copy'."]
+    );
+
+    // Another note declaring the alias changes the report of "a", not its content
+    Indexer::new(&mut ws, &fs).update_content(
+        path("b"),
+        "---\nid: other\naliases: [shared]\n---\n",
+        "main".to_string(),
+    );
+    let after = ws.audit_note(&path("a")).unwrap();
+    assert_ne!(after.result_id, before.result_id);
+    assert_eq!(
+        messages(&ws),
+        vec![
+            "Alias 'Shared' is also declared by 'b'; links to it resolve to 'a'.",
+            "Frontmatter id 'same' is also used by 'copy'.",
+        ]
+    );
+    assert_eq!(
+        ws.audit_notes()
+            .into_iter()
+            .find(|audit| audit.path == path("a")),
+        Some(after)
+    );
+}
+
+#[test]
+fn test_note_id_derived_from_frontmatter_id() {
+    let content = "---\nid: abc123\ntitle: Target\n---\n# Target";
+    let mut ids = Vec::new();
+    // Two workspaces with nothing cached agree on the note's identity
+    for _ in 0..2 {
+        let (mut ws, temp_dir) = create_test_workspace();
+        let fs = PhysicalFileSystem;
+        let target_path = temp_dir.path().join("target.md");
+        let ref_path = temp_dir.path().join("ref.md");
+
+        // A link indexed first points at a ghost, which the real note replaces
+        Indexer::new(&mut ws, &fs).update_content(
+            ref_path.clone(),
+            "[[target]]",
+            "main".to_string(),
+        );
+        Indexer::new(&mut ws, &fs).update_content(target_path.clone(), content, "main".to_string());
+
+        let id = ws.store.note_id_by_path(&target_path).unwrap().clone();
+        assert_eq!(ws.identity.lookup(&"target".to_string()), Some(id.clone()));
+        assert_eq!(ws.backlinks_of(&target_path), vec![ref_path]);
+        ids.push(id);
+    }
+    assert_eq!(ids[0], ids[1]);
+    assert_eq!(ids[0], crate::model::NoteId::from_frontmatter_id("abc123"));
+}
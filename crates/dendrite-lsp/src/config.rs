@@ -11,6 +11,14 @@ pub struct LspSettings {
 
     /// Maximum number of mutation history entries for undo
     pub mutation_history_limit: usize,
+
+    /// Hover preview settings
+    #[serde(default)]
+    pub hover: HoverSettings,
+
+    /// Reactions to files renamed or deleted from the editor
+    #[serde(default)]
+    pub files: FileOperationSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -33,6 +41,35 @@ pub struct CacheSettings {
     pub save_interval: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HoverSettings {
+    /// How many levels of nested `![[embed]]`s are expanded in previews
+    pub max_embed_depth: usize,
+
+    /// Maximum preview length in characters
+    pub max_length: usize,
+}
+
+impl Default for HoverSettings {
+    fn default() -> Self {
+        Self {
+            max_embed_depth: dendrite_core::render::DEFAULT_MAX_EMBED_DEPTH,
+            max_length: 3000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileOperationSettings {
+    /// Replace links to deleted notes with their plain-text label
+    pub unlink_on_delete: bool,
+
+    /// Rewrite links to notes renamed outside the editor (e.g. by `git mv`)
+    pub update_links_on_external_rename: bool,
+}
+
 impl Default for LspSettings {
     fn default() -> Self {
         Self {
@@ -42,6 +79,8 @@ impl Default for LspSettings {
                 save_interval: 5000,
             },
             mutation_history_limit: 5,
+            hover: HoverSettings::default(),
+            files: FileOperationSettings::default(),
         }
     }
 }
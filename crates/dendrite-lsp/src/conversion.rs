@@ -4,15 +4,17 @@
 //! (Point, TextRange) and LSP types (Position, Range) when needed.
 
 use dendrite_core::model::{Point, TextRange};
-use dendrite_core::mutation::model::{Change, EditPlan, ResourceOperation};
+use dendrite_core::mutation::model::{Change, EditPlan, MutationKind, ResourceOperation};
 use dendrite_core::mutation::model::{
     Diagnostic as CoreDiagnostic, DiagnosticSeverity as CoreSeverity,
 };
+use std::collections::HashMap;
 use std::path::PathBuf;
 use tower_lsp::lsp_types::{
-    CreateFile, CreateFileOptions, DeleteFile, DeleteFileOptions, DocumentChangeOperation, OneOf,
-    OptionalVersionedTextDocumentIdentifier, Position, Range, RenameFile, RenameFileOptions,
-    ResourceOp, TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+    AnnotatedTextEdit, ChangeAnnotation, CreateFile, CreateFileOptions, DeleteFile,
+    DeleteFileOptions, DocumentChangeOperation, OneOf, OptionalVersionedTextDocumentIdentifier,
+    Position, Range, RenameFile, RenameFileOptions, ResourceOp, TextDocumentEdit, TextEdit, Url,
+    WorkspaceEdit,
 };
 use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
 
@@ -103,10 +105,52 @@ pub fn path_to_uri(path: &PathBuf) -> Option<Url> {
     Url::from_file_path(path).ok()
 }
 
+/// Convert the TextEdits of a single-document EditPlan (e.g. formatting) to LSP TextEdits.
+/// Resource operations are ignored.
+pub fn edit_plan_to_text_edits(plan: EditPlan) -> Vec<TextEdit> {
+    plan.edits
+        .into_iter()
+        .flat_map(|group| group.changes)
+        .filter_map(|change| match change {
+            Change::TextEdit(edit) => Some(TextEdit {
+                range: text_range_to_lsp_range(edit.range),
+                new_text: edit.new_text,
+            }),
+            Change::ResourceOp(_) => None,
+        })
+        .collect()
+}
+
 /// Convert EditPlan to LSP WorkspaceEdit
-pub fn edit_plan_to_workspace_edit(plan: EditPlan) -> WorkspaceEdit {
+///
+/// `change_annotations` tells whether the client supports them
+/// (`workspace.workspaceEdit.changeAnnotationSupport`).
+pub fn edit_plan_to_workspace_edit(plan: EditPlan, change_annotations: bool) -> WorkspaceEdit {
     let mut document_changes = Vec::new();
 
+    // Plans moving or removing notes are shown to the user before being applied
+    let annotation = (change_annotations && needs_confirmation(&plan)).then(|| {
+        (
+            EDIT_PLAN_ANNOTATION.to_string(),
+            ChangeAnnotation {
+                label: plan.describe(),
+                needs_confirmation: Some(true),
+                description: None,
+            },
+        )
+    });
+    let annotation_id = annotation.as_ref().map(|(id, _)| id.clone());
+    let text_edit = |range, new_text| -> OneOf<TextEdit, AnnotatedTextEdit> {
+        let text_edit = TextEdit { range, new_text };
+        match &annotation_id {
+            Some(id) => OneOf::Right(AnnotatedTextEdit {
+                text_edit,
+                annotation_id: id.clone(),
+            }),
+            None => OneOf::Left(text_edit),
+        }
+    };
+
     for group in plan.edits {
         let uri = match Url::parse(&group.uri) {
             Ok(u) if u.scheme() == "file" => u,
@@ -138,10 +182,10 @@ pub fn edit_plan_to_workspace_edit(plan: EditPlan) -> WorkspaceEdit {
         for change in group.changes {
             match change {
                 Change::TextEdit(edit) => {
-                    current_text_edits.push(TextEdit {
-                        range: text_range_to_lsp_range(edit.range),
-                        new_text: edit.new_text,
-                    });
+                    current_text_edits.push(text_edit(
+                        text_range_to_lsp_range(edit.range),
+                        edit.new_text,
+                    ));
                 }
                 Change::ResourceOp(op) => {
                     // 1. Flush pending text edits
@@ -151,7 +195,7 @@ pub fn edit_plan_to_workspace_edit(plan: EditPlan) -> WorkspaceEdit {
                                 uri: uri.clone(),
                                 version: None,
                             },
-                            edits: current_text_edits.into_iter().map(OneOf::Left).collect(),
+                            edits: current_text_edits,
                         }));
                         current_text_edits = Vec::new();
                     }
@@ -172,7 +216,7 @@ pub fn edit_plan_to_workspace_edit(plan: EditPlan) -> WorkspaceEdit {
                                         overwrite: Some(overwrite),
                                         ignore_if_exists: None,
                                     }),
-                                    annotation_id: None,
+                                    annotation_id: annotation_id.clone(),
                                 });
                                 document_changes.push(DocumentChangeOperation::Op(op));
                             }
@@ -184,7 +228,7 @@ pub fn edit_plan_to_workspace_edit(plan: EditPlan) -> WorkspaceEdit {
                                     overwrite: Some(false),
                                     ignore_if_exists: Some(true),
                                 }),
-                                annotation_id: None,
+                                annotation_id: annotation_id.clone(),
                             });
                             document_changes.push(DocumentChangeOperation::Op(create_op));
 
@@ -195,19 +239,7 @@ pub fn edit_plan_to_workspace_edit(plan: EditPlan) -> WorkspaceEdit {
                                             uri: uri.clone(),
                                             version: None,
                                         },
-                                        edits: vec![OneOf::Left(TextEdit {
-                                            range: Range {
-                                                start: Position {
-                                                    line: 0,
-                                                    character: 0,
-                                                },
-                                                end: Position {
-                                                    line: 0,
-                                                    character: 0,
-                                                },
-                                            },
-                                            new_text: text,
-                                        })],
+                                        edits: vec![text_edit(Range::default(), text)],
                                     },
                                 ));
                             }
@@ -220,7 +252,7 @@ pub fn edit_plan_to_workspace_edit(plan: EditPlan) -> WorkspaceEdit {
                                 options: Some(DeleteFileOptions {
                                     recursive: None,
                                     ignore_if_not_exists: Some(ignore_if_not_exists),
-                                    annotation_id: None,
+                                    annotation_id: annotation_id.clone(),
                                 }),
                             });
                             document_changes.push(DocumentChangeOperation::Op(op));
@@ -237,7 +269,7 @@ pub fn edit_plan_to_workspace_edit(plan: EditPlan) -> WorkspaceEdit {
                     uri: uri.clone(),
                     version: None,
                 },
-                edits: current_text_edits.into_iter().map(OneOf::Left).collect(),
+                edits: current_text_edits,
             }));
         }
     }
@@ -247,6 +279,18 @@ pub fn edit_plan_to_workspace_edit(plan: EditPlan) -> WorkspaceEdit {
         document_changes: Some(tower_lsp::lsp_types::DocumentChanges::Operations(
             document_changes,
         )),
-        change_annotations: None,
+        change_annotations: annotation.map(|annotation| HashMap::from([annotation])),
     }
 }
+
+const EDIT_PLAN_ANNOTATION: &str = "dendrite.editPlan";
+
+fn needs_confirmation(plan: &EditPlan) -> bool {
+    matches!(
+        plan.mutation_kind,
+        MutationKind::HierarchyRefactor
+            | MutationKind::MergeNote
+            | MutationKind::DeleteNote
+            | MutationKind::MoveToVault
+    )
+}
@@ -1,6 +1,12 @@
+use crate::overlay::OverlayContentProvider;
+use crate::protocol::{
+    DuplicateClusterSummary, DuplicateNoteSummary, FindDuplicatesParams, FindDuplicatesResult,
+    RelatedNoteSummary, RelatedNotesParams, RelatedNotesResult, UnlinkedMentionSummary,
+    UnlinkedMentionsParams, UnlinkedMentionsResult,
+};
 use crate::state::GlobalState;
 use std::collections::HashMap;
-use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::Client;
 
@@ -42,3 +48,260 @@ pub async fn handle_workspace_audit_command(
 
     Ok(Some(serde_json::to_value(report).unwrap()))
 }
+
+/// Convert a note's audit into LSP diagnostics
+fn note_audit_items(
+    audit: dendrite_core::analysis::audit::NoteAudit,
+    root_path: &std::path::Path,
+) -> Vec<Diagnostic> {
+    audit
+        .diagnostics
+        .into_iter()
+        .filter_map(|diag| {
+            crate::conversion::core_diagnostic_to_lsp_diagnostic(diag, Some(root_path))
+                .map(|(_, lsp_diag)| lsp_diag)
+        })
+        .collect()
+}
+
+/// Handle "textDocument/diagnostic" request (pull model)
+pub async fn handle_document_diagnostic(
+    state: &GlobalState,
+    params: DocumentDiagnosticParams,
+) -> Result<DocumentDiagnosticReportResult> {
+    let engine_guard = state.engine.read().await;
+    let audit = match (&*engine_guard, params.text_document.uri.to_file_path()) {
+        (Some(engine), Ok(path)) => engine
+            .workspace
+            .audit_note(&path)
+            .map(|audit| (audit, engine.workspace.root())),
+        _ => None,
+    };
+
+    let report = match audit {
+        Some((audit, _)) if params.previous_result_id.as_ref() == Some(&audit.result_id) => {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id: audit.result_id,
+                },
+            })
+        }
+        Some((audit, root_path)) => {
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                    result_id: Some(audit.result_id.clone()),
+                    items: note_audit_items(audit, root_path),
+                },
+            })
+        }
+        // Not a note (yet): nothing to report, and nothing to cache
+        None => DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport::default()),
+    };
+
+    Ok(DocumentDiagnosticReportResult::Report(report))
+}
+
+/// Handle "workspace/diagnostic" request (pull model)
+pub async fn handle_workspace_diagnostic(
+    state: &GlobalState,
+    params: WorkspaceDiagnosticParams,
+) -> Result<WorkspaceDiagnosticReportResult> {
+    let previous: HashMap<Url, String> = params
+        .previous_result_ids
+        .into_iter()
+        .map(|id| (id.uri, id.value))
+        .collect();
+
+    let engine_guard = state.engine.read().await;
+    let Some(engine) = &*engine_guard else {
+        return Ok(WorkspaceDiagnosticReportResult::Report(
+            WorkspaceDiagnosticReport::default(),
+        ));
+    };
+    let root_path = engine.workspace.root();
+
+    let items = engine
+        .workspace
+        .audit_notes()
+        .into_iter()
+        .filter_map(|audit| {
+            let uri = Url::from_file_path(&audit.path).ok()?;
+            let report = if previous.get(&uri) == Some(&audit.result_id) {
+                WorkspaceDocumentDiagnosticReport::Unchanged(
+                    WorkspaceUnchangedDocumentDiagnosticReport {
+                        uri,
+                        version: None,
+                        unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                            result_id: audit.result_id,
+                        },
+                    },
+                )
+            } else {
+                WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                    uri,
+                    version: None,
+                    full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                        result_id: Some(audit.result_id.clone()),
+                        items: note_audit_items(audit, root_path),
+                    },
+                })
+            };
+            Some(report)
+        })
+        .collect();
+
+    Ok(WorkspaceDiagnosticReportResult::Report(
+        WorkspaceDiagnosticReport { items },
+    ))
+}
+
+const DEFAULT_RELATED_LIMIT: usize = 10;
+
+/// Handle "dendrite/relatedNotes" command
+/// Arguments: [{ uri, limit? }]
+/// Returns: { notes: [{ key, uri, title, score }] }, most similar first
+pub async fn handle_related_notes_command(
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: RelatedNotesParams = if let Some(first_arg) = params.arguments.first() {
+        serde_json::from_value(first_arg.clone())
+            .map_err(|_| Error::invalid_params("Invalid params"))?
+    } else {
+        return Err(Error::invalid_params("Missing params"));
+    };
+    let path = Url::parse(&params.uri)
+        .ok()
+        .and_then(|u| u.to_file_path().ok())
+        .ok_or_else(|| Error::invalid_params("URI is not a file path"))?;
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let notes = engine
+        .workspace
+        .related_notes(&path, params.limit.unwrap_or(DEFAULT_RELATED_LIMIT))
+        .into_iter()
+        .map(|note| RelatedNoteSummary {
+            uri: note
+                .path
+                .as_ref()
+                .and_then(crate::conversion::path_to_uri)
+                .map(|uri| uri.to_string()),
+            key: note.key,
+            title: note.title,
+            score: note.score,
+        })
+        .collect();
+
+    serde_json::to_value(RelatedNotesResult { notes })
+        .map(Some)
+        .map_err(|e| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to serialize result: {}", e).into(),
+            data: None,
+        })
+}
+
+/// Handle "dendrite/unlinkedMentions" command
+/// Arguments: [{ uri? }] (optional; defaults to the whole workspace)
+/// Returns: { mentions: [{ uri, range, text, target_key }] }
+pub async fn handle_unlinked_mentions_command(
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: UnlinkedMentionsParams = match params.arguments.first() {
+        Some(first_arg) => serde_json::from_value(first_arg.clone())
+            .map_err(|_| Error::invalid_params("Invalid params"))?,
+        None => UnlinkedMentionsParams::default(),
+    };
+    let paths = match &params.uri {
+        Some(uri) => Some(vec![Url::parse(uri)
+            .ok()
+            .and_then(|u| u.to_file_path().ok())
+            .ok_or_else(|| Error::invalid_params("URI is not a file path"))?]),
+        None => None,
+    };
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let mentions = {
+        let documents = state.document_cache.read().await;
+        let provider = OverlayContentProvider::new(&documents, &*engine.fs);
+        engine
+            .workspace
+            .unlinked_mentions(&provider, paths.as_deref())
+    };
+
+    let mentions = mentions
+        .into_iter()
+        .filter_map(|mention| {
+            Some(UnlinkedMentionSummary {
+                uri: Url::from_file_path(&mention.path).ok()?.to_string(),
+                range: crate::conversion::text_range_to_lsp_range(mention.range),
+                text: mention.text,
+                target_key: mention.target,
+            })
+        })
+        .collect();
+
+    serde_json::to_value(UnlinkedMentionsResult { mentions })
+        .map(Some)
+        .map_err(|e| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to serialize result: {}", e).into(),
+            data: None,
+        })
+}
+
+/// Handle "dendrite/findDuplicates" command
+/// Arguments: [{ threshold? }] (optional; defaults to 0.8)
+/// Returns: { clusters: [{ notes: [{ key, uri }], shared_ratio, pairs: [{ a, b, shared_ratio }] }] }
+pub async fn handle_find_duplicates_command(
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: FindDuplicatesParams = match params.arguments.first() {
+        Some(first_arg) => serde_json::from_value(first_arg.clone())
+            .map_err(|_| Error::invalid_params("Invalid params"))?,
+        None => FindDuplicatesParams::default(),
+    };
+    let threshold = params
+        .threshold
+        .unwrap_or(dendrite_core::analysis::duplicates::DEFAULT_DUPLICATE_THRESHOLD);
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(Error::invalid_params("threshold must be between 0 and 1"));
+    }
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let clusters = engine
+        .workspace
+        .duplicate_clusters(threshold)
+        .into_iter()
+        .map(|cluster| DuplicateClusterSummary {
+            notes: cluster
+                .notes
+                .into_iter()
+                .map(|note| DuplicateNoteSummary {
+                    uri: crate::conversion::path_to_uri(&note.path).map(|uri| uri.to_string()),
+                    key: note.key,
+                })
+                .collect(),
+            shared_ratio: cluster.shared_ratio,
+            pairs: cluster.pairs,
+        })
+        .collect();
+
+    serde_json::to_value(FindDuplicatesResult { clusters })
+        .map(Some)
+        .map_err(|e| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to serialize result: {}", e).into(),
+            data: None,
+        })
+}
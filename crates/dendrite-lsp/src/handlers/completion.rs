@@ -97,23 +97,32 @@ pub async fn handle_completion(
         }
     } else {
         // --- NOTE COMPLETION ---
-        ws.all_note_keys()
+        let notes = ws.all_note_keys().into_iter().map(|(key, display_name)| {
+            let detail = if display_name.is_empty() {
+                None
+            } else {
+                Some(display_name)
+            };
+            CompletionItem {
+                label: key.clone(),
+                kind: Some(CompletionItemKind::FILE),
+                detail,
+                insert_text: Some(key),
+                ..Default::default()
+            }
+        });
+        // Aliases are inserted as written so the link keeps resolving through them
+        let aliases = ws
+            .all_note_aliases()
             .into_iter()
-            .map(|(key, display_name)| {
-                let detail = if display_name.is_empty() {
-                    None
-                } else {
-                    Some(display_name)
-                };
-                CompletionItem {
-                    label: key.clone(),
-                    kind: Some(CompletionItemKind::FILE),
-                    detail,
-                    insert_text: Some(key),
-                    ..Default::default()
-                }
-            })
-            .collect()
+            .map(|(alias, key)| CompletionItem {
+                label: alias.clone(),
+                kind: Some(CompletionItemKind::REFERENCE),
+                detail: Some(format!("Alias of {}", key)),
+                insert_text: Some(alias),
+                ..Default::default()
+            });
+        notes.chain(aliases).collect()
     };
 
     Ok(Some(CompletionResponse::Array(items)))
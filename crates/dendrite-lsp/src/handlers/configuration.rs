@@ -23,10 +23,11 @@ pub async fn handle_did_change_configuration(
                     *config_lock = new_settings;
 
                     // Respond to history limit change
-                    let mut history = state.mutation_history.write().await;
-                    while history.len() > config_lock.mutation_history_limit {
-                        history.pop_front();
-                    }
+                    state
+                        .mutation_history
+                        .write()
+                        .await
+                        .truncate(config_lock.mutation_history_limit);
 
                     client
                         .log_message(MessageType::INFO, "✅ LSP settings updated successfully")
@@ -0,0 +1,114 @@
+use crate::conversion::edit_plan_to_workspace_edit;
+use crate::overlay::OverlayContentProvider;
+use crate::state::GlobalState;
+use dendrite_core::mutation::model::EditPlan;
+use std::path::PathBuf;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+/// Handle "workspace/willRenameFiles" request
+///
+/// Returns the link updates for every note at or below the renamed paths.
+/// The client applies them before performing the rename itself. They are not
+/// recorded in the mutation history, since the client may still decline them.
+pub async fn handle_will_rename_files(
+    state: &GlobalState,
+    params: RenameFilesParams,
+) -> Result<Option<WorkspaceEdit>> {
+    let renames: Vec<(PathBuf, PathBuf)> = params
+        .files
+        .iter()
+        .filter_map(|file| Some((uri_to_path(&file.old_uri)?, uri_to_path(&file.new_uri)?)))
+        .collect();
+
+    let plan = {
+        let engine = state.engine.read().await;
+        match &*engine {
+            Some(v) => {
+                crate::handlers::with_overlay(state, v, |ws, cp| ws.file_rename_edits(cp, &renames))
+                    .await
+            }
+            None => return Ok(None),
+        }
+    };
+
+    match plan {
+        Some(p) => {
+            crate::handlers::validate_edit_plan(state, &p).await?;
+            let change_annotations = state.supports_change_annotations().await;
+            Ok(Some(edit_plan_to_workspace_edit(p, change_annotations)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Handle "workspace/willDeleteFiles" request
+///
+/// When `files.unlinkOnDelete` is enabled, references to the deleted notes
+/// are replaced by their label so they don't turn into broken links.
+pub async fn handle_will_delete_files(
+    state: &GlobalState,
+    params: DeleteFilesParams,
+) -> Result<Option<WorkspaceEdit>> {
+    let enabled = {
+        let config = state.config.read().await;
+        config.files.unlink_on_delete
+    };
+    if !enabled {
+        return Ok(None);
+    }
+
+    let paths: Vec<PathBuf> = params
+        .files
+        .iter()
+        .filter_map(|file| uri_to_path(&file.uri))
+        .collect();
+
+    let plan = {
+        let engine = state.engine.read().await;
+        match &*engine {
+            Some(v) => {
+                crate::handlers::with_overlay(state, v, |ws, cp| ws.file_delete_edits(cp, &paths))
+                    .await
+            }
+            None => return Ok(None),
+        }
+    };
+
+    match plan {
+        Some(p) => {
+            crate::handlers::validate_edit_plan(state, &p).await?;
+            let change_annotations = state.supports_change_annotations().await;
+            Ok(Some(edit_plan_to_workspace_edit(p, change_annotations)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The undo plan of a reversible plan, from the open documents and the file system.
+///
+/// Must be computed before the edit is applied, from the content the edit replaces.
+pub(crate) async fn undo_plan(state: &GlobalState, plan: &EditPlan) -> Option<EditPlan> {
+    if !plan.reversible {
+        return None;
+    }
+    let documents = state.document_cache.read().await;
+    let overlay = OverlayContentProvider::new(&documents, &*state.fs);
+    plan.undo_plan(&overlay).ok()
+}
+
+pub(crate) async fn push_history(state: &GlobalState, plan: &EditPlan, undo: EditPlan) {
+    let limit = {
+        let config = state.config.read().await;
+        config.mutation_history_limit
+    };
+    let mut history = state.mutation_history.write().await;
+    history.record(plan.clone(), undo, limit);
+    if let Err(e) = history.save(&*state.fs) {
+        eprintln!("❌ Failed to save mutation history: {}", e);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::conversion::{edit_plan_to_text_edits, lsp_position_to_point};
+use crate::overlay::OverlayContentProvider;
+use crate::state::GlobalState;
+use dendrite_core::model::TextRange;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+async fn format_document(
+    state: &GlobalState,
+    uri: &Url,
+    range: Option<Range>,
+) -> Result<Option<Vec<TextEdit>>> {
+    let Ok(path) = uri.to_file_path() else {
+        return Ok(None);
+    };
+
+    let engine_lock = state.engine.read().await;
+    let Some(engine) = &*engine_lock else {
+        return Ok(None);
+    };
+
+    let range = range.map(|r| TextRange {
+        start: lsp_position_to_point(r.start),
+        end: lsp_position_to_point(r.end),
+    });
+
+    let plan = {
+        let documents = state.document_cache.read().await;
+        let provider = OverlayContentProvider::new(&documents, &*engine.fs);
+        engine.workspace.format_note(&provider, &path, range)
+    };
+
+    Ok(plan.map(edit_plan_to_text_edits))
+}
+
+/// Handle "textDocument/formatting" request
+pub async fn handle_formatting(
+    state: &GlobalState,
+    params: DocumentFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+    format_document(state, &params.text_document.uri, None).await
+}
+
+/// Handle "textDocument/rangeFormatting" request
+pub async fn handle_range_formatting(
+    state: &GlobalState,
+    params: DocumentRangeFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+    format_document(state, &params.text_document.uri, Some(params.range)).await
+}
@@ -0,0 +1,142 @@
+use crate::conversion::path_to_uri;
+use crate::protocol::{
+    GraphNodeSummary, GraphStatsParams, NeighborhoodParams, NeighborhoodResult, PathStepSummary,
+    ShortestPathParams, ShortestPathResult,
+};
+use crate::state::GlobalState;
+use dendrite_core::analysis::traversal::TraversalOptions;
+use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
+use tower_lsp::lsp_types::ExecuteCommandParams;
+
+const DEFAULT_HUB_LIMIT: usize = 10;
+const DEFAULT_NEIGHBORHOOD_DEPTH: usize = 1;
+
+/// Handle "dendrite/graphStats" command
+/// Arguments: [{ hub_limit? }] (optional)
+/// Returns: GraphReport (orphans, dead_ends, hubs, components, unreachable_from_root)
+pub async fn handle_graph_stats_command(
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: GraphStatsParams = match params.arguments.first() {
+        Some(first_arg) => serde_json::from_value(first_arg.clone())
+            .map_err(|_| Error::invalid_params("Invalid params"))?,
+        None => GraphStatsParams::default(),
+    };
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let report = engine
+        .workspace
+        .graph_report(params.hub_limit.unwrap_or(DEFAULT_HUB_LIMIT));
+
+    to_json(report)
+}
+
+/// Handle "dendrite/shortestPath" command
+/// Arguments: [{ from, to, direction?, include_hierarchy? }]
+/// Returns: { path: [{ key, uri, via }] | null }
+pub async fn handle_shortest_path_command(
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: ShortestPathParams = parse_params(params)?;
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+    let ws = &engine.workspace;
+
+    let options = TraversalOptions {
+        direction: params.direction,
+        include_hierarchy: params.include_hierarchy,
+    };
+    let path = ws
+        .shortest_path(&params.from, &params.to, options)
+        .map(|steps| {
+            steps
+                .into_iter()
+                .map(|step| PathStepSummary {
+                    uri: note_uri(ws, &step.key),
+                    key: step.key,
+                    via: step.via,
+                })
+                .collect()
+        });
+
+    to_json(ShortestPathResult { path })
+}
+
+/// Handle "dendrite/neighborhood" command
+/// Arguments: [{ note_key, depth?, direction?, include_hierarchy? }]
+/// Returns: { nodes: [{ key, uri, title, depth }], edges: [{ source, target, kind }] }
+pub async fn handle_neighborhood_command(
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: NeighborhoodParams = parse_params(params)?;
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let options = TraversalOptions {
+        direction: params.direction,
+        include_hierarchy: params.include_hierarchy,
+    };
+    let neighborhood = engine
+        .workspace
+        .neighborhood(
+            &params.note_key,
+            params.depth.unwrap_or(DEFAULT_NEIGHBORHOOD_DEPTH),
+            options,
+        )
+        .ok_or_else(|| Error {
+            code: ErrorCode::InvalidParams,
+            message: format!("Note not found: {}", params.note_key).into(),
+            data: None,
+        })?;
+
+    let nodes = neighborhood
+        .nodes
+        .into_iter()
+        .map(|node| GraphNodeSummary {
+            uri: node
+                .path
+                .as_ref()
+                .and_then(path_to_uri)
+                .map(|uri| uri.to_string()),
+            key: node.key,
+            title: node.title,
+            depth: node.depth,
+        })
+        .collect();
+
+    to_json(NeighborhoodResult {
+        nodes,
+        edges: neighborhood.edges,
+    })
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: ExecuteCommandParams) -> Result<T> {
+    let first_arg = params
+        .arguments
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::invalid_params("Missing params"))?;
+    serde_json::from_value(first_arg).map_err(|_| Error::invalid_params("Invalid params"))
+}
+
+fn note_uri(ws: &dendrite_core::Workspace, key: &str) -> Option<String> {
+    ws.lookup_note(key)
+        .and_then(|note| note.path.as_ref())
+        .and_then(path_to_uri)
+        .map(|uri| uri.to_string())
+}
+
+fn to_json<T: serde::Serialize>(result: T) -> Result<Option<serde_json::Value>> {
+    serde_json::to_value(result).map(Some).map_err(|e| Error {
+        code: ErrorCode::InternalError,
+        message: format!("Failed to serialize result: {}", e).into(),
+        data: None,
+    })
+}
@@ -79,30 +79,77 @@ pub async fn handle_reorganize_hierarchy_command(
     let engine_guard = state.engine.read().await;
     let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
 
-    let plan = engine.rename_hierarchy(&old_key, &new_key);
+    let plan = crate::handlers::with_overlay(state, engine, |ws, cp| {
+        ws.rename_hierarchy(cp, &old_key, &new_key)
+    })
+    .await;
 
     if let Some(plan) = plan {
-        crate::handlers::apply_edit_plan(client, plan.clone()).await?;
-
-        // Store in history for undo
-        if plan.reversible {
-            let limit = {
-                let config = state.config.read().await;
-                config.mutation_history_limit
-            };
-            let mut history = state.mutation_history.write().await;
-            history.push_back(plan);
-            while history.len() > limit {
-                history.pop_front();
-            }
-        }
-
+        crate::handlers::apply_edit_plan(client, state, plan).await?;
         Ok(Some(serde_json::Value::Bool(true)))
     } else {
         Ok(Some(serde_json::Value::Bool(false)))
     }
 }
 
+/// Handle "dendrite/refactorHierarchy" command
+/// Arguments: [{ pattern, replacement, scope?, dry_run? }]
+/// Renames every note whose key matches the pattern, unless keys collide.
+pub async fn handle_refactor_hierarchy_command(
+    client: &Client,
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: crate::protocol::RefactorHierarchyParams =
+        if let Some(first_arg) = params.arguments.first() {
+            serde_json::from_value(first_arg.clone())
+                .map_err(|_| Error::invalid_params("Invalid params"))?
+        } else {
+            return Err(Error::invalid_params("Missing params"));
+        };
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let report = crate::handlers::with_overlay(state, engine, |ws, cp| {
+        refactor_hierarchy_report(ws, cp, &params)
+    })
+    .await?;
+    let mut result = crate::protocol::RefactorHierarchyResult {
+        mappings: report.mappings,
+        collisions: report.collisions.into_iter().map(|d| d.message).collect(),
+        applied: false,
+    };
+
+    if let Some(plan) = report.plan.filter(|_| !params.dry_run) {
+        crate::handlers::apply_edit_plan(client, state, plan).await?;
+        result.applied = true;
+    }
+
+    serde_json::to_value(result).map(Some).map_err(|e| Error {
+        code: ErrorCode::InternalError,
+        message: format!("Failed to serialize result: {}", e).into(),
+        data: None,
+    })
+}
+
+pub(crate) fn refactor_hierarchy_report(
+    ws: &dendrite_core::Workspace,
+    content_provider: &dyn dendrite_core::mutation::model::ContentProvider,
+    params: &crate::protocol::RefactorHierarchyParams,
+) -> Result<dendrite_core::mutation::hierarchy::HierarchyRefactorReport> {
+    ws.refactor_hierarchy(
+        content_provider,
+        &params.pattern,
+        &params.replacement,
+        params.scope.as_deref(),
+    )
+    .map_err(|errors| {
+        let messages: Vec<_> = errors.into_iter().map(|d| d.message).collect();
+        Error::invalid_params(messages.join(" "))
+    })
+}
+
 /// Handle "dendrite/resolveHierarchyEdits" command
 /// Arguments: [old_key, new_key]
 /// Returns: [[OldKey, NewKey], ...]
@@ -117,7 +164,10 @@ pub async fn handle_resolve_hierarchy_edits(
     let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
 
     // Dry Run
-    let plan = engine.rename_hierarchy(&old_key, &new_key);
+    let plan = crate::handlers::with_overlay(state, engine, |ws, cp| {
+        ws.rename_hierarchy(cp, &old_key, &new_key)
+    })
+    .await;
     let mut moves = Vec::new();
 
     if let Some(plan) = plan {
@@ -154,7 +204,7 @@ pub async fn handle_resolve_hierarchy_edits(
     Ok(Some(serde_json::to_value(moves).unwrap()))
 }
 
-fn parse_hierarchy_args(params: &ExecuteCommandParams) -> Result<(String, String)> {
+pub(crate) fn parse_hierarchy_args(params: &ExecuteCommandParams) -> Result<(String, String)> {
     if params.arguments.len() < 2 {
         return Err(Error::invalid_params(
             "Missing arguments: [old_key, new_key]",
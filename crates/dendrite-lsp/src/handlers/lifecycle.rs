@@ -1,3 +1,4 @@
+use crate::history::MutationHistory;
 use crate::state::GlobalState;
 use dendrite_core::{DendriteEngine, DendronModel, Workspace};
 use tower_lsp::jsonrpc::Result;
@@ -10,6 +11,7 @@ pub async fn handle_initialize(
     state: &GlobalState,
     params: InitializeParams,
 ) -> Result<InitializeResult> {
+    *state.client_capabilities.write().await = params.capabilities.clone();
     let root_uri = params.root_uri;
 
     if let Some(uri) = root_uri {
@@ -106,6 +108,13 @@ pub async fn handle_initialize(
 
             let mut engine_lock = state.engine.write().await;
             *engine_lock = Some(engine);
+
+            // Undo survives a restart
+            let limit = state.config.read().await.mutation_history_limit;
+            let mut history =
+                MutationHistory::load(MutationHistory::file_path(&root_path), &*state.fs);
+            history.truncate(limit);
+            *state.mutation_history.write().await = history;
         }
     } else {
         client
@@ -147,10 +156,35 @@ pub async fn handle_initialize(
                     "dendrite/workspaceAudit".to_string(),
                     "dendrite/deleteNote".to_string(),
                     "dendrite/getBacklinks".to_string(),
+                    "dendrite/renderNote".to_string(),
+                    "dendrite/search".to_string(),
+                    "dendrite/graphStats".to_string(),
+                    "dendrite/shortestPath".to_string(),
+                    "dendrite/neighborhood".to_string(),
+                    "dendrite/unlinkedMentions".to_string(),
+                    "dendrite/relatedNotes".to_string(),
+                    "dendrite/findDuplicates".to_string(),
+                    "dendrite/mergeNote".to_string(),
+                    "dendrite/mintDuplicateIds".to_string(),
+                    "dendrite/redoMutation".to_string(),
+                    "dendrite/listHistory".to_string(),
+                    "dendrite/previewEditPlan".to_string(),
+                    "dendrite/splitNoteByHeadings".to_string(),
+                    "dendrite/refactorHierarchy".to_string(),
+                    "dendrite/moveToVault".to_string(),
                 ],
                 work_done_progress_options: Default::default(),
             }),
             code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            document_formatting_provider: Some(OneOf::Left(true)),
+            document_range_formatting_provider: Some(OneOf::Left(true)),
+            diagnostic_provider: Some(DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                identifier: Some("dendrite".to_string()),
+                // Link diagnostics depend on the link targets
+                inter_file_dependencies: true,
+                workspace_diagnostics: true,
+                work_done_progress_options: Default::default(),
+            })),
             semantic_tokens_provider: Some(
                 SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
                     work_done_progress_options: WorkDoneProgressOptions::default(),
@@ -172,6 +206,8 @@ pub async fn handle_initialize(
                             },
                         }],
                     }),
+                    will_rename: Some(note_file_operation_options()),
+                    will_delete: Some(note_file_operation_options()),
                     ..Default::default()
                 }),
             }),
@@ -180,3 +216,22 @@ pub async fn handle_initialize(
         ..Default::default()
     })
 }
+
+/// Notes, and folders that may contain notes
+fn note_file_operation_options() -> FileOperationRegistrationOptions {
+    let filter = |glob: &str, matches| FileOperationFilter {
+        scheme: Some("file".to_string()),
+        pattern: FileOperationPattern {
+            glob: glob.to_string(),
+            matches: Some(matches),
+            options: None,
+        },
+    };
+
+    FileOperationRegistrationOptions {
+        filters: vec![
+            filter("**/*.md", FileOperationPatternKind::File),
+            filter("**", FileOperationPatternKind::Folder),
+        ],
+    }
+}
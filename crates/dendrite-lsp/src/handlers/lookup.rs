@@ -1,6 +1,11 @@
 use crate::conversion::path_to_uri;
-use crate::protocol::{GetBacklinksResult, ListNotesParams, ListNotesResult, NoteSummary};
+use crate::overlay::OverlayContentProvider;
+use crate::protocol::{
+    BacklinkGroup, BacklinkReference, GetBacklinksResult, ListNotesParams, ListNotesResult,
+    NoteSummary,
+};
 use crate::state::GlobalState;
+use dendrite_core::analysis::backlinks::{group_by_anchor, BacklinkContext};
 use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
 use tower_lsp::lsp_types::{ExecuteCommandParams, MessageType};
 use tower_lsp::Client;
@@ -163,7 +168,7 @@ pub async fn handle_get_note_key(
 }
 
 pub async fn handle_get_backlinks_command(
-    _state: &GlobalState,
+    state: &GlobalState,
     params: ExecuteCommandParams,
 ) -> Result<Option<serde_json::Value>> {
     let params: crate::protocol::GetBacklinksParams =
@@ -175,7 +180,7 @@ pub async fn handle_get_backlinks_command(
         };
     let note_key = params.note_key;
 
-    let engine_guard = _state.engine.read().await;
+    let engine_guard = state.engine.read().await;
     let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
 
     let backlinks = engine
@@ -191,7 +196,39 @@ pub async fn handle_get_backlinks_command(
         })
         .collect::<Vec<_>>();
 
-    let result = GetBacklinksResult { backlinks };
+    let contexts = {
+        let documents = state.document_cache.read().await;
+        let provider = OverlayContentProvider::new(&documents, &*engine.fs);
+        engine
+            .workspace
+            .backlinks_with_context(&provider, &note_key)
+    };
+    let to_reference = |backlink: BacklinkContext| BacklinkReference {
+        source_key: backlink.source_key,
+        uri: path_to_uri(&backlink.source_path).map(|uri| uri.to_string()),
+        title: backlink.source_title,
+        range: crate::conversion::text_range_to_lsp_range(backlink.range),
+        anchor: backlink.anchor,
+        context: backlink.context,
+        heading_path: backlink.heading_path,
+    };
+
+    let groups = params.group_by_anchor.then(|| {
+        group_by_anchor(contexts.clone())
+            .into_iter()
+            .map(|group| BacklinkGroup {
+                anchor: group.anchor,
+                references: group.backlinks.into_iter().map(to_reference).collect(),
+            })
+            .collect()
+    });
+    let references = contexts.into_iter().map(to_reference).collect();
+
+    let result = GetBacklinksResult {
+        backlinks,
+        references,
+        groups,
+    };
     serde_json::to_value(result).map(Some).map_err(|e| Error {
         code: tower_lsp::jsonrpc::ErrorCode::InternalError,
         message: format!("Failed to serialize results: {}", e).into(),
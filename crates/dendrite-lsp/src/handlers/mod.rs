@@ -1,6 +1,9 @@
 pub mod analysis;
 mod completion;
 mod configuration;
+mod file_operations;
+mod formatting;
+mod graph;
 mod hierarchy;
 mod lifecycle;
 pub mod lookup;
@@ -9,11 +12,16 @@ pub mod mutation;
 mod navigation;
 mod notifications;
 pub mod rename;
+mod render;
+mod search;
 mod semantic_tokens;
 
 pub use analysis::*;
 pub use completion::*;
 pub use configuration::*;
+pub use file_operations::*;
+pub use formatting::*;
+pub use graph::*;
 pub use hierarchy::*;
 pub use lifecycle::*;
 pub use lookup::*;
@@ -21,4 +29,6 @@ pub use metadata::*;
 pub use mutation::*;
 pub use navigation::*;
 pub use notifications::*;
+pub use render::*;
+pub use search::*;
 pub use semantic_tokens::*;
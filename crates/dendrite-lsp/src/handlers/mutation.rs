@@ -1,14 +1,72 @@
+use crate::overlay::OverlayContentProvider;
 use crate::state::GlobalState;
 use dendrite_core::model::TextRange;
-use dendrite_core::mutation::model::EditPlan;
+use dendrite_core::mutation::model::{ContentProvider, DiagnosticSeverity, EditPlan};
+use dendrite_core::workspace::{DendriteEngine, Workspace};
 
 use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::Client;
 
-/// Helper to apply EditPlan via WorkspaceEdit
-pub(crate) async fn apply_edit_plan(client: &Client, plan: EditPlan) -> Result<()> {
-    let workspace_edit = crate::conversion::edit_plan_to_workspace_edit(plan);
+/// Compute something (usually a plan) against the open documents, falling back to
+/// the file system: the same content plans are validated against when applied.
+pub(crate) async fn with_overlay<T>(
+    state: &GlobalState,
+    engine: &DendriteEngine,
+    f: impl FnOnce(&Workspace, &dyn ContentProvider) -> T,
+) -> T {
+    let documents = state.document_cache.read().await;
+    let overlay = OverlayContentProvider::new(&documents, &*engine.fs);
+    f(&engine.workspace, &overlay)
+}
+
+/// Check a plan's preconditions against the open documents and the file system.
+pub(crate) async fn validate_edit_plan(state: &GlobalState, plan: &EditPlan) -> Result<()> {
+    let documents = state.document_cache.read().await;
+    let overlay = OverlayContentProvider::new(&documents, &*state.fs);
+    plan.validate(&*state.fs, &overlay).map_err(|failures| {
+        let messages: Vec<_> = failures.into_iter().map(|d| d.message).collect();
+        Error {
+            code: ErrorCode::ContentModified,
+            message: format!("Stale edit plan: {}", messages.join(" ")).into(),
+            data: None,
+        }
+    })
+}
+
+/// Helper to apply EditPlan via WorkspaceEdit, after checking it is not stale.
+/// Reversible plans are recorded in the mutation history, and the plan's
+/// warnings are shown once it is applied.
+pub(crate) async fn apply_edit_plan(
+    client: &Client,
+    state: &GlobalState,
+    plan: EditPlan,
+) -> Result<()> {
+    if let Err(error) = validate_edit_plan(state, &plan).await {
+        client
+            .show_message(MessageType::ERROR, error.message.clone())
+            .await;
+        return Err(error);
+    }
+    let undo = crate::handlers::file_operations::undo_plan(state, &plan).await;
+
+    send_edit_plan(client, state, plan.clone()).await?;
+    if let Some(undo) = undo {
+        crate::handlers::file_operations::push_history(state, &plan, undo).await;
+    }
+    for diagnostic in &plan.diagnostics {
+        if diagnostic.severity == DiagnosticSeverity::Warning {
+            client
+                .show_message(MessageType::WARNING, diagnostic.message.clone())
+                .await;
+        }
+    }
+    Ok(())
+}
+
+async fn send_edit_plan(client: &Client, state: &GlobalState, plan: EditPlan) -> Result<()> {
+    let change_annotations = state.supports_change_annotations().await;
+    let workspace_edit = crate::conversion::edit_plan_to_workspace_edit(plan, change_annotations);
 
     client
         .apply_edit(workspace_edit)
@@ -24,11 +82,10 @@ pub(crate) async fn apply_edit_plan(client: &Client, plan: EditPlan) -> Result<(
 
 pub async fn handle_code_action(
     _client: &Client,
-    _state: &GlobalState,
+    state: &GlobalState,
     params: CodeActionParams,
 ) -> Result<Option<Vec<CodeActionOrCommand>>> {
-    // Only support RefactorExtract for now
-    let mut actions = Vec::new();
+    let mut actions = link_mention_actions(state, &params).await;
 
     // Check if there is a selection (non-empty range)
     let range = params.range;
@@ -53,6 +110,55 @@ pub async fn handle_code_action(
     Ok(Some(actions))
 }
 
+/// "Link to note" quick fixes for unlinked mentions touching the requested range
+async fn link_mention_actions(
+    state: &GlobalState,
+    params: &CodeActionParams,
+) -> Vec<CodeActionOrCommand> {
+    let uri = &params.text_document.uri;
+    let Ok(path) = uri.to_file_path() else {
+        return Vec::new();
+    };
+
+    let engine_guard = state.engine.read().await;
+    let Some(engine) = engine_guard.as_ref() else {
+        return Vec::new();
+    };
+    let ws = &engine.workspace;
+
+    let mentions = {
+        let documents = state.document_cache.read().await;
+        let provider = OverlayContentProvider::new(&documents, &*engine.fs);
+        ws.unlinked_mentions(&provider, Some(std::slice::from_ref(&path)))
+    };
+
+    mentions
+        .into_iter()
+        .filter_map(|mention| {
+            let range = crate::conversion::text_range_to_lsp_range(mention.range);
+            if range.end < params.range.start || params.range.end < range.start {
+                return None;
+            }
+            let alias = (mention.text != mention.target).then_some(mention.text.as_str());
+            let new_text = ws.format_wikilink(&mention.target, alias);
+            let edit = WorkspaceEdit {
+                changes: Some(
+                    [(uri.clone(), vec![TextEdit { range, new_text }])]
+                        .into_iter()
+                        .collect(),
+                ),
+                ..Default::default()
+            };
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Link to '{}'", mention.target),
+                kind: Some(CodeActionKind::QUICKFIX),
+                edit: Some(edit),
+                ..Default::default()
+            }))
+        })
+        .collect()
+}
+
 pub async fn handle_create_note(
     client: &Client,
     state: &GlobalState,
@@ -97,7 +203,7 @@ pub async fn handle_create_note(
         });
 
         // Reuse existing mutation handler to apply EditPlan
-        apply_edit_plan(client, plan).await?;
+        apply_edit_plan(client, state, plan).await?;
         Ok(Some(serde_json::to_value(target_uri).unwrap()))
     } else {
         Ok(None)
@@ -154,16 +260,61 @@ pub async fn handle_split_note_command(
         },
     };
 
-    let plan = engine.split_note(&source_path, text_range, &new_note_name);
+    let plan = with_overlay(state, engine, |ws, cp| {
+        ws.split_note(cp, &source_path, text_range, &new_note_name)
+    })
+    .await;
 
     if let Some(plan) = plan {
-        apply_edit_plan(client, plan).await?;
+        apply_edit_plan(client, state, plan).await?;
         Ok(Some(serde_json::Value::Bool(true)))
     } else {
         Ok(Some(serde_json::Value::Bool(false)))
     }
 }
 
+/// Handle "dendrite/splitNoteByHeadings" command
+/// Arguments: [{ note_key, level?, replacement? }]
+/// Moves each section at the heading level into a child note `note_key.<section>`.
+pub async fn handle_split_note_by_headings_command(
+    client: &Client,
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: crate::protocol::SplitNoteByHeadingsParams =
+        if let Some(first_arg) = params.arguments.first() {
+            serde_json::from_value(first_arg.clone())
+                .map_err(|_| Error::invalid_params("Invalid params"))?
+        } else {
+            return Err(Error::invalid_params("Missing params"));
+        };
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let plan = with_overlay(state, engine, |ws, cp| {
+        ws.split_note_by_headings(cp, &params.note_key, &split_options(&params))
+    })
+    .await;
+
+    if let Some(plan) = plan {
+        apply_edit_plan(client, state, plan).await?;
+        Ok(Some(serde_json::Value::Bool(true)))
+    } else {
+        Ok(Some(serde_json::Value::Bool(false)))
+    }
+}
+
+fn split_options(
+    params: &crate::protocol::SplitNoteByHeadingsParams,
+) -> dendrite_core::mutation::split::SplitByHeadingOptions {
+    let defaults = dendrite_core::mutation::split::SplitByHeadingOptions::default();
+    dendrite_core::mutation::split::SplitByHeadingOptions {
+        level: params.level.unwrap_or(defaults.level),
+        replacement: params.replacement,
+    }
+}
+
 pub async fn handle_delete_note_command(
     client: &Client,
     state: &GlobalState,
@@ -181,55 +332,322 @@ pub async fn handle_delete_note_command(
     let engine_guard = state.engine.read().await;
     let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
 
-    let plan = engine.delete_note(&note_key);
+    let plan = with_overlay(state, engine, |ws, cp| ws.delete_note(cp, &note_key)).await;
 
     if let Some(plan) = plan {
-        apply_edit_plan(client, plan).await?;
+        apply_edit_plan(client, state, plan).await?;
         Ok(Some(serde_json::Value::Bool(true)))
     } else {
         Ok(Some(serde_json::Value::Bool(false)))
     }
 }
 
-pub async fn handle_undo_mutation(client: &tower_lsp::Client, state: &GlobalState) -> Result<()> {
-    let mut history = state.mutation_history.write().await;
-
-    if let Some(plan) = history.pop_back() {
-        // 1. Invert the plan
-        let engine_guard = state.engine.read().await;
-        // Map Vault (if present) to dyn ContentProvider
-        let cp = engine_guard
-            .as_ref()
-            .map(|v| v as &dyn dendrite_core::mutation::model::ContentProvider);
-        let inverted_plan = plan.invert(cp);
-
-        // 2. Convert to WorkspaceEdit
-        let edit = crate::conversion::edit_plan_to_workspace_edit(inverted_plan);
-
-        // 3. Ask client to apply the edit
-        match client.apply_edit(edit).await {
-            Ok(response) if response.applied => {
-                client
-                    .show_message(MessageType::INFO, "Mutation undone successfully.")
-                    .await;
-                Ok(())
-            }
-            Ok(_) => Err(Error {
-                code: ErrorCode::InternalError,
-                message: "Undo edit was rejected by client or failed to apply.".into(),
-                data: None,
-            }),
-            Err(e) => Err(Error {
-                code: ErrorCode::InternalError,
-                message: format!("Failed to apply undo edit: {}", e).into(),
-                data: None,
-            }),
-        }
+/// Handle "dendrite/moveToVault" command
+/// Arguments: [{ note_key, vault, include_descendants? }]
+pub async fn handle_move_to_vault_command(
+    client: &Client,
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: crate::protocol::MoveToVaultParams =
+        if let Some(first_arg) = params.arguments.first() {
+            serde_json::from_value(first_arg.clone())
+                .map_err(|_| Error::invalid_params("Invalid params"))?
+        } else {
+            return Err(Error::invalid_params("Missing params"));
+        };
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let plan = with_overlay(state, engine, |ws, cp| {
+        ws.move_to_vault(
+            cp,
+            &params.note_key,
+            params.include_descendants,
+            &params.vault,
+        )
+    })
+    .await;
+
+    if let Some(plan) = plan {
+        apply_edit_plan(client, state, plan).await?;
+        Ok(Some(serde_json::Value::Bool(true)))
+    } else {
+        Ok(Some(serde_json::Value::Bool(false)))
+    }
+}
+
+/// Handle "dendrite/mergeNote" command
+/// Arguments: [{ source_key, target_key, heading?, frontmatter? }]
+/// Inserts the source into the target, retargets its backlinks and deletes it.
+pub async fn handle_merge_note_command(
+    client: &Client,
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: crate::protocol::MergeNoteParams = if let Some(first_arg) = params.arguments.first()
+    {
+        serde_json::from_value(first_arg.clone())
+            .map_err(|_| Error::invalid_params("Invalid params"))?
     } else {
-        // No history to undo
+        return Err(Error::invalid_params("Missing params"));
+    };
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let plan = with_overlay(state, engine, |ws, cp| {
+        ws.merge_note(
+            cp,
+            &params.source_key,
+            &params.target_key,
+            &merge_options(&params),
+        )
+    })
+    .await;
+
+    if let Some(plan) = plan {
+        apply_edit_plan(client, state, plan).await?;
+        Ok(Some(serde_json::Value::Bool(true)))
+    } else {
+        Ok(Some(serde_json::Value::Bool(false)))
+    }
+}
+
+fn merge_options(
+    params: &crate::protocol::MergeNoteParams,
+) -> dendrite_core::mutation::merge::MergeOptions {
+    dendrite_core::mutation::merge::MergeOptions {
+        heading: params.heading.clone(),
+        frontmatter: params.frontmatter,
+    }
+}
+
+/// Handle "dendrite/mintDuplicateIds" command
+/// Gives notes sharing a frontmatter id with another note a new one (the audit's fix).
+pub async fn handle_mint_duplicate_ids_command(
+    client: &Client,
+    state: &GlobalState,
+) -> Result<Option<serde_json::Value>> {
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let plan = with_overlay(state, engine, |ws, cp| ws.mint_duplicate_ids(cp)).await;
+
+    if let Some(plan) = plan {
+        apply_edit_plan(client, state, plan).await?;
+        Ok(Some(serde_json::Value::Bool(true)))
+    } else {
+        Ok(Some(serde_json::Value::Bool(false)))
+    }
+}
+
+pub async fn handle_undo_mutation(client: &tower_lsp::Client, state: &GlobalState) -> Result<()> {
+    let Some(entry) = state.mutation_history.write().await.pop_undo() else {
         client
             .show_message(MessageType::INFO, "No mutation history to undo.")
             .await;
-        Ok(())
+        return Ok(());
+    };
+
+    // The files must still be exactly as the mutation left them
+    let result = match validate_edit_plan(state, &entry.undo).await {
+        Ok(()) => send_edit_plan(client, state, entry.undo.clone()).await,
+        Err(error) => Err(error),
+    };
+    finish_history_step(client, state, entry, result, true).await
+}
+
+pub async fn handle_redo_mutation(client: &tower_lsp::Client, state: &GlobalState) -> Result<()> {
+    let Some(entry) = state.mutation_history.write().await.pop_redo() else {
+        client
+            .show_message(MessageType::INFO, "No undone mutation to redo.")
+            .await;
+        return Ok(());
+    };
+
+    // The files must still be exactly as the undo left them
+    let result = match validate_edit_plan(state, &entry.plan).await {
+        Ok(()) => send_edit_plan(client, state, entry.plan.clone()).await,
+        Err(error) => Err(error),
+    };
+    finish_history_step(client, state, entry, result, false).await
+}
+
+/// Move the entry to the other stack if the step succeeded, or back where it came from.
+async fn finish_history_step(
+    client: &tower_lsp::Client,
+    state: &GlobalState,
+    entry: crate::history::HistoryEntry,
+    result: Result<()>,
+    undo: bool,
+) -> Result<()> {
+    let (message_type, message) = match (&result, undo) {
+        (Ok(()), true) => (MessageType::INFO, format!("Undone: {}", entry.description)),
+        (Ok(()), false) => (MessageType::INFO, format!("Redone: {}", entry.description)),
+        (Err(error), true) => (
+            MessageType::ERROR,
+            format!("Cannot undo '{}': {}", entry.description, error.message),
+        ),
+        (Err(error), false) => (
+            MessageType::ERROR,
+            format!("Cannot redo '{}': {}", entry.description, error.message),
+        ),
+    };
+
+    {
+        let mut history = state.mutation_history.write().await;
+        if result.is_ok() == undo {
+            history.push_redo(entry);
+        } else {
+            history.push_undo(entry);
+        }
+        if let Err(e) = history.save(&*state.fs) {
+            eprintln!("❌ Failed to save mutation history: {}", e);
+        }
+    }
+
+    client.show_message(message_type, message).await;
+    result
+}
+
+/// Handle "dendrite/listHistory" command
+/// Returns the undoable and redoable mutations, most recent first.
+pub async fn handle_list_history_command(state: &GlobalState) -> Result<Option<serde_json::Value>> {
+    let history = state.mutation_history.read().await;
+    let summary = |entry: &crate::history::HistoryEntry| crate::protocol::HistoryEntrySummary {
+        id: entry.id,
+        timestamp: entry.timestamp,
+        description: entry.description.clone(),
+        mutation_kind: entry.plan.mutation_kind.clone(),
+    };
+    let result = crate::protocol::ListHistoryResult {
+        undo: history.undo_entries().map(summary).collect(),
+        redo: history.redo_entries().map(summary).collect(),
+    };
+    serde_json::to_value(result).map(Some).map_err(|e| Error {
+        code: ErrorCode::InternalError,
+        message: format!("Failed to serialize result: {}", e).into(),
+        data: None,
+    })
+}
+
+/// Handle "dendrite/previewEditPlan" command
+/// Arguments: [{ command, arguments }]
+/// Returns the unified diff the mutation command would apply, without applying it.
+pub async fn handle_preview_edit_plan_command(
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: crate::protocol::PreviewEditPlanParams =
+        if let Some(first_arg) = params.arguments.first() {
+            serde_json::from_value(first_arg.clone())
+                .map_err(|_| Error::invalid_params("Invalid params"))?
+        } else {
+            return Err(Error::invalid_params("Missing params"));
+        };
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let documents = state.document_cache.read().await;
+    let overlay = OverlayContentProvider::new(&documents, &*engine.fs);
+    let Some(plan) = plan_for_command(&engine.workspace, &overlay, params)? else {
+        return Ok(None);
+    };
+
+    let preview = engine
+        .workspace
+        .preview_edit_plan(&overlay, &plan)
+        .map_err(|failures| {
+            let messages: Vec<_> = failures.into_iter().map(|d| d.message).collect();
+            Error {
+                code: ErrorCode::ContentModified,
+                message: format!("Cannot preview edit plan: {}", messages.join(" ")).into(),
+                data: None,
+            }
+        })?;
+
+    serde_json::to_value(preview).map(Some).map_err(|e| Error {
+        code: ErrorCode::InternalError,
+        message: format!("Failed to serialize result: {}", e).into(),
+        data: None,
+    })
+}
+
+/// The plan a mutation command would apply with the given arguments.
+fn plan_for_command(
+    ws: &Workspace,
+    content_provider: &dyn ContentProvider,
+    params: crate::protocol::PreviewEditPlanParams,
+) -> Result<Option<EditPlan>> {
+    let first_arg = |arguments: &[serde_json::Value]| -> Result<serde_json::Value> {
+        arguments
+            .first()
+            .cloned()
+            .ok_or_else(|| Error::invalid_params("Missing params"))
+    };
+
+    match params.command.as_str() {
+        "dendrite/reorganizeHierarchy" => {
+            let (old_key, new_key) =
+                crate::handlers::parse_hierarchy_args(&ExecuteCommandParams {
+                    command: params.command.clone(),
+                    arguments: params.arguments,
+                    work_done_progress_params: Default::default(),
+                })?;
+            Ok(ws.rename_hierarchy(content_provider, &old_key, &new_key))
+        }
+        "dendrite/splitNoteByHeadings" => {
+            let args: crate::protocol::SplitNoteByHeadingsParams =
+                serde_json::from_value(first_arg(&params.arguments)?)
+                    .map_err(|_| Error::invalid_params("Invalid params"))?;
+            Ok(ws.split_note_by_headings(content_provider, &args.note_key, &split_options(&args)))
+        }
+        "dendrite/refactorHierarchy" => {
+            let args: crate::protocol::RefactorHierarchyParams =
+                serde_json::from_value(first_arg(&params.arguments)?)
+                    .map_err(|_| Error::invalid_params("Invalid params"))?;
+            let report = crate::handlers::refactor_hierarchy_report(ws, content_provider, &args)?;
+            if !report.collisions.is_empty() {
+                let messages: Vec<_> = report.collisions.into_iter().map(|d| d.message).collect();
+                return Err(Error::invalid_params(messages.join(" ")));
+            }
+            Ok(report.plan)
+        }
+        "dendrite/moveToVault" => {
+            let args: crate::protocol::MoveToVaultParams =
+                serde_json::from_value(first_arg(&params.arguments)?)
+                    .map_err(|_| Error::invalid_params("Invalid params"))?;
+            Ok(ws.move_to_vault(
+                content_provider,
+                &args.note_key,
+                args.include_descendants,
+                &args.vault,
+            ))
+        }
+        "dendrite/deleteNote" => {
+            let args: crate::protocol::DeleteNoteParams =
+                serde_json::from_value(first_arg(&params.arguments)?)
+                    .map_err(|_| Error::invalid_params("Invalid params"))?;
+            Ok(ws.delete_note(content_provider, &args.note_key))
+        }
+        "dendrite/mergeNote" => {
+            let args: crate::protocol::MergeNoteParams =
+                serde_json::from_value(first_arg(&params.arguments)?)
+                    .map_err(|_| Error::invalid_params("Invalid params"))?;
+            Ok(ws.merge_note(
+                content_provider,
+                &args.source_key,
+                &args.target_key,
+                &merge_options(&args),
+            ))
+        }
+        "dendrite/mintDuplicateIds" => Ok(ws.mint_duplicate_ids(content_provider)),
+        other => Err(Error::invalid_params(format!(
+            "Cannot preview command '{}'",
+            other
+        ))),
     }
 }
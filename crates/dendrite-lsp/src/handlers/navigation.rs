@@ -1,5 +1,7 @@
 use crate::conversion::{lsp_position_to_point, path_to_uri, text_range_to_lsp_range};
+use crate::overlay::OverlayContentProvider;
 use crate::state::GlobalState;
+use dendrite_core::render::TransclusionOptions;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::Client;
@@ -115,49 +117,25 @@ pub async fn handle_hover(
         return Ok(None);
     };
 
-    // Get the target note for hover information
-    let target_path = ws.get_link_target_path(link);
-    let target_node = target_path.as_ref().and_then(|path| ws.note_by_path(path));
-
-    let target_info = if let (Some(path), Some(note)) = (&target_path, target_node) {
-        // Read the file accurately
-        match std::fs::read_to_string(path) {
-            Ok(content) => {
-                // Determine the starting point: Anchor/Block range or Content Offset
-                let lines: Vec<&str> = content.lines().collect();
-                let start_line = if let Some(range) = ws.resolve_link_anchor(link) {
-                    range.start.line
-                } else {
-                    // Find the line where note.content_offset falls
-                    let mut current_offset = 0u32;
-                    let mut found_line = 0u32;
-                    for (i, line) in lines.iter().enumerate() {
-                        if current_offset >= note.content_offset {
-                            found_line = i as u32;
-                            break;
-                        }
-                        current_offset += (line.len() + 1) as u32; // +1 for newline
-                    }
-                    found_line
-                };
-
-                let preview: String = lines
-                    .iter()
-                    .skip(start_line as usize)
-                    .take(10)
-                    .cloned()
-                    .collect::<Vec<&str>>()
-                    .join("\n");
-
-                let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("md");
-                format!("```{}\n{}\n```", extension, preview.trim())
-            }
-            Err(_) => format!("Target: {:?}", path),
+    let options = {
+        let config = state.config.read().await;
+        TransclusionOptions {
+            max_depth: config.hover.max_embed_depth,
+            max_length: config.hover.max_length,
         }
-    } else if let Some(path) = &target_path {
-        format!("Target: {:?}", path)
-    } else {
-        "Target: (not found)".to_string()
+    };
+
+    // Render the linked content, preferring unsaved buffers over disk
+    let preview = {
+        let documents = state.document_cache.read().await;
+        let provider = OverlayContentProvider::new(&documents, &*engine.fs);
+        ws.link_preview(&provider, link, &options)
+    };
+
+    let target_info = match (preview, ws.get_link_target_path(link)) {
+        (Some(preview), _) => preview,
+        (None, Some(path)) => format!("Target: {:?}", path),
+        (None, None) => "Target: (not found)".to_string(),
     };
 
     // Convert link range to LSP range for hover highlighting
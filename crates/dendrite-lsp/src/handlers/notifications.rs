@@ -48,50 +48,87 @@ pub async fn handle_did_change(state: &GlobalState, params: DidChangeTextDocumen
 }
 
 /// Handle "workspace/didChangeWatchedFiles" notification
+///
+/// A batch may contain files moved outside the editor, reported as a delete plus
+/// a create; the engine pairs them up so the notes keep their identity.
 pub async fn handle_did_change_watched_files(
     client: &Client,
     state: &GlobalState,
     params: DidChangeWatchedFilesParams,
 ) {
-    let mut engine_lock = state.engine.write().await;
-    let mut changed = false;
-
-    if let Some(v) = &mut *engine_lock {
+    let mut deleted = Vec::new();
+    let mut upserted = Vec::new();
+    {
+        let mut cache = state.document_cache.write().await;
         for change in params.changes {
             let uri = change.uri.clone();
             if let Ok(path) = uri.to_file_path() {
                 match change.typ {
                     FileChangeType::CREATED | FileChangeType::CHANGED => {
                         if let Ok(content) = state.fs.read_to_string(&path) {
-                            // Update cache
-                            {
-                                let mut cache = state.document_cache.write().await;
-                                cache.insert(uri, content.clone());
-                            }
-                            v.update_content(path, &content);
-                            changed = true;
+                            cache.insert(uri, content.clone());
+                            upserted.push((path, content));
                         }
                     }
                     FileChangeType::DELETED => {
-                        // Remove from cache
-                        {
-                            let mut cache = state.document_cache.write().await;
-                            cache.remove(&uri);
-                        }
-                        v.delete_file(&path);
-                        changed = true;
+                        cache.remove(&uri);
+                        deleted.push(path);
                     }
                     _ => {}
                 }
             }
         }
     }
+    if deleted.is_empty() && upserted.is_empty() {
+        return;
+    }
 
-    if changed {
-        let _ = state.dirty_signal.send(());
-        client
-            .send_notification::<HierarchyChangedNotification>(serde_json::Value::Null)
-            .await;
+    let renames = {
+        let mut engine_lock = state.engine.write().await;
+        let Some(v) = &mut *engine_lock else {
+            return;
+        };
+        v.sync_file_changes(&deleted, &upserted)
+    };
+
+    let _ = state.dirty_signal.send(());
+    client
+        .send_notification::<HierarchyChangedNotification>(serde_json::Value::Null)
+        .await;
+
+    if renames.is_empty() {
+        return;
+    }
+    client
+        .log_message(
+            MessageType::INFO,
+            format!(
+                "Detected {} note(s) renamed outside the editor",
+                renames.len()
+            ),
+        )
+        .await;
+
+    let update_links = {
+        let config = state.config.read().await;
+        config.files.update_links_on_external_rename
+    };
+    if !update_links {
+        return;
+    }
+    // Computed from the open buffers, like every other plan the LSP applies
+    let plan = {
+        let engine_lock = state.engine.read().await;
+        let Some(engine) = engine_lock.as_ref() else {
+            return;
+        };
+        crate::handlers::with_overlay(state, engine, |ws, cp| {
+            ws.external_rename_edits(cp, &renames)
+        })
+        .await
+    };
+    if let Some(plan) = plan {
+        let _ = crate::handlers::apply_edit_plan(client, state, plan).await;
     }
 }
 
@@ -103,6 +140,8 @@ impl tower_lsp::lsp_types::notification::Notification for HierarchyChangedNotifi
 }
 
 /// Handle "workspace/didRenameFiles" notification
+///
+/// Links were already updated via "workspace/willRenameFiles"; only the index is refreshed here.
 pub async fn handle_did_rename_files(
     client: &Client,
     state: &GlobalState,
@@ -127,41 +166,14 @@ pub async fn handle_did_rename_files(
                             cache.remove(&old_url);
                         }
 
-                        // 1. Update internal index
-                        v.rename_file(old_path.clone(), new_path.clone(), &content);
-
-                        // 2. Generate mutation plan for the move
-                        if let Some(mut plan) = v.move_note(&old_path, new_path) {
-                            // 3. Filter out the RenameFile operation (it's already done by the user in the IDE)
-                            plan.edits.retain(|group| {
-                                group.changes.iter().all(|change| {
-                                    !matches!(
-                                        change,
-                                        dendrite_core::mutation::model::Change::ResourceOp(_)
-                                    )
-                                })
-                            });
-
-                            if !plan.edits.is_empty() {
-                                // 4. Convert and apply edits
-                                let workspace_edit =
-                                    crate::conversion::edit_plan_to_workspace_edit(plan.clone());
-                                let _ = client.apply_edit(workspace_edit).await;
-
-                                // Store in history for undo
-                                if plan.reversible {
-                                    let mut history = state.mutation_history.write().await;
-                                    history.push_back(plan);
-                                    if history.len() > 5 {
-                                        history.pop_front();
-                                    }
-                                }
-                            }
-                        }
+                        v.rename_file(old_path, new_path, &content);
                     }
                 }
             }
         }
         let _ = state.dirty_signal.send(());
+        client
+            .send_notification::<HierarchyChangedNotification>(serde_json::Value::Null)
+            .await;
     }
 }
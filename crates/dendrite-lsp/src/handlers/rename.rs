@@ -31,28 +31,23 @@ pub async fn handle_rename(
         None => return Ok(None),
     };
 
-    // Calculate edits using Vault API
-    // We use match instead of ? to handle Option->Result correctly
+    // Calculate edits against the open documents, as they are validated
     let plan = match &*engine {
-        Some(v) => v.rename_note(&old_key, &new_name),
+        Some(v) => {
+            crate::handlers::with_overlay(state, v, |ws, cp| {
+                ws.rename_note(cp, &old_key, &new_name)
+            })
+            .await
+        }
         None => return Ok(None),
     };
 
     match plan {
         Some(p) => {
-            if p.reversible {
-                let limit = {
-                    let config = state.config.read().await;
-                    config.mutation_history_limit
-                };
-                let mut history = state.mutation_history.write().await;
-                history.push_back(p.clone());
-                // Limit history size based on config
-                while history.len() > limit {
-                    history.pop_front();
-                }
-            }
-            Ok(Some(edit_plan_to_workspace_edit(p)))
+            // Not recorded for undo: the client may still decline the edit
+            crate::handlers::validate_edit_plan(state, &p).await?;
+            let change_annotations = state.supports_change_annotations().await;
+            Ok(Some(edit_plan_to_workspace_edit(p, change_annotations)))
         }
         None => Ok(None),
     }
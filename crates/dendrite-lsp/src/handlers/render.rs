@@ -0,0 +1,68 @@
+use crate::overlay::OverlayContentProvider;
+use crate::protocol::{RenderNoteParams, RenderNoteResult};
+use crate::state::GlobalState;
+use dendrite_core::render::RenderOptions;
+use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
+use tower_lsp::lsp_types::{ExecuteCommandParams, Url};
+
+/// Handle "dendrite/renderNote" command
+/// Arguments: [{ uri, frontmatter? }]
+/// Returns: { html }
+pub async fn handle_render_note_command(
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: RenderNoteParams = if let Some(first_arg) = params.arguments.first() {
+        serde_json::from_value(first_arg.clone())
+            .map_err(|_| Error::invalid_params("Invalid params"))?
+    } else {
+        return Err(Error::invalid_params("Missing params"));
+    };
+
+    let path = Url::parse(&params.uri)
+        .ok()
+        .and_then(|u| u.to_file_path().ok())
+        .ok_or_else(|| Error::invalid_params("URI is not a file path"))?;
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+    let ws = &engine.workspace;
+
+    let key = ws
+        .note_by_path(&path)
+        .and_then(|note| ws.key_of_note(note))
+        .ok_or_else(|| Error {
+            code: ErrorCode::InvalidParams,
+            message: format!("Note not found: {}", params.uri).into(),
+            data: None,
+        })?;
+
+    let options = {
+        let config = state.config.read().await;
+        RenderOptions {
+            frontmatter: params.frontmatter,
+            max_embed_depth: config.hover.max_embed_depth,
+        }
+    };
+
+    let to_uri = |p: &std::path::Path| {
+        Url::from_file_path(p)
+            .map(|u| u.to_string())
+            .unwrap_or_else(|_| p.to_string_lossy().to_string())
+    };
+
+    let html = {
+        let documents = state.document_cache.read().await;
+        let provider = OverlayContentProvider::new(&documents, &*engine.fs);
+        ws.render_note_html(&provider, &key, &options, &to_uri)
+    }
+    .ok_or_else(Error::internal_error)?;
+
+    serde_json::to_value(RenderNoteResult { html })
+        .map(Some)
+        .map_err(|e| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to serialize result: {}", e).into(),
+            data: None,
+        })
+}
@@ -0,0 +1,65 @@
+use crate::conversion::{path_to_uri, text_range_to_lsp_range};
+use crate::overlay::OverlayContentProvider;
+use crate::protocol::{SearchHitSummary, SearchParams, SearchResult};
+use crate::state::GlobalState;
+use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
+use tower_lsp::lsp_types::ExecuteCommandParams;
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Handle "dendrite/search" command
+/// Arguments: [{ query, limit? }]
+/// Returns: { hits: [{ key, uri, title, vault_name, score, matches, snippet }] }
+pub async fn handle_search_command(
+    state: &GlobalState,
+    params: ExecuteCommandParams,
+) -> Result<Option<serde_json::Value>> {
+    let params: SearchParams = if let Some(first_arg) = params.arguments.first() {
+        serde_json::from_value(first_arg.clone())
+            .map_err(|_| Error::invalid_params("Invalid params"))?
+    } else {
+        return Err(Error::invalid_params("Missing params"));
+    };
+
+    let engine_guard = state.engine.read().await;
+    let engine = engine_guard.as_ref().ok_or_else(Error::internal_error)?;
+
+    let hits = {
+        let documents = state.document_cache.read().await;
+        let provider = OverlayContentProvider::new(&documents, &*engine.fs);
+        engine.workspace.search(
+            &provider,
+            &params.query,
+            params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        )
+    };
+
+    let hits = hits
+        .into_iter()
+        .map(|hit| SearchHitSummary {
+            uri: hit
+                .path
+                .as_ref()
+                .and_then(path_to_uri)
+                .map(|uri| uri.to_string()),
+            key: hit.key,
+            title: hit.title,
+            vault_name: hit.vault_name,
+            score: hit.score,
+            matches: hit
+                .matches
+                .into_iter()
+                .map(text_range_to_lsp_range)
+                .collect(),
+            snippet: hit.snippet,
+        })
+        .collect();
+
+    serde_json::to_value(SearchResult { hits })
+        .map(Some)
+        .map_err(|e| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to serialize result: {}", e).into(),
+            data: None,
+        })
+}
@@ -0,0 +1,109 @@
+//! Undo/redo history of applied mutations, persisted under `.dendrite/`.
+
+use dendrite_core::mutation::model::EditPlan;
+use dendrite_core::vfs::FileSystem;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub description: String,
+    /// The applied plan; its preconditions describe the files before it (for redo)
+    pub plan: EditPlan,
+    /// Its inverse; its preconditions describe the files after it (for undo)
+    pub undo: EditPlan,
+}
+
+/// Applied mutations, oldest first, and the undone ones available for redo.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MutationHistory {
+    undo: VecDeque<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    next_id: u64,
+    /// Where the history is persisted, once a workspace is open
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl MutationHistory {
+    pub fn file_path(root: &Path) -> PathBuf {
+        root.join(".dendrite").join("history.json")
+    }
+
+    /// Load the history saved at `path`, starting empty if there is none.
+    pub fn load(path: PathBuf, fs: &dyn FileSystem) -> Self {
+        let mut history: Self = fs
+            .read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        history.path = Some(path);
+        history
+    }
+
+    pub fn save(&self, fs: &dyn FileSystem) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_vec(self).map_err(std::io::Error::other)?;
+        fs.write_all(path, &json)
+    }
+
+    /// Record a newly applied plan; anything undone before can no longer be redone.
+    pub fn record(&mut self, plan: EditPlan, undo: EditPlan, limit: usize) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.next_id += 1;
+        self.undo.push_back(HistoryEntry {
+            id: self.next_id,
+            timestamp,
+            description: plan.describe(),
+            plan,
+            undo,
+        });
+        self.redo.clear();
+        self.truncate(limit);
+    }
+
+    pub fn truncate(&mut self, limit: usize) {
+        while self.undo.len() > limit {
+            self.undo.pop_front();
+        }
+        if self.redo.len() > limit {
+            self.redo.drain(..self.redo.len() - limit);
+        }
+    }
+
+    pub fn pop_undo(&mut self) -> Option<HistoryEntry> {
+        self.undo.pop_back()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<HistoryEntry> {
+        self.redo.pop()
+    }
+
+    /// Return an entry taken with `pop_undo` after undoing it.
+    pub fn push_redo(&mut self, entry: HistoryEntry) {
+        self.redo.push(entry);
+    }
+
+    /// Return an entry taken with `pop_undo` (if undo failed) or `pop_redo` (after redoing it).
+    pub fn push_undo(&mut self, entry: HistoryEntry) {
+        self.undo.push_back(entry);
+    }
+
+    /// Undoable entries, most recent first
+    pub fn undo_entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.undo.iter().rev()
+    }
+
+    /// Redoable entries, most recently undone first
+    pub fn redo_entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.redo.iter().rev()
+    }
+}
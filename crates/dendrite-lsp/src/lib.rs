@@ -15,6 +15,8 @@ mod cache_manager;
 pub mod config;
 mod conversion;
 mod handlers;
+mod history;
+mod overlay;
 mod protocol;
 mod state;
 #[cfg(test)]
@@ -91,9 +93,27 @@ impl Backend {
                 handlers::handle_undo_mutation(&self.client, &self.state).await?;
                 Ok(None)
             }
+            "dendrite/redoMutation" => {
+                handlers::handle_redo_mutation(&self.client, &self.state).await?;
+                Ok(None)
+            }
+            "dendrite/listHistory" => handlers::handle_list_history_command(&self.state).await,
+            "dendrite/previewEditPlan" => {
+                handlers::handle_preview_edit_plan_command(&self.state, params).await
+            }
             "dendrite/splitNote" => {
                 handlers::handle_split_note_command(&self.client, &self.state, params).await
             }
+            "dendrite/moveToVault" => {
+                handlers::handle_move_to_vault_command(&self.client, &self.state, params).await
+            }
+            "dendrite/refactorHierarchy" => {
+                handlers::handle_refactor_hierarchy_command(&self.client, &self.state, params).await
+            }
+            "dendrite/splitNoteByHeadings" => {
+                handlers::handle_split_note_by_headings_command(&self.client, &self.state, params)
+                    .await
+            }
             "dendrite/reorganizeHierarchy" => {
                 handlers::handle_reorganize_hierarchy_command(&self.client, &self.state, params)
                     .await
@@ -113,6 +133,34 @@ impl Backend {
             "dendrite/resolveHierarchyEdits" => {
                 handlers::handle_resolve_hierarchy_edits(&self.client, &self.state, params).await
             }
+            "dendrite/renderNote" => {
+                handlers::handle_render_note_command(&self.state, params).await
+            }
+            "dendrite/search" => handlers::handle_search_command(&self.state, params).await,
+            "dendrite/graphStats" => {
+                handlers::handle_graph_stats_command(&self.state, params).await
+            }
+            "dendrite/shortestPath" => {
+                handlers::handle_shortest_path_command(&self.state, params).await
+            }
+            "dendrite/neighborhood" => {
+                handlers::handle_neighborhood_command(&self.state, params).await
+            }
+            "dendrite/unlinkedMentions" => {
+                handlers::handle_unlinked_mentions_command(&self.state, params).await
+            }
+            "dendrite/relatedNotes" => {
+                handlers::handle_related_notes_command(&self.state, params).await
+            }
+            "dendrite/findDuplicates" => {
+                handlers::handle_find_duplicates_command(&self.state, params).await
+            }
+            "dendrite/mergeNote" => {
+                handlers::handle_merge_note_command(&self.client, &self.state, params).await
+            }
+            "dendrite/mintDuplicateIds" => {
+                handlers::handle_mint_duplicate_ids_command(&self.client, &self.state).await
+            }
             _ => Err(Error {
                 code: ErrorCode::MethodNotFound,
                 message: format!("Unknown command: {}", params.command).into(),
@@ -163,6 +211,20 @@ impl tower_lsp::LanguageServer for Backend {
         handlers::handle_did_change_configuration(&self.client, &self.state, params).await;
     }
 
+    async fn will_rename_files(
+        &self,
+        params: RenameFilesParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
+        handlers::handle_will_rename_files(&self.state, params).await
+    }
+
+    async fn will_delete_files(
+        &self,
+        params: DeleteFilesParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
+        handlers::handle_will_delete_files(&self.state, params).await
+    }
+
     async fn did_rename_files(&self, params: RenameFilesParams) {
         handlers::handle_did_rename_files(&self.client, &self.state, params).await;
     }
@@ -213,6 +275,20 @@ impl tower_lsp::LanguageServer for Backend {
         handlers::handle_code_action(&self.client, &self.state, params).await
     }
 
+    async fn formatting(
+        &self,
+        params: DocumentFormattingParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<TextEdit>>> {
+        handlers::handle_formatting(&self.state, params).await
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<TextEdit>>> {
+        handlers::handle_range_formatting(&self.state, params).await
+    }
+
     async fn will_save_wait_until(
         &self,
         params: WillSaveTextDocumentParams,
@@ -220,6 +296,20 @@ impl tower_lsp::LanguageServer for Backend {
         handlers::handle_will_save_wait_until(&self.state, params).await
     }
 
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> tower_lsp::jsonrpc::Result<DocumentDiagnosticReportResult> {
+        handlers::handle_document_diagnostic(&self.state, params).await
+    }
+
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> tower_lsp::jsonrpc::Result<WorkspaceDiagnosticReportResult> {
+        handlers::handle_workspace_diagnostic(&self.state, params).await
+    }
+
     async fn execute_command(
         &self,
         params: ExecuteCommandParams,
@@ -0,0 +1,40 @@
+//! Content access that prefers unsaved editor buffers over the file system.
+
+use dendrite_core::mutation::model::ContentProvider;
+use dendrite_core::vfs::FileSystem;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tower_lsp::lsp_types::Url;
+
+/// ContentProvider backed by the LSP document cache, falling back to the `FileSystem`.
+///
+/// Core code addresses files by plain path strings, so lookups convert the
+/// path to a `file://` URL before consulting the open documents.
+pub struct OverlayContentProvider<'a> {
+    documents: &'a HashMap<Url, String>,
+    fs: &'a dyn FileSystem,
+}
+
+impl<'a> OverlayContentProvider<'a> {
+    pub fn new(documents: &'a HashMap<Url, String>, fs: &'a dyn FileSystem) -> Self {
+        Self { documents, fs }
+    }
+}
+
+impl ContentProvider for OverlayContentProvider<'_> {
+    fn get_content(&self, uri: &str) -> Option<String> {
+        let path = match Url::parse(uri) {
+            Ok(url) if url.scheme() == "file" => url.to_file_path().ok()?,
+            _ => PathBuf::from(uri),
+        };
+
+        if let Some(text) = Url::from_file_path(&path)
+            .ok()
+            .and_then(|url| self.documents.get(&url))
+        {
+            return Some(text.clone());
+        }
+
+        self.fs.read_to_string(&path).ok()
+    }
+}
@@ -48,6 +48,7 @@ pub struct GetNoteKeyResult {
     pub key: String,
 }
 
+#[allow(dead_code)] // Part of the client contract; dendrite/createNote currently takes a bare key
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateNoteParams {
     pub note_key: String,
@@ -61,9 +62,279 @@ pub struct DeleteNoteParams {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetBacklinksParams {
     pub note_key: String,
+    /// Also return the references grouped by target anchor
+    #[serde(default)]
+    pub group_by_anchor: bool,
+}
+
+/// A single link to the requested note
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BacklinkReference {
+    pub source_key: String,
+    pub uri: Option<String>,
+    pub title: Option<String>,
+    pub range: tower_lsp::lsp_types::Range,
+    /// Anchor of the target the link points to
+    pub anchor: Option<String>,
+    /// Paragraph or list item containing the link
+    pub context: String,
+    /// Headings the link sits under, outermost first
+    pub heading_path: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BacklinkGroup {
+    /// None for links to the note as a whole
+    pub anchor: Option<String>,
+    pub references: Vec<BacklinkReference>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetBacklinksResult {
+    /// One entry per linking note
     pub backlinks: Vec<NoteSummary>,
+    /// One entry per link, ordered by source key and position
+    #[serde(default)]
+    pub references: Vec<BacklinkReference>,
+    /// Present when `group_by_anchor` was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<BacklinkGroup>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenderNoteParams {
+    pub uri: String,
+    /// How to present frontmatter ("strip" or "table")
+    #[serde(default)]
+    pub frontmatter: dendrite_core::render::FrontmatterMode,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenderNoteResult {
+    pub html: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchParams {
+    /// Words, "phrases", prefix*, and vault:/key:/tag: filters
+    pub query: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHitSummary {
+    pub key: String,
+    pub uri: Option<String>,
+    pub title: Option<String>,
+    pub vault_name: String,
+    pub score: f64,
+    /// Ranges of matched terms in the note
+    pub matches: Vec<tower_lsp::lsp_types::Range>,
+    pub snippet: Option<dendrite_core::search::Snippet>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub hits: Vec<SearchHitSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct GraphStatsParams {
+    /// Number of hubs to report per degree (default 10)
+    pub hub_limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShortestPathParams {
+    pub from: String,
+    pub to: String,
+    /// "outgoing", "incoming" or "both" (default)
+    #[serde(default)]
+    pub direction: dendrite_core::analysis::traversal::LinkDirection,
+    #[serde(default)]
+    pub include_hierarchy: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PathStepSummary {
+    pub key: String,
+    pub uri: Option<String>,
+    /// How this note was reached from the previous one (None for the first)
+    pub via: Option<dendrite_core::analysis::traversal::StepKind>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShortestPathResult {
+    /// None if the notes are not connected
+    pub path: Option<Vec<PathStepSummary>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NeighborhoodParams {
+    pub note_key: String,
+    /// Number of hops (default 1)
+    pub depth: Option<usize>,
+    #[serde(default)]
+    pub direction: dendrite_core::analysis::traversal::LinkDirection,
+    #[serde(default)]
+    pub include_hierarchy: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphNodeSummary {
+    pub key: String,
+    pub uri: Option<String>,
+    pub title: Option<String>,
+    pub depth: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NeighborhoodResult {
+    pub nodes: Vec<GraphNodeSummary>,
+    pub edges: Vec<dendrite_core::analysis::traversal::GraphEdge>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct UnlinkedMentionsParams {
+    /// Only scan this note (default: the whole workspace)
+    pub uri: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnlinkedMentionSummary {
+    pub uri: String,
+    pub range: tower_lsp::lsp_types::Range,
+    pub text: String,
+    pub target_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnlinkedMentionsResult {
+    pub mentions: Vec<UnlinkedMentionSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelatedNotesParams {
+    pub uri: String,
+    /// Maximum number of suggestions (default 10)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelatedNoteSummary {
+    pub key: String,
+    pub uri: Option<String>,
+    pub title: Option<String>,
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelatedNotesResult {
+    pub notes: Vec<RelatedNoteSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct FindDuplicatesParams {
+    /// Minimum shared-content ratio, 0 to 1 (default 0.8)
+    pub threshold: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateNoteSummary {
+    pub key: String,
+    pub uri: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateClusterSummary {
+    pub notes: Vec<DuplicateNoteSummary>,
+    pub shared_ratio: f64,
+    pub pairs: Vec<dendrite_core::analysis::duplicates::DuplicatePair>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FindDuplicatesResult {
+    pub clusters: Vec<DuplicateClusterSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefactorHierarchyParams {
+    /// Regex matched against note keys, e.g. "^daily\\.journal\\.(\\d+)"
+    pub pattern: String,
+    /// Replacement for the first match, with "$1"-style capture groups
+    pub replacement: String,
+    /// Only rename this note and its descendants (default: every note)
+    pub scope: Option<String>,
+    /// Report the mappings without applying them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefactorHierarchyResult {
+    pub mappings: Vec<dendrite_core::mutation::hierarchy::KeyMapping>,
+    /// Why the refactor cannot be applied; empty if it can
+    pub collisions: Vec<String>,
+    pub applied: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SplitNoteByHeadingsParams {
+    pub note_key: String,
+    /// Heading level whose sections become child notes (default 2)
+    pub level: Option<u8>,
+    /// "embed" (default) or "link"
+    #[serde(default)]
+    pub replacement: dendrite_core::mutation::split::SectionReplacement,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveToVaultParams {
+    pub note_key: String,
+    /// Name of the destination vault
+    pub vault: String,
+    /// Also move the note's descendants
+    #[serde(default)]
+    pub include_descendants: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeNoteParams {
+    /// Note to merge and delete
+    pub source_key: String,
+    /// Note receiving the source's content and backlinks
+    pub target_key: String,
+    /// Heading (text or anchor) whose section receives the source body (default: the end)
+    #[serde(default)]
+    pub heading: Option<String>,
+    /// "keep-target" (default), "add-missing" or "combine"
+    #[serde(default)]
+    pub frontmatter: dendrite_core::mutation::merge::FrontmatterStrategy,
+}
+
+/// An applied (or undone) mutation in the history
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryEntrySummary {
+    pub id: u64,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub description: String,
+    pub mutation_kind: dendrite_core::mutation::model::MutationKind,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListHistoryResult {
+    /// Most recent first
+    pub undo: Vec<HistoryEntrySummary>,
+    /// Most recently undone first
+    pub redo: Vec<HistoryEntrySummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreviewEditPlanParams {
+    /// Mutation command to preview, e.g. "dendrite/reorganizeHierarchy"
+    pub command: String,
+    /// That command's arguments
+    #[serde(default)]
+    pub arguments: Vec<serde_json::Value>,
 }
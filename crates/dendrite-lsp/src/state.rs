@@ -1,11 +1,11 @@
 use crate::config::LspSettings;
-use dendrite_core::mutation::model::EditPlan;
+use crate::history::MutationHistory;
 use dendrite_core::vfs::FileSystem;
 use dendrite_core::workspace::DendriteEngine;
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tower_lsp::lsp_types::Url;
+use tower_lsp::lsp_types::{ClientCapabilities, Url};
 
 /// Global state for LSP server
 /// Must be Send + Sync
@@ -18,12 +18,14 @@ pub struct GlobalState {
     pub document_cache: Arc<RwLock<HashMap<Url, String>>>,
     /// Virtual File System backend
     pub fs: Arc<dyn FileSystem>,
-    /// History of applied mutations for multi-level undo
-    pub mutation_history: Arc<RwLock<VecDeque<EditPlan>>>,
+    /// History of applied mutations for multi-level undo and redo
+    pub mutation_history: Arc<RwLock<MutationHistory>>,
     /// Signal to trigger debounced cache saving
     pub(crate) dirty_signal: tokio::sync::mpsc::UnboundedSender<()>,
     /// LSP-specific settings (from client or default)
     pub config: Arc<RwLock<LspSettings>>,
+    /// Capabilities announced by the client in "initialize"
+    pub client_capabilities: Arc<RwLock<ClientCapabilities>>,
 }
 
 impl GlobalState {
@@ -35,11 +37,10 @@ impl GlobalState {
             engine: Arc::new(RwLock::new(None)),
             document_cache: Arc::new(RwLock::new(HashMap::new())),
             fs,
-            mutation_history: Arc::new(RwLock::new(VecDeque::with_capacity(
-                config.mutation_history_limit,
-            ))),
+            mutation_history: Arc::new(RwLock::new(MutationHistory::default())),
             dirty_signal: dirty_tx,
             config: Arc::new(RwLock::new(config)),
+            client_capabilities: Arc::new(RwLock::new(ClientCapabilities::default())),
         };
 
         // Start background cache manager
@@ -50,4 +51,14 @@ impl GlobalState {
 
         state
     }
+
+    /// Whether the client accepts change annotations in workspace edits
+    pub(crate) async fn supports_change_annotations(&self) -> bool {
+        let capabilities = self.client_capabilities.read().await;
+        capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.workspace_edit.as_ref())
+            .is_some_and(|edit| edit.change_annotation_support.is_some())
+    }
 }
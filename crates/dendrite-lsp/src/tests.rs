@@ -17,6 +17,54 @@ async fn setup_test_context() -> (Backend, TempDir) {
     (backend, temp_dir)
 }
 
+/// Initialize a server through its service, connected to a client that applies
+/// every "workspace/applyEdit" request and forwards the edit.
+async fn setup_connected_context(
+    root: &std::path::Path,
+) -> (
+    Backend,
+    LspService<Backend>,
+    tokio::sync::mpsc::UnboundedReceiver<WorkspaceEdit>,
+) {
+    use futures::{SinkExt, StreamExt};
+    use tower_lsp::jsonrpc::{Request, Response};
+    use tower_service::Service;
+
+    let fs = Arc::new(PhysicalFileSystem);
+    let (mut service, socket) = LspService::new(|client| Backend::new(client, fs.clone()));
+    let backend = service.inner().clone();
+
+    let (edits_tx, edits_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (mut requests, mut responses) = socket.split();
+    tokio::spawn(async move {
+        while let Some(request) = requests.next().await {
+            let (method, id, params) = request.into_parts();
+            let Some(id) = id else {
+                continue;
+            };
+            let result = if method == "workspace/applyEdit" {
+                let params: ApplyWorkspaceEditParams =
+                    serde_json::from_value(params.unwrap()).unwrap();
+                let _ = edits_tx.send(params.edit);
+                serde_json::json!({ "applied": true })
+            } else {
+                serde_json::Value::Null
+            };
+            let _ = responses.send(Response::from_ok(id, result)).await;
+        }
+    });
+
+    let root_uri = Url::from_file_path(root).unwrap();
+    let initialize = Request::build("initialize")
+        .params(serde_json::to_value(create_initialize_params(root_uri)).unwrap())
+        .id(1)
+        .finish();
+    let response = service.call(initialize).await.unwrap().unwrap();
+    assert!(response.is_ok());
+
+    (backend, service, edits_rx)
+}
+
 #[allow(deprecated)]
 fn create_initialize_params(root_uri: Url) -> InitializeParams {
     InitializeParams {
@@ -75,7 +123,7 @@ async fn test_lsp_completion() {
         .unwrap();
 
     let note_path = temp_dir.path().join("main.md");
-    let content = "Check this: [[";
+    let content = "---\naliases: [Home Page]\n---\nCheck this: [[";
     fs::write(&note_path, content).unwrap();
 
     let uri = Url::from_file_path(&note_path).unwrap();
@@ -97,7 +145,7 @@ async fn test_lsp_completion() {
         text_document_position: TextDocumentPositionParams {
             text_document: TextDocumentIdentifier { uri: uri.clone() },
             position: Position {
-                line: 0,
+                line: 3,
                 character: 14,
             },
         },
@@ -113,6 +161,8 @@ async fn test_lsp_completion() {
     if let Some(CompletionResponse::Array(items)) = response {
         assert!(!items.is_empty());
         assert!(items.iter().any(|i| i.label == "main"));
+        let alias = items.iter().find(|i| i.label == "Home Page").unwrap();
+        assert_eq!(alias.detail.as_deref(), Some("Alias of main"));
     } else {
         panic!("Expected completion array");
     }
@@ -293,27 +343,27 @@ async fn test_lsp_rename() {
         DocumentChanges::Operations(ops) => {
             for op in ops {
                 match op {
-                    DocumentChangeOperation::Op(ResourceOp::Rename(rename_file)) => {
-                        if rename_file.old_uri == old_uri {
-                            let expected_new_path = temp_dir.path().join("new_note.md");
-                            let expected_new_uri = Url::from_file_path(expected_new_path).unwrap();
-                            let actual_new_uri = rename_file.new_uri;
+                    DocumentChangeOperation::Op(ResourceOp::Rename(rename_file))
+                        if rename_file.old_uri == old_uri =>
+                    {
+                        let expected_new_path = temp_dir.path().join("new_note.md");
+                        let expected_new_uri = Url::from_file_path(expected_new_path).unwrap();
+                        let actual_new_uri = rename_file.new_uri;
 
-                            assert_eq!(actual_new_uri.path(), expected_new_uri.path());
+                        assert_eq!(actual_new_uri.path(), expected_new_uri.path());
 
-                            rename_found = true;
-                        }
+                        rename_found = true;
                     }
-                    DocumentChangeOperation::Edit(text_edit) => {
-                        if text_edit.text_document.uri == source_uri {
-                            assert!(!text_edit.edits.is_empty());
-                            if let OneOf::Left(edit) = &text_edit.edits[0] {
-                                assert_eq!(edit.new_text, "[[new_note]]");
-                            } else {
-                                panic!("Expected standard TextEdit");
-                            }
-                            link_update_found = true;
+                    DocumentChangeOperation::Edit(text_edit)
+                        if text_edit.text_document.uri == source_uri =>
+                    {
+                        assert!(!text_edit.edits.is_empty());
+                        if let OneOf::Left(edit) = &text_edit.edits[0] {
+                            assert_eq!(edit.new_text, "[[new_note]]");
+                        } else {
+                            panic!("Expected standard TextEdit");
                         }
+                        link_update_found = true;
                     }
                     _ => {}
                 }
@@ -856,6 +906,7 @@ async fn test_lsp_get_backlinks() {
         command: "dendrite/getBacklinks".to_string(),
         arguments: vec![serde_json::to_value(crate::protocol::GetBacklinksParams {
             note_key: "target".to_string(),
+            group_by_anchor: false,
         })
         .unwrap()],
         ..Default::default()
@@ -869,6 +920,76 @@ async fn test_lsp_get_backlinks() {
 
     assert_eq!(backlinks_result.backlinks.len(), 1);
     assert_eq!(backlinks_result.backlinks[0].key, "source");
+    assert_eq!(backlinks_result.references.len(), 1);
+    assert_eq!(backlinks_result.references[0].context, "Link to [[target]]");
+    assert!(backlinks_result.groups.is_none());
+}
+
+#[tokio::test]
+async fn test_lsp_get_backlinks_grouped_by_anchor() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    for (name, text) in [
+        ("target.md", "# Target\n## Setup"),
+        (
+            "source.md",
+            "# Source\n## Notes\n- see [[target#setup]]\n- and [[target]]",
+        ),
+        ("other.md", "Also [[target#setup]]"),
+    ] {
+        let path = temp_dir.path().join(name);
+        fs::write(&path, text).unwrap();
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: Url::from_file_path(&path).unwrap(),
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+    }
+
+    let result = backend
+        .handle_execute_command(ExecuteCommandParams {
+            command: "dendrite/getBacklinks".to_string(),
+            arguments: vec![serde_json::json!({ "note_key": "target", "group_by_anchor": true })],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let result: crate::protocol::GetBacklinksResult = serde_json::from_value(result).unwrap();
+
+    assert_eq!(result.references.len(), 3);
+    let groups = result.groups.unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].anchor, None);
+    assert_eq!(groups[0].references[0].context, "and [[target]]");
+    assert_eq!(groups[1].anchor.as_deref(), Some("setup"));
+    let sources: Vec<_> = groups[1]
+        .references
+        .iter()
+        .map(|r| (r.source_key.as_str(), r.heading_path.clone()))
+        .collect();
+    assert_eq!(
+        sources,
+        vec![
+            ("other", vec![]),
+            ("source", vec!["Source".to_string(), "Notes".to_string()])
+        ]
+    );
+    assert_eq!(groups[1].references[1].range.start, Position::new(2, 6));
 }
 
 #[tokio::test]
@@ -928,5 +1049,1257 @@ async fn test_delete_note_plan() {
         } else {
             panic!("Expected DeleteFile operation");
         }
+
+        // 3. The plan goes stale once the note is edited
+        assert!(handlers::validate_edit_plan(state, &plan).await.is_ok());
+        state
+            .document_cache
+            .write()
+            .await
+            .insert(note_uri.clone(), "# Delete Me\nUnsaved".to_string());
+        let error = handlers::validate_edit_plan(state, &plan)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, tower_lsp::jsonrpc::ErrorCode::ContentModified);
+        assert_eq!(
+            error.message,
+            format!(
+                "Stale edit plan: '{}' changed since the edit was planned.",
+                note_path.display()
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_lsp_hover_renders_anchor_section() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    // Target exists on disk with stale content; the open buffer is authoritative
+    let target_path = temp_dir.path().join("target.md");
+    fs::write(&target_path, "# Target\n\nOn disk").unwrap();
+    let target_uri = Url::from_file_path(&target_path).unwrap();
+    let target_text = "# Target\n\n## Usage\n\nUnsaved usage\n\n![[snippet]]\n\n## Other\n\nHidden";
+
+    let snippet_path = temp_dir.path().join("snippet.md");
+    fs::write(&snippet_path, "Embedded snippet").unwrap();
+
+    let source_path = temp_dir.path().join("source.md");
+    let source_text = "See [[target#usage]]";
+    fs::write(&source_path, source_text).unwrap();
+    let source_uri = Url::from_file_path(&source_path).unwrap();
+
+    for (uri, text) in [
+        (
+            Url::from_file_path(&snippet_path).unwrap(),
+            "Embedded snippet",
+        ),
+        (target_uri, target_text),
+        (source_uri.clone(), source_text),
+    ] {
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri,
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+    }
+
+    let hover = handlers::handle_hover(
+        client,
+        state,
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: source_uri },
+                position: Position {
+                    line: 0,
+                    character: 8,
+                },
+            },
+            work_done_progress_params: Default::default(),
+        },
+    )
+    .await
+    .unwrap()
+    .expect("Should produce hover");
+
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert_eq!(
+        markup.value,
+        "## Usage\n\nUnsaved usage\n\nEmbedded snippet"
+    );
+}
+
+#[tokio::test]
+async fn test_lsp_render_note_command() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    let target_path = temp_dir.path().join("target.md");
+    fs::write(&target_path, "# Target\n\nShared paragraph ^shared").unwrap();
+    let target_uri = Url::from_file_path(&target_path).unwrap();
+
+    let note_path = temp_dir.path().join("note.md");
+    let note_text = "---\ntitle: Note\n---\nSee [[target]]\n\n![[target#^shared]]";
+    fs::write(&note_path, note_text).unwrap();
+    let note_uri = Url::from_file_path(&note_path).unwrap();
+
+    for (uri, text) in [
+        (target_uri.clone(), "# Target\n\nShared paragraph ^shared"),
+        (note_uri.clone(), note_text),
+    ] {
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri,
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+    }
+
+    let params = ExecuteCommandParams {
+        command: "dendrite/renderNote".to_string(),
+        arguments: vec![serde_json::json!({
+            "uri": note_uri.to_string(),
+            "frontmatter": "table",
+        })],
+        ..Default::default()
+    };
+
+    let result = backend
+        .handle_execute_command(params)
+        .await
+        .unwrap()
+        .expect("Should return rendered note");
+    let html = result["html"].as_str().unwrap();
+
+    assert!(html.contains("<tr><th>title</th><td>Note</td></tr>"));
+    assert!(html.contains(&format!("href=\"{}\"", target_uri)));
+    assert!(html.contains("<p>Shared paragraph</p>"));
+}
+
+#[tokio::test]
+async fn test_lsp_will_rename_and_delete_files() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    let old_path = temp_dir.path().join("old_note.md");
+    fs::write(&old_path, "# Old Note").unwrap();
+    let old_uri = Url::from_file_path(&old_path).unwrap();
+
+    let source_path = temp_dir.path().join("source.md");
+    let source_content = "Link to [[old_note]]";
+    fs::write(&source_path, source_content).unwrap();
+    let source_uri = Url::from_file_path(&source_path).unwrap();
+
+    for (uri, text) in [
+        (old_uri.clone(), "# Old Note"),
+        (source_uri.clone(), source_content),
+    ] {
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri,
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+    }
+
+    let source_edits = |edit: WorkspaceEdit| -> Vec<String> {
+        let Some(DocumentChanges::Operations(ops)) = edit.document_changes else {
+            panic!("Expected DocumentChanges::Operations");
+        };
+        ops.into_iter()
+            .flat_map(|op| match op {
+                DocumentChangeOperation::Edit(text_edit) => {
+                    assert_eq!(text_edit.text_document.uri, source_uri);
+                    text_edit.edits
+                }
+                // The client performs the file operation itself
+                DocumentChangeOperation::Op(op) => panic!("Unexpected resource op: {:?}", op),
+            })
+            .map(|edit| match edit {
+                OneOf::Left(edit) => edit.new_text,
+                OneOf::Right(edit) => edit.text_edit.new_text,
+            })
+            .collect()
+    };
+
+    // willRenameFiles: the link is rewritten before the file moves
+    let new_uri = Url::from_file_path(temp_dir.path().join("new_note.md")).unwrap();
+    let edit = handlers::handle_will_rename_files(
+        state,
+        RenameFilesParams {
+            files: vec![FileRename {
+                old_uri: old_uri.to_string(),
+                new_uri: new_uri.to_string(),
+            }],
+        },
+    )
+    .await
+    .unwrap()
+    .expect("Rename should update links");
+    assert_eq!(source_edits(edit), vec!["[[new_note]]"]);
+
+    // willDeleteFiles: disabled by default
+    let delete_params = || DeleteFilesParams {
+        files: vec![FileDelete {
+            uri: old_uri.to_string(),
+        }],
+    };
+    let edit = handlers::handle_will_delete_files(state, delete_params())
+        .await
+        .unwrap();
+    assert!(edit.is_none());
+
+    state.config.write().await.files.unlink_on_delete = true;
+    let edit = handlers::handle_will_delete_files(state, delete_params())
+        .await
+        .unwrap()
+        .expect("Delete should unlink references");
+    assert_eq!(source_edits(edit), vec!["old_note"]);
+}
+
+#[tokio::test]
+async fn test_lsp_watched_files_detect_external_rename() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let old_path = temp_dir.path().join("old_note.md");
+    let new_path = temp_dir.path().join("archive.old_note.md");
+    let source_path = temp_dir.path().join("source.md");
+    fs::write(&old_path, "---\nid: stable\n---\n# Old Note").unwrap();
+    fs::write(&source_path, "Link to [[old_note]]").unwrap();
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    // `mv` outside the editor: the watcher reports a delete and a create
+    fs::rename(&old_path, &new_path).unwrap();
+    handlers::handle_did_change_watched_files(
+        client,
+        state,
+        DidChangeWatchedFilesParams {
+            changes: vec![
+                FileEvent {
+                    uri: Url::from_file_path(&old_path).unwrap(),
+                    typ: FileChangeType::DELETED,
+                },
+                FileEvent {
+                    uri: Url::from_file_path(&new_path).unwrap(),
+                    typ: FileChangeType::CREATED,
+                },
+            ],
+        },
+    )
+    .await;
+
+    let engine = state.engine.read().await;
+    let ws = &engine.as_ref().unwrap().workspace;
+    assert_eq!(ws.backlinks_of(&new_path), vec![source_path]);
+    assert_eq!(
+        ws.resolve_note_key(&new_path).as_deref(),
+        Some("archive.old_note")
+    );
+}
+
+#[tokio::test]
+async fn test_lsp_external_rename_updates_links_when_enabled() {
+    let temp_dir = TempDir::new().unwrap();
+    let old_path = temp_dir.path().join("old_note.md");
+    let new_path = temp_dir.path().join("archive.old_note.md");
+    let source_path = temp_dir.path().join("source.md");
+    fs::write(&old_path, "---\nid: stable\n---\n# Old Note").unwrap();
+    fs::write(&source_path, "Link to [[old_note]]").unwrap();
+
+    let (backend, _service, mut edits) = setup_connected_context(temp_dir.path()).await;
+    let client = &backend.client;
+    let state = &backend.state;
+    state
+        .config
+        .write()
+        .await
+        .files
+        .update_links_on_external_rename = true;
+
+    // The link sits on another line in the unsaved buffer than on disk
+    let source_uri = Url::from_file_path(&source_path).unwrap();
+    handlers::handle_did_open(
+        state,
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: source_uri.clone(),
+                language_id: "markdown".to_string(),
+                version: 1,
+                text: "Unsaved line\nLink to [[old_note]]".to_string(),
+            },
+        },
+    )
+    .await;
+
+    fs::rename(&old_path, &new_path).unwrap();
+    handlers::handle_did_change_watched_files(
+        client,
+        state,
+        DidChangeWatchedFilesParams {
+            changes: vec![
+                FileEvent {
+                    uri: Url::from_file_path(&old_path).unwrap(),
+                    typ: FileChangeType::DELETED,
+                },
+                FileEvent {
+                    uri: Url::from_file_path(&new_path).unwrap(),
+                    typ: FileChangeType::CREATED,
+                },
+            ],
+        },
+    )
+    .await;
+
+    // Text-only operations come back from the wire as plain edits
+    let edit = edits.try_recv().expect("Link edits should be applied");
+    let Some(DocumentChanges::Edits(document_edits)) = edit.document_changes else {
+        panic!("Expected text document edits");
+    };
+    let text_edits: Vec<TextEdit> = document_edits
+        .into_iter()
+        .flat_map(|text_edit| {
+            assert_eq!(text_edit.text_document.uri, source_uri);
+            text_edit.edits
+        })
+        .map(|edit| match edit {
+            OneOf::Left(edit) => edit,
+            OneOf::Right(edit) => edit.text_edit,
+        })
+        .collect();
+    assert_eq!(text_edits.len(), 1);
+    assert_eq!(text_edits[0].new_text, "[[archive.old_note]]");
+    assert_eq!(text_edits[0].range.start, Position::new(1, 8));
+
+    // The edit was applied, so it can be undone
+    let list = handlers::handle_list_history_command(state)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(list["undo"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_lsp_pull_diagnostics_result_ids() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    let open = |path: std::path::PathBuf, text: &'static str| async move {
+        fs::write(&path, text).unwrap();
+        let uri = Url::from_file_path(&path).unwrap();
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+        uri
+    };
+
+    let source_uri = open(temp_dir.path().join("source.md"), "Link to [[target]]").await;
+
+    let pull = |previous_result_id: Option<String>| {
+        let uri = source_uri.clone();
+        async move {
+            let result = handlers::handle_document_diagnostic(
+                state,
+                DocumentDiagnosticParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    identifier: None,
+                    previous_result_id,
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                },
+            )
+            .await
+            .unwrap();
+            let DocumentDiagnosticReportResult::Report(report) = result else {
+                panic!("Expected a report");
+            };
+            report
+        }
+    };
+
+    // 1. Broken link reported with a result ID
+    let DocumentDiagnosticReport::Full(full) = pull(None).await else {
+        panic!("Expected full report");
+    };
+    let broken_id = full.full_document_diagnostic_report.result_id.unwrap();
+    assert_eq!(full.full_document_diagnostic_report.items.len(), 1);
+
+    // 2. Nothing changed
+    assert!(matches!(
+        pull(Some(broken_id.clone())).await,
+        DocumentDiagnosticReport::Unchanged(_)
+    ));
+
+    // 3. Creating the link target changes the source's report
+    open(temp_dir.path().join("target.md"), "# Target").await;
+    let DocumentDiagnosticReport::Full(full) = pull(Some(broken_id.clone())).await else {
+        panic!("Expected full report after target was created");
+    };
+    let fixed_id = full.full_document_diagnostic_report.result_id.unwrap();
+    assert_ne!(fixed_id, broken_id);
+    assert!(full.full_document_diagnostic_report.items.is_empty());
+
+    // 4. Workspace pull honours known result IDs
+    let WorkspaceDiagnosticReportResult::Report(report) = handlers::handle_workspace_diagnostic(
+        state,
+        WorkspaceDiagnosticParams {
+            identifier: None,
+            previous_result_ids: vec![PreviousResultId {
+                uri: source_uri.clone(),
+                value: fixed_id,
+            }],
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+    )
+    .await
+    .unwrap() else {
+        panic!("Expected a report");
+    };
+
+    assert_eq!(report.items.len(), 2);
+    for item in report.items {
+        match item {
+            WorkspaceDocumentDiagnosticReport::Unchanged(unchanged) => {
+                assert_eq!(unchanged.uri, source_uri)
+            }
+            WorkspaceDocumentDiagnosticReport::Full(full) => {
+                assert_ne!(full.uri, source_uri);
+                assert!(full.full_document_diagnostic_report.items.is_empty());
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_lsp_formatting_normalizes_links() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    fs::write(temp_dir.path().join("target.md"), "# Target\n\n## Usage").unwrap();
+    let target_uri = Url::from_file_path(temp_dir.path().join("target.md")).unwrap();
+
+    // The buffer differs from disk; formatting works on the buffer
+    let source_path = temp_dir.path().join("source.md");
+    fs::write(&source_path, "Saved").unwrap();
+    let source_uri = Url::from_file_path(&source_path).unwrap();
+
+    for (uri, text) in [
+        (target_uri, "# Target\n\n## Usage"),
+        (
+            source_uri.clone(),
+            "See [docs](target.md#Usage)\n[[ target ]]",
+        ),
+    ] {
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri,
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+    }
+
+    let edits = handlers::handle_formatting(
+        state,
+        DocumentFormattingParams {
+            text_document: TextDocumentIdentifier {
+                uri: source_uri.clone(),
+            },
+            options: Default::default(),
+            work_done_progress_params: Default::default(),
+        },
+    )
+    .await
+    .unwrap()
+    .expect("Should produce formatting edits");
+
+    let new_texts: Vec<_> = edits.iter().map(|e| e.new_text.as_str()).collect();
+    assert_eq!(new_texts, vec!["[[docs|target#usage]]", "[[target]]"]);
+
+    let edits = handlers::handle_range_formatting(
+        state,
+        DocumentRangeFormattingParams {
+            text_document: TextDocumentIdentifier { uri: source_uri },
+            range: Range {
+                start: Position {
+                    line: 1,
+                    character: 0,
+                },
+                end: Position {
+                    line: 1,
+                    character: 12,
+                },
+            },
+            options: Default::default(),
+            work_done_progress_params: Default::default(),
+        },
+    )
+    .await
+    .unwrap()
+    .expect("Should produce range formatting edits");
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "[[target]]");
+}
+
+#[tokio::test]
+async fn test_lsp_search_command() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    let note_path = temp_dir.path().join("proj.alpha.md");
+    fs::write(&note_path, "saved text").unwrap();
+    let note_uri = Url::from_file_path(&note_path).unwrap();
+    let other_path = temp_dir.path().join("daily.md");
+    fs::write(&other_path, "a needle elsewhere").unwrap();
+
+    // Unsaved buffer content is what gets searched
+    handlers::handle_did_open(
+        state,
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: note_uri.clone(),
+                language_id: "markdown".to_string(),
+                version: 0,
+                text: "# Alpha\nFind the needle here".to_string(),
+            },
+        },
+    )
+    .await;
+
+    let params = ExecuteCommandParams {
+        command: "dendrite/search".to_string(),
+        arguments: vec![serde_json::json!({ "query": "needle key:proj.*" })],
+        ..Default::default()
+    };
+
+    let result = backend
+        .handle_execute_command(params)
+        .await
+        .unwrap()
+        .expect("Should return search results");
+    let hits = result["hits"].as_array().unwrap();
+
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["key"], "proj.alpha");
+    assert_eq!(hits[0]["uri"], note_uri.to_string());
+    assert_eq!(
+        hits[0]["matches"][0],
+        serde_json::json!({
+            "start": { "line": 1, "character": 9 },
+            "end": { "line": 1, "character": 15 },
+        })
+    );
+    assert_eq!(hits[0]["snippet"]["text"], "# Alpha Find the needle here");
+}
+
+#[tokio::test]
+async fn test_lsp_graph_stats_command() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    for (name, text) in [("root.md", "[[a]]"), ("a.md", "# A"), ("b.md", "# B")] {
+        let path = temp_dir.path().join(name);
+        fs::write(&path, text).unwrap();
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: Url::from_file_path(&path).unwrap(),
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+    }
+
+    let params = ExecuteCommandParams {
+        command: "dendrite/graphStats".to_string(),
+        arguments: vec![serde_json::json!({ "hub_limit": 1 })],
+        ..Default::default()
+    };
+
+    let report = backend
+        .handle_execute_command(params)
+        .await
+        .unwrap()
+        .expect("Should return graph report");
+
+    assert_eq!(report["orphans"], serde_json::json!(["b"]));
+    assert_eq!(report["dead_ends"], serde_json::json!(["a"]));
+    assert_eq!(report["hubs_by_in_degree"][0]["key"], "a");
+    assert_eq!(report["components"].as_array().unwrap().len(), 2);
+    assert_eq!(report["unreachable_from_root"], serde_json::json!(["b"]));
+}
+
+#[tokio::test]
+async fn test_lsp_shortest_path_and_neighborhood() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    for (name, text) in [("a.md", "[[b]]"), ("b.md", "[[c]]"), ("c.md", "# C")] {
+        let path = temp_dir.path().join(name);
+        fs::write(&path, text).unwrap();
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: Url::from_file_path(&path).unwrap(),
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+    }
+
+    let result = backend
+        .handle_execute_command(ExecuteCommandParams {
+            command: "dendrite/shortestPath".to_string(),
+            arguments: vec![serde_json::json!({ "from": "c", "to": "a" })],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let path = result["path"].as_array().unwrap();
+    assert_eq!(path.len(), 3);
+    assert_eq!(path[1]["via"], "backlink");
+    assert_eq!(
+        path[2]["uri"],
+        Url::from_file_path(temp_dir.path().join("a.md"))
+            .unwrap()
+            .to_string()
+    );
+
+    let result = backend
+        .handle_execute_command(ExecuteCommandParams {
+            command: "dendrite/shortestPath".to_string(),
+            arguments: vec![serde_json::json!({ "from": "c", "to": "a", "direction": "outgoing" })],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(result["path"].is_null());
+
+    let result = backend
+        .handle_execute_command(ExecuteCommandParams {
+            command: "dendrite/neighborhood".to_string(),
+            arguments: vec![serde_json::json!({ "note_key": "a", "depth": 2 })],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(result["nodes"].as_array().unwrap().len(), 3);
+    assert_eq!(result["nodes"][2]["depth"], 2);
+    assert_eq!(
+        result["edges"][0],
+        serde_json::json!({ "source": "a", "target": "b", "kind": "link" })
+    );
+}
+
+#[tokio::test]
+async fn test_lsp_unlinked_mentions_and_link_action() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    let note_path = temp_dir.path().join("source.md");
+    let note_uri = Url::from_file_path(&note_path).unwrap();
+    for (path, text) in [
+        (temp_dir.path().join("proj.tokio.md"), "# Tokio Runtime"),
+        (note_path.clone(), "We run on the tokio runtime."),
+    ] {
+        fs::write(&path, text).unwrap();
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: Url::from_file_path(&path).unwrap(),
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+    }
+
+    let result = backend
+        .handle_execute_command(ExecuteCommandParams {
+            command: "dendrite/unlinkedMentions".to_string(),
+            arguments: vec![serde_json::json!({ "uri": note_uri.to_string() })],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        result["mentions"],
+        serde_json::json!([{
+            "uri": note_uri.to_string(),
+            "range": {
+                "start": { "line": 0, "character": 14 },
+                "end": { "line": 0, "character": 27 },
+            },
+            "text": "tokio runtime",
+            "target_key": "proj.tokio",
+        }])
+    );
+
+    let cursor = Position {
+        line: 0,
+        character: 16,
+    };
+    let actions = backend
+        .code_action(CodeActionParams {
+            text_document: TextDocumentIdentifier {
+                uri: note_uri.clone(),
+            },
+            range: Range {
+                start: cursor,
+                end: cursor,
+            },
+            context: CodeActionContext::default(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let Some(CodeActionOrCommand::CodeAction(action)) = actions.first() else {
+        panic!("Expected a link action");
+    };
+    assert_eq!(action.title, "Link to 'proj.tokio'");
+    let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&note_uri];
+    assert_eq!(edits[0].new_text, "[[tokio runtime|proj.tokio]]");
+}
+
+#[tokio::test]
+async fn test_lsp_related_notes_command() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    for (name, text) in [
+        ("a.md", "compiler optimization passes"),
+        ("b.md", "optimization passes in the compiler"),
+        ("c.md", "[[a]] compiler optimization passes"),
+        ("d.md", "weekend hiking trip"),
+    ] {
+        let path = temp_dir.path().join(name);
+        fs::write(&path, text).unwrap();
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: Url::from_file_path(&path).unwrap(),
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .await;
+    }
+
+    let a_uri = Url::from_file_path(temp_dir.path().join("a.md")).unwrap();
+    let result = backend
+        .handle_execute_command(ExecuteCommandParams {
+            command: "dendrite/relatedNotes".to_string(),
+            arguments: vec![serde_json::json!({ "uri": a_uri.to_string() })],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    // c links to a already; d shares nothing
+    let notes = result["notes"].as_array().unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0]["key"], "b");
+    assert!(notes[0]["score"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test]
+async fn test_lsp_find_duplicates_command() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    let body = "Notes from the planning meeting about the quarterly release schedule";
+    for (name, text) in [
+        ("meeting.md", body.to_string()),
+        ("meeting-copy.md", format!("{} and budget", body)),
+        (
+            "other.md",
+            "A recipe for sourdough bread with a long cold ferment".to_string(),
+        ),
+    ] {
+        let path = temp_dir.path().join(name);
+        fs::write(&path, &text).unwrap();
+        handlers::handle_did_open(
+            state,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: Url::from_file_path(&path).unwrap(),
+                    language_id: "markdown".to_string(),
+                    version: 0,
+                    text,
+                },
+            },
+        )
+        .await;
+    }
+
+    let result = backend
+        .handle_execute_command(ExecuteCommandParams {
+            command: "dendrite/findDuplicates".to_string(),
+            arguments: vec![serde_json::json!({ "threshold": 0.7 })],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let clusters = result["clusters"].as_array().unwrap();
+    assert_eq!(clusters.len(), 1);
+    let keys: Vec<_> = clusters[0]["notes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|note| note["key"].as_str().unwrap())
+        .collect();
+    assert_eq!(keys, vec!["meeting", "meeting-copy"]);
+    assert!(clusters[0]["shared_ratio"].as_f64().unwrap() >= 0.7);
+
+    let invalid = backend
+        .handle_execute_command(ExecuteCommandParams {
+            command: "dendrite/findDuplicates".to_string(),
+            arguments: vec![serde_json::json!({ "threshold": 2.0 })],
+            ..Default::default()
+        })
+        .await;
+    assert!(invalid.is_err());
+}
+
+#[tokio::test]
+async fn test_lsp_rename_with_unsaved_buffer() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    let old_path = temp_dir.path().join("old_note.md");
+    fs::write(&old_path, "# Old Note").unwrap();
+    let source_path = temp_dir.path().join("source.md");
+    fs::write(&source_path, "Link to [[old_note]]").unwrap();
+    let source_uri = Url::from_file_path(&source_path).unwrap();
+
+    // The buffer has edits that are not saved yet
+    handlers::handle_did_open(
+        state,
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: source_uri.clone(),
+                language_id: "markdown".to_string(),
+                version: 0,
+                text: "Unsaved line\nLink to [[old_note]]".to_string(),
+            },
+        },
+    )
+    .await;
+    {
+        let mut engine = state.engine.write().await;
+        engine
+            .as_mut()
+            .unwrap()
+            .update_content(old_path.clone(), "# Old Note");
+    }
+
+    let edit = handlers::rename::handle_rename(
+        client,
+        state,
+        RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&old_path).unwrap(),
+                },
+                position: Position::default(),
+            },
+            new_name: "new_note".to_string(),
+            work_done_progress_params: Default::default(),
+        },
+    )
+    .await
+    .expect("Plan is checked against the buffer it was computed from")
+    .expect("Rename should return edits");
+
+    let Some(DocumentChanges::Operations(ops)) = edit.document_changes else {
+        panic!("Expected DocumentChanges::Operations");
+    };
+    let link_edit = ops
+        .iter()
+        .find_map(|op| match op {
+            DocumentChangeOperation::Edit(edit) if edit.text_document.uri == source_uri => {
+                Some(edit)
+            }
+            _ => None,
+        })
+        .expect("Should update the link in the buffer");
+    let OneOf::Left(text_edit) = &link_edit.edits[0] else {
+        panic!("Expected standard TextEdit");
+    };
+    assert_eq!(text_edit.new_text, "[[new_note]]");
+    assert_eq!(text_edit.range.start, Position::new(1, 8));
+}
+
+#[tokio::test]
+async fn test_lsp_mutation_history_persisted_and_checked() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri.clone()))
+        .await
+        .unwrap();
+
+    let old_path = temp_dir.path().join("old_note.md");
+    fs::write(&old_path, "# Old Note").unwrap();
+    let source_path = temp_dir.path().join("source.md");
+    fs::write(&source_path, "Link to [[old_note]]").unwrap();
+    {
+        let mut engine = state.engine.write().await;
+        let engine = engine.as_mut().unwrap();
+        engine.update_content(old_path.clone(), "# Old Note");
+        engine.update_content(source_path.clone(), "Link to [[old_note]]");
+    }
+
+    let new_path = temp_dir.path().join("new_note.md");
+    handlers::handle_will_rename_files(
+        state,
+        RenameFilesParams {
+            files: vec![FileRename {
+                old_uri: Url::from_file_path(&old_path).unwrap().to_string(),
+                new_uri: Url::from_file_path(&new_path).unwrap().to_string(),
+            }],
+        },
+    )
+    .await
+    .unwrap()
+    .expect("Rename should update links");
+
+    // The client may still decline the edit: nothing is recorded
+    let list = handlers::handle_list_history_command(state)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(list["undo"].as_array().unwrap().is_empty());
+    assert!(!temp_dir.path().join(".dendrite/history.json").exists());
+
+    // Record it the way an applied "dendrite/..." command is
+    let plan = {
+        let engine = state.engine.read().await;
+        engine
+            .as_ref()
+            .unwrap()
+            .will_rename_files(&[(old_path.clone(), new_path)])
+            .unwrap()
+    };
+    let undo = handlers::undo_plan(state, &plan).await.unwrap();
+    handlers::push_history(state, &plan, undo).await;
+
+    let list = handlers::handle_list_history_command(state)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(list["undo"].as_array().unwrap().len(), 1);
+    assert_eq!(list["undo"][0]["description"], "Move note: source.md");
+    assert!(list["redo"].as_array().unwrap().is_empty());
+
+    // The history survives a restart
+    assert!(temp_dir.path().join(".dendrite/history.json").exists());
+    let (backend2, _) = setup_test_context().await;
+    handlers::handle_initialize(
+        &backend2.client,
+        &backend2.state,
+        create_initialize_params(root_uri),
+    )
+    .await
+    .unwrap();
+    let list = handlers::handle_list_history_command(&backend2.state)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(list["undo"].as_array().unwrap().len(), 1);
+
+    // The client never applied the edit: undo refuses and keeps the entry
+    let error = handlers::handle_undo_mutation(client, state)
+        .await
+        .unwrap_err();
+    assert!(error.message.starts_with("Stale edit plan:"));
+    let list = handlers::handle_list_history_command(state)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(list["undo"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_lsp_preview_edit_plan_command() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    let notes = [
+        ("proj.md", "# Project"),
+        ("proj.alpha.md", "# Alpha"),
+        ("index.md", "See [[proj.alpha]]\n"),
+    ];
+    {
+        let mut engine = state.engine.write().await;
+        let engine = engine.as_mut().unwrap();
+        for (name, content) in notes {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            engine.update_content(path, content);
+        }
     }
+
+    let preview = |command: &str, arguments: serde_json::Value| ExecuteCommandParams {
+        command: "dendrite/previewEditPlan".to_string(),
+        arguments: vec![serde_json::json!({ "command": command, "arguments": arguments })],
+        work_done_progress_params: Default::default(),
+    };
+
+    let result = handlers::handle_preview_edit_plan_command(
+        state,
+        preview(
+            "dendrite/reorganizeHierarchy",
+            serde_json::json!(["proj", "work"]),
+        ),
+    )
+    .await
+    .unwrap()
+    .expect("Reorganizing should produce a plan");
+    let diff = result["diff"].as_str().unwrap();
+    assert!(diff.contains("rename from proj.alpha.md\nrename to work.alpha.md"));
+    assert!(diff.contains("-See [[proj.alpha]]\n+See [[work.alpha]]"));
+    assert_eq!(result["insertions"], 1);
+    assert_eq!(result["deletions"], 1);
+    assert!(result["summary"].as_str().unwrap().contains("2 renamed"));
+
+    // Nothing was applied
+    assert!(temp_dir.path().join("proj.alpha.md").exists());
+
+    let error = handlers::handle_preview_edit_plan_command(
+        state,
+        preview("dendrite/splitNote", serde_json::json!([])),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.code, tower_lsp::jsonrpc::ErrorCode::InvalidParams);
+
+    // Destructive plans ask the client for confirmation, if it supports that
+    let plan = {
+        let engine = state.engine.read().await;
+        engine.as_ref().unwrap().delete_note("proj.alpha").unwrap()
+    };
+    let edit = crate::conversion::edit_plan_to_workspace_edit(plan.clone(), true);
+    let annotations = edit.change_annotations.expect("Delete should be annotated");
+    assert!(annotations
+        .values()
+        .all(|annotation| annotation.needs_confirmation == Some(true)));
+
+    assert!(!state.supports_change_annotations().await);
+    let edit = crate::conversion::edit_plan_to_workspace_edit(plan, false);
+    assert!(edit.change_annotations.is_none());
+    let json = serde_json::to_string(&edit).unwrap();
+    assert!(!json.contains("annotationId"));
+
+    // Support is taken from the capabilities sent with "initialize"
+    let mut params = create_initialize_params(Url::from_file_path(temp_dir.path()).unwrap());
+    params.capabilities.workspace = Some(WorkspaceClientCapabilities {
+        workspace_edit: Some(WorkspaceEditClientCapabilities {
+            change_annotation_support: Some(Default::default()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    handlers::handle_initialize(client, state, params)
+        .await
+        .unwrap();
+    assert!(state.supports_change_annotations().await);
+}
+
+#[tokio::test]
+async fn test_lsp_refactor_hierarchy_command() {
+    let (backend, temp_dir) = setup_test_context().await;
+    let client = &backend.client;
+    let state = &backend.state;
+
+    let root_uri = Url::from_file_path(temp_dir.path()).unwrap();
+    handlers::handle_initialize(client, state, create_initialize_params(root_uri))
+        .await
+        .unwrap();
+
+    {
+        let mut engine = state.engine.write().await;
+        let engine = engine.as_mut().unwrap();
+        for key in ["daily.journal.2024.01", "daily.journal.2024.02"] {
+            let path = temp_dir.path().join(format!("{}.md", key));
+            fs::write(&path, "# Day").unwrap();
+            engine.update_content(path, "# Day");
+        }
+    }
+
+    let refactor = |replacement: &str| ExecuteCommandParams {
+        command: "dendrite/refactorHierarchy".to_string(),
+        arguments: vec![serde_json::json!({
+            "pattern": r"^daily\.journal\.(\d+)\.(\d+)",
+            "replacement": replacement,
+            "dry_run": true,
+        })],
+        work_done_progress_params: Default::default(),
+    };
+
+    let result =
+        handlers::handle_refactor_hierarchy_command(client, state, refactor("journal.$1.$2"))
+            .await
+            .unwrap()
+            .unwrap();
+    assert_eq!(result["applied"], false);
+    assert_eq!(result["mappings"][0]["old_key"], "daily.journal.2024.01");
+    assert_eq!(result["mappings"][0]["new_key"], "journal.2024.01");
+    assert!(result["collisions"].as_array().unwrap().is_empty());
+
+    let result = handlers::handle_refactor_hierarchy_command(client, state, refactor("journal.$1"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        result["collisions"][0],
+        "'daily.journal.2024.01' and 'daily.journal.2024.02' would both become 'journal.2024'."
+    );
+    assert!(temp_dir.path().join("daily.journal.2024.01.md").exists());
 }